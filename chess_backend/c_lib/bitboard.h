@@ -14,11 +14,13 @@ typedef struct piece_map_bitboards
   U64 rooks;
 } piece_map_bitboards;
 
+// File (0-7) of the rook that each side can castle with, or -1 if the castling right has been lost.
+// Storing the file rather than a flag allows for Chess960 positions.
 typedef struct castling_rights {
-  bool white_king;
-  bool white_queen;
-  bool black_king;
-  bool black_queen;
+  int white_king;
+  int white_queen;
+  int black_king;
+  int black_queen;
 } castling_rights;
 
 typedef struct bitboard_base
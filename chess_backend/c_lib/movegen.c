@@ -102,5 +102,12 @@ U64 generateAttackTargets(piece_map_bitboards pieces, int colour,
     res |= pawnAttackTargets(pawn_arr->squares[i], colour);
   }
 
+  free(king_arr);
+  free(queen_arr);
+  free(bishop_arr);
+  free(knight_arr);
+  free(rook_arr);
+  free(pawn_arr);
+
   return res;
 }
@@ -47,7 +47,7 @@ U64 mask_king_attacks(int square) {
 
 // init pre-calculated attack tables for leaper pieces (pawns, knights, kings)
 void init_king_attacks() {
-  printf("Generating King Targets\n");
+  fprintf(stderr, "Generating King Targets\n");
   // loop over 64 board squares
   for (int square = 0; square < 64; square++) {
     king_attacks[square] = mask_king_attacks(square);
@@ -81,7 +81,7 @@ U64 mask_knight_attacks(int square) {
 
 // init pre-calculated attack tables for leaper pieces (pawns, knights, kings)
 void init_knight_attacks() {
-  printf("Generating Knight Targets\n");
+  fprintf(stderr, "Generating Knight Targets\n");
   // loop over 64 board squares
   for (int square = 0; square < 64; square++) {
     // init leaper attacks
@@ -71,7 +71,7 @@ U64 mask_pawn_targets(int side, int square) {
 
 // init pre-calculated attack tables for leaper pieces (pawns, knights, kings)
 void init_pawn_attacks() {
-  printf("Generating Pawn Attack Targets\n");
+  fprintf(stderr, "Generating Pawn Attack Targets\n");
   // loop over 64 board squares
   for (int square = 0; square < 64; square++) {
     // init pawn attacks
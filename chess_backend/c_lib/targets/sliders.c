@@ -344,7 +344,7 @@ U64 get_rook_attacks(int square, U64 occupancy) {
 }
 
 void init_sliders() {
-  printf("Generating Slider Targets\n");
+  fprintf(stderr, "Generating Slider Targets\n");
   // init attack tables
   init_sliders_attacks(bishop);
   init_sliders_attacks(rook);
@@ -0,0 +1,126 @@
+// Queries about which squares are attacked and by which pieces
+use crate::{
+    bishopTargets, generateAttackTargets, kingTargets, knightTargets, pawnAttackTargets,
+    queenTargets, rookTargets, utils::squares::Square, BitBoard, Board, Colour, Piece,
+};
+
+impl Board {
+    // Pieces of both sides (among `occupancy`) attacking the square. Sliding pieces are blocked by
+    // `occupancy`, so removing a piece from it reveals the pieces behind it (x-rays).
+    pub(crate) fn attackers_with_occupancy(&self, square: i32, occupancy: u64) -> u64 {
+        let (white, black) = (self.base.white, self.base.black);
+        let diagonal = white.bishops | white.queens | black.bishops | black.queens;
+        let straight = white.rooks | white.queens | black.rooks | black.queens;
+        unsafe {
+            // A pawn attacks the square if a pawn of the other colour on the square would attack it
+            let pawns = pawnAttackTargets(square, Colour::Black.as_int()) & white.pawns
+                | pawnAttackTargets(square, Colour::White.as_int()) & black.pawns;
+            (pawns
+                | knightTargets(square) & (white.knights | black.knights)
+                | kingTargets(square) & (white.king | black.king)
+                | bishopTargets(square, occupancy) & diagonal
+                | rookTargets(square, occupancy) & straight)
+                & occupancy
+        }
+    }
+
+    fn occupancy(&self) -> u64 {
+        self.base.white_occupied | self.base.black_occupied
+    }
+
+    /// Pieces of the given colour that attack the square
+    pub fn attackers_to(&self, square: Square, colour: Colour) -> BitBoard {
+        BitBoard(
+            self.attackers_with_occupancy(square.as_int(), self.occupancy())
+                & self.base.get_side_occupied(colour),
+        )
+    }
+
+    /// Pieces giving check to the king of the side to move
+    pub fn checkers(&self) -> BitBoard {
+        let colour = self.side_to_move();
+        match self.pieces(Piece::King(colour)).lsb() {
+            Some(king) => self.attackers_to(king, colour.other()),
+            None => BitBoard::EMPTY,
+        }
+    }
+
+    /// Pieces of the given colour that can not leave the line between their king and an
+    /// attacking rook, bishop or queen without exposing the king
+    pub fn pinned_pieces(&self, colour: Colour) -> BitBoard {
+        let side = self.base.get_side(colour);
+        let other = self.base.get_side(colour.other());
+        if side.king == 0 {
+            return BitBoard(0);
+        }
+        let king = side.king.trailing_zeros() as i32;
+        let occupancy = self.occupancy();
+        let own = self.base.get_side_occupied(colour);
+
+        // Sliders that would attack the king if there were no pieces of `colour` in the way
+        let other_occupied = self.base.get_side_occupied(colour.other());
+        let (straight, diagonal) = unsafe {
+            (
+                rookTargets(king, other_occupied) & (other.rooks | other.queens),
+                bishopTargets(king, other_occupied) & (other.bishops | other.queens),
+            )
+        };
+
+        let mut res = 0;
+        for (mut snipers, targets) in [
+            (
+                straight,
+                rookTargets as unsafe extern "C" fn(i32, u64) -> u64,
+            ),
+            (diagonal, bishopTargets),
+        ] {
+            while snipers != 0 {
+                let sniper = snipers.trailing_zeros() as i32;
+                // The squares strictly between the king and the sniper
+                let between =
+                    unsafe { targets(king, 1 << sniper) & targets(sniper, 1 << king) } & occupancy;
+                if between.count_ones() == 1 && between & own != 0 {
+                    res |= between;
+                }
+                snipers &= snipers - 1;
+            }
+        }
+        BitBoard(res)
+    }
+
+    /// Every square attacked by a piece of the given colour
+    pub fn attacked_squares(&self, colour: Colour) -> BitBoard {
+        BitBoard(unsafe {
+            generateAttackTargets(
+                self.base.get_side(colour),
+                colour.as_int(),
+                self.occupancy(),
+            )
+        })
+    }
+
+    /// Whether a piece of the given colour attacks the square
+    pub fn is_square_attacked(&self, square: Square, colour: Colour) -> bool {
+        self.attackers_to(square, colour).0 != 0
+    }
+
+    /// Squares attacked by the piece on the given square, including squares occupied by pieces
+    /// of the same colour (the pieces it defends). Pawns only attack diagonally.
+    pub fn attacks_from(&self, square: Square) -> BitBoard {
+        let occupancy = self.occupancy();
+        let index = square.as_int();
+        BitBoard(match self.piece_on(square) {
+            None => 0,
+            Some(piece) => unsafe {
+                match piece {
+                    Piece::Pawn(colour) => pawnAttackTargets(index, colour.as_int()),
+                    Piece::King(_) => kingTargets(index),
+                    Piece::Queen(_) => queenTargets(index, occupancy),
+                    Piece::Bishop(_) => bishopTargets(index, occupancy),
+                    Piece::Knight(_) => knightTargets(index),
+                    Piece::Rook(_) => rookTargets(index, occupancy),
+                }
+            },
+        })
+    }
+}
@@ -0,0 +1,202 @@
+// Bitboard wrapper with set operations and square iteration
+use crate::utils::squares::{File, Rank, Square};
+use std::{
+    fmt::Display,
+    ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Not},
+};
+
+const FILE_A: u64 = 0x0101010101010101;
+const RANK_1: u64 = 0xff;
+
+/// Set of squares, with bit n standing for the square with number n (a1 = 0 to h8 = 63)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct BitBoard(pub u64);
+
+/// The eight directions a bitboard can be shifted in, with north pointing towards the eighth rank
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    North,
+    South,
+    East,
+    West,
+    NorthEast,
+    NorthWest,
+    SouthEast,
+    SouthWest,
+}
+
+impl BitBoard {
+    pub const EMPTY: Self = Self(0);
+    pub const FULL: Self = Self(u64::MAX);
+
+    /// All squares on the given file
+    pub const fn file(file: File) -> Self {
+        Self(FILE_A << file as u32)
+    }
+    /// All squares on the given rank
+    pub const fn rank(rank: Rank) -> Self {
+        Self(RANK_1 << (rank as u32 * 8))
+    }
+
+    // Ranks should be ordered 8-1 and squares in rank should be in order a-h
+    pub fn from_ranks(mut ranks: [[u8; 8]; 8]) -> Self {
+        let mut res = 0;
+        let mut exp = 0;
+        ranks.reverse();
+        for rank in ranks {
+            for square in rank {
+                if square == 1 {
+                    // only calculated if sqare is not 0
+                    res += 2u64.pow(exp);
+                }
+                exp += 1;
+            }
+        }
+
+        Self(res)
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+    /// Number of squares in the set
+    pub const fn popcount(&self) -> u32 {
+        self.0.count_ones()
+    }
+    pub const fn contains(&self, square: Square) -> bool {
+        self.0 & square.bitboard() != 0
+    }
+    pub fn set(&mut self, square: Square) {
+        self.0 |= square.bitboard();
+    }
+    pub fn clear(&mut self, square: Square) {
+        self.0 &= !square.bitboard();
+    }
+
+    /// The square with the lowest number (closest to a1)
+    pub fn lsb(&self) -> Option<Square> {
+        (self.0 != 0).then(|| Square::from_index_unchecked(self.0.trailing_zeros() as i32))
+    }
+    /// Removes the square with the lowest number from the set and returns it
+    pub fn pop_lsb(&mut self) -> Option<Square> {
+        let res = self.lsb();
+        self.0 &= self.0.wrapping_sub(1);
+        res
+    }
+
+    /// Moves every square one step in the given direction. Squares that would leave the board
+    /// are dropped rather than wrapping around to the other side.
+    pub const fn shift(&self, direction: Direction) -> Self {
+        let not_a = self.0 & !FILE_A;
+        let not_h = self.0 & !(FILE_A << 7);
+        Self(match direction {
+            Direction::North => self.0 << 8,
+            Direction::South => self.0 >> 8,
+            Direction::East => not_h << 1,
+            Direction::West => not_a >> 1,
+            Direction::NorthEast => not_h << 9,
+            Direction::NorthWest => not_a << 7,
+            Direction::SouthEast => not_h >> 7,
+            Direction::SouthWest => not_a >> 9,
+        })
+    }
+
+    /// Iterates over the squares in the set, from a1 to h8, without allocating
+    pub fn iter(&self) -> Squares {
+        Squares(*self)
+    }
+}
+
+/// Iterator over the squares of a `BitBoard`, see `BitBoard::iter`
+#[derive(Debug, Clone)]
+pub struct Squares(BitBoard);
+impl Iterator for Squares {
+    type Item = Square;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop_lsb()
+    }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.0.popcount() as usize;
+        (n, Some(n))
+    }
+}
+impl ExactSizeIterator for Squares {}
+
+impl IntoIterator for BitBoard {
+    type Item = Square;
+    type IntoIter = Squares;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+impl FromIterator<Square> for BitBoard {
+    fn from_iter<T: IntoIterator<Item = Square>>(iter: T) -> Self {
+        let mut res = Self::EMPTY;
+        for square in iter {
+            res.set(square);
+        }
+        res
+    }
+}
+
+impl From<Square> for BitBoard {
+    fn from(value: Square) -> Self {
+        Self(value.bitboard())
+    }
+}
+impl From<u64> for BitBoard {
+    fn from(value: u64) -> Self {
+        Self(value)
+    }
+}
+impl From<BitBoard> for u64 {
+    fn from(value: BitBoard) -> Self {
+        value.0
+    }
+}
+
+macro_rules! impl_bit_operator {
+    ($($trait:ident::$function:ident, $assign_trait:ident::$assign_function:ident, $op:tt;)*) => {
+        $(
+            impl $trait for BitBoard {
+                type Output = Self;
+                fn $function(self, rhs: Self) -> Self::Output {
+                    Self(self.0 $op rhs.0)
+                }
+            }
+            impl $assign_trait for BitBoard {
+                fn $assign_function(&mut self, rhs: Self) {
+                    self.0 = self.0 $op rhs.0;
+                }
+            }
+        )*
+    };
+}
+impl_bit_operator!(
+    BitAnd::bitand, BitAndAssign::bitand_assign, &;
+    BitOr::bitor, BitOrAssign::bitor_assign, |;
+    BitXor::bitxor, BitXorAssign::bitxor_assign, ^;
+);
+impl Not for BitBoard {
+    type Output = Self;
+    fn not(self) -> Self::Output {
+        Self(!self.0)
+    }
+}
+
+impl Display for BitBoard {
+    /// The board from White's side, with the eighth rank at the top
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for rank in Rank::ALL.iter().rev() {
+            for file in File::ALL {
+                if self.contains(Square::new(file, *rank)) {
+                    write!(f, "1 ")?;
+                } else {
+                    write!(f, ". ")?;
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
@@ -1,30 +1,54 @@
 use crate::{
     bishopTargets, bitboard_base, castling_rights, createBase, generateAttackTargets, kingTargets,
     knightTargets, pawnAttackTargets, pawnTargets, piece_map_bitboards, queenTargets, rookTargets,
-    utils::{extract_squares, squares::*, Colour, Piece, Pieces},
-    START_POSITION,
+    utils::{
+        squares::*,
+        zobrist::{pieces_diff_key, pieces_key, ZOBRIST_KEYS},
+        ChessError, Colour, Piece, Pieces,
+    },
+    BitBoard, PositionError, START_POSITION,
 };
 use core::panic;
-use std::fmt::Display;
+use std::{
+    fmt::Display,
+    hash::{Hash, Hasher},
+};
+
+// Bitboard of the light squares (b1, d1, ..., a2, c2, ...)
+const LIGHT_SQUARES: u64 = 0x55AA55AA55AA55AA;
+
+// Squares from `a` to `b` (inclusive) on the same rank
+fn squares_between(a: i32, b: i32) -> u64 {
+    let (low, high) = (a.min(b), a.max(b));
+    (1u64 << high << 1).wrapping_sub(1 << low)
+}
 
 type TargetFunction = Box<dyn Fn(i32) -> u64>;
 type MutateFunction = Box<dyn Fn(&mut Board)>;
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum Side {
     King,
     Queen,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum MoveType {
     Standard,
+    /// Castling moves have the king's square as starting square and the castling rook's square
+    /// as destination square (as the king "captures" its own rook in Chess960 notation)
     Castling(Side),
     EnPassent,
     Promotion(Piece),
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum GameState {
     // in order to avoid generating moves multiple times, the moves are inherited
     // with the "Ongoing" state.
@@ -33,22 +57,35 @@ pub enum GameState {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum FinishedState {
     Win(Colour, ReasonWin),
     Draw(ReasonDraw),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum ReasonWin {
     Checkmate,
     Resignation,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum ReasonDraw {
     Stalemate,
     InsufficientMaterial,
+    /// Fifty move rule, 100 halfmoves without a capture or pawn move (a draw that can be claimed)
     HalfmoveLimit,
+    /// Seventy-five move rule, 150 halfmoves without a capture or pawn move (an automatic draw)
+    SeventyFiveMoveRule,
+    /// Same position reached for the third time (a draw that can be claimed)
+    ThreefoldRepetition,
+    /// Same position reached for the fifth time (an automatic draw)
+    FivefoldRepetition,
     Agreement,
 }
 
@@ -60,7 +97,7 @@ impl bitboard_base {
         }
     }
 
-    fn get_side_occupied(&self, colour: Colour) -> u64 {
+    pub(crate) fn get_side_occupied(&self, colour: Colour) -> u64 {
         match colour {
             Colour::White => self.white_occupied,
             Colour::Black => self.black_occupied,
@@ -136,66 +173,28 @@ impl bitboard_base {
         target_function: TargetFunction,
     ) -> Vec<(ChessMoveBase, Option<MutateFunction>)> {
         let mut res = Vec::new();
-        for square in extract_squares(bitboard) {
+        for square in BitBoard(bitboard).iter().map(|s| s.as_int()) {
             let side_occupied = self.get_side_occupied(colour);
-            for target in
-                extract_squares((target_function(square) ^ side_occupied) & !side_occupied)
+            for target in BitBoard((target_function(square) ^ side_occupied) & !side_occupied)
+                .iter()
+                .map(|s| s.as_int())
             {
                 let res_board = self.make_move(piece, 1 << square, 1 << target, colour, None);
-                let f =
-                    if piece == Piece::Pawn(Colour::White) && target - square == 16 {
-                        Some(Box::new(move |b: &mut Board| b.killer_square = square + 8)
-                            as MutateFunction)
-                    } else if piece == Piece::Pawn(Colour::Black) && square - target == 16 {
-                        Some(Box::new(move |b: &mut Board| b.killer_square = square - 8)
-                            as MutateFunction)
-                    } else if let Piece::King(c) = piece {
-                        match c {
-                            Colour::White => Some(Box::new(|b: &mut Board| {
-                                b.castling_rights.white_king = false;
-                                b.castling_rights.white_queen = false;
-                            }) as MutateFunction),
-                            Colour::Black => Some(Box::new(|b: &mut Board| {
-                                b.castling_rights.black_king = false;
-                                b.castling_rights.black_queen = false;
-                            }) as MutateFunction),
-                        }
-                    } else if let Piece::Rook(c) = piece {
-                        match c {
-                            Colour::White => {
-                                if square == a1 {
-                                    Some(Box::new(|b: &mut Board| {
-                                        b.castling_rights.white_queen = false
-                                    }) as MutateFunction)
-                                } else if square == h1 {
-                                    Some(Box::new(|b: &mut Board| {
-                                        b.castling_rights.white_king = false
-                                    }) as MutateFunction)
-                                } else {
-                                    None
-                                }
-                            }
-                            Colour::Black => {
-                                if square == a8 {
-                                    Some(Box::new(|b: &mut Board| {
-                                        b.castling_rights.black_queen = false
-                                    }) as MutateFunction)
-                                } else if square == h8 {
-                                    Some(Box::new(|b: &mut Board| {
-                                        b.castling_rights.black_king = false
-                                    }) as MutateFunction)
-                                } else {
-                                    None
-                                }
-                            }
-                        }
-                    } else {
-                        None
-                    };
+                let f = if piece == Piece::Pawn(Colour::White) && target - square == 16 {
+                    Some(Box::new(move |b: &mut Board| {
+                        b.set_killer_square(Square::from_index(square + 8))
+                    }) as MutateFunction)
+                } else if piece == Piece::Pawn(Colour::Black) && square - target == 16 {
+                    Some(Box::new(move |b: &mut Board| {
+                        b.set_killer_square(Square::from_index(square - 8))
+                    }) as MutateFunction)
+                } else {
+                    None
+                };
                 res.push((
                     ChessMoveBase {
-                        starting_square: Some(square),
-                        destination_square: Some(target),
+                        starting_square: Square::from_index(square),
+                        destination_square: Square::from_index(target),
                         piece,
                         move_type: MoveType::Standard,
                         colour,
@@ -208,120 +207,105 @@ impl bitboard_base {
         res
     }
 
-    fn get_pseudo_legal_castling(
+    pub(crate) fn get_pseudo_legal_castling(
         &self,
         colour: Colour,
+        castling_rights: castling_rights,
     ) -> Vec<(ChessMoveBase, Option<MutateFunction>)> {
-        let (king_side, queen_side, mutate): (
-            bitboard_base,
-            bitboard_base,
-            (MutateFunction, MutateFunction),
-        ) = match colour {
-            Colour::White => {
-                let mut king_side = self.white;
-                let mut queen_side = self.white;
-                king_side.king ^= 80;
-                king_side.rooks ^= 160;
-
-                queen_side.king ^= 20;
-                queen_side.rooks ^= 9;
-
-                let mutate = (
-                    Box::new(|b: &mut Board| {
-                        b.castling_rights.white_king = false;
-                        b.castling_rights.white_queen = false;
-                    }) as MutateFunction,
-                    Box::new(|b: &mut Board| {
-                        b.castling_rights.white_king = false;
-                        b.castling_rights.white_queen = false;
-                    }) as MutateFunction,
-                );
-                unsafe {
-                    (
-                        createBase(king_side, self.black),
-                        createBase(queen_side, self.black),
-                        mutate,
-                    )
-                }
+        let mut res = Vec::new();
+        let side = self.get_side(colour);
+        let back_rank = match colour {
+            Colour::White => 0,
+            Colour::Black => 56,
+        };
+        let king = side.king.trailing_zeros() as i32;
+        if side.king == 0 || king / 8 != back_rank / 8 {
+            return res;
+        }
+
+        let occupancy = self.white_occupied | self.black_occupied;
+        let mut other_attacks = None;
+        for castling_side in [Side::King, Side::Queen] {
+            let file = castling_rights.file(colour, castling_side);
+            if file < 0 || side.rooks & 1 << (back_rank + file) == 0 {
+                continue;
             }
-            Colour::Black => {
-                let mut king_side = self.black;
-                let mut queen_side = self.black;
-                king_side.king ^= 5764607523034234880;
-                king_side.rooks ^= 11529215046068469760;
-
-                queen_side.king ^= 1441151880758558720;
-                queen_side.rooks ^= 648518346341351424;
-
-                let mutate = (
-                    Box::new(|b: &mut Board| {
-                        b.castling_rights.black_king = false;
-                        b.castling_rights.black_queen = false;
-                    }) as MutateFunction,
-                    Box::new(|b: &mut Board| {
-                        b.castling_rights.black_king = false;
-                        b.castling_rights.black_queen = false;
-                    }) as MutateFunction,
-                );
-                unsafe {
-                    (
-                        createBase(self.white, king_side),
-                        createBase(self.white, queen_side),
-                        mutate,
-                    )
-                }
+            let rook = back_rank + file;
+            // The king and rook always end up on the same squares as in standard chess
+            let (king_dest, rook_dest) = match castling_side {
+                Side::King => (back_rank + 6, back_rank + 5),
+                Side::Queen => (back_rank + 2, back_rank + 3),
+            };
+
+            // Apart from the king and the castling rook, the squares that either of them passes
+            // over (including their destinations) must be empty
+            let king_path = squares_between(king, king_dest);
+            let rook_path = squares_between(rook, rook_dest);
+            if (king_path | rook_path) & occupancy & !(1 << king | 1 << rook) != 0 {
+                continue;
+            }
+            // The king may not castle out of, through or into check
+            let attacks = *other_attacks.get_or_insert_with(|| unsafe {
+                generateAttackTargets(
+                    self.get_side(colour.other()),
+                    colour.other().as_int(),
+                    occupancy,
+                )
+            });
+            if king_path & attacks != 0 {
+                continue;
             }
-        };
 
-        vec![
-            (
-                ChessMoveBase {
-                    starting_square: None,
-                    destination_square: None,
-                    piece: Piece::King(colour),
-                    move_type: MoveType::Castling(Side::King),
-                    colour,
-                    res_board: king_side,
-                },
-                Some(mutate.0),
-            ),
-            (
+            let mut res_side = side;
+            res_side.king = 1 << king_dest;
+            res_side.rooks = (side.rooks & !(1 << rook)) | 1 << rook_dest;
+            let res_board = unsafe {
+                match colour {
+                    Colour::White => createBase(res_side, self.black),
+                    Colour::Black => createBase(self.white, res_side),
+                }
+            };
+            res.push((
                 ChessMoveBase {
-                    starting_square: None,
-                    destination_square: None,
+                    starting_square: Square::from_index(king),
+                    destination_square: Square::from_index(rook),
                     piece: Piece::King(colour),
-                    move_type: MoveType::Castling(Side::Queen),
+                    move_type: MoveType::Castling(castling_side),
                     colour,
-                    res_board: queen_side,
+                    res_board,
                 },
-                Some(mutate.1),
-            ),
-        ]
+                None,
+            ));
+        }
+        res
     }
 
     fn get_enpassent_move(
         &self,
         start_square: i32,
-        killer_square: i32,
+        killer_square: Square,
         colour: Colour,
     ) -> (ChessMoveBase, Option<MutateFunction>) {
         let mut white = self.white;
         let mut black = self.black;
+        let captured = killer_square
+            .backward(colour)
+            .expect("Killer square should not be on a back rank");
         match colour {
             Colour::White => {
-                white.pawns ^= (1 << start_square) + (1 << killer_square);
-                black.pawns ^= 1 << (killer_square - 8);
+                white.pawns ^= (1 << start_square) | killer_square.bitboard();
+                black.pawns ^= captured.bitboard();
             }
             Colour::Black => {
-                black.pawns ^= (1 << start_square) + (1 << killer_square);
-                white.pawns ^= 1 << (killer_square + 8);
+                black.pawns ^= (1 << start_square) | killer_square.bitboard();
+                white.pawns ^= captured.bitboard();
             }
         };
         unsafe {
             let res_board = createBase(white, black);
             (
                 ChessMoveBase {
-                    starting_square: Some(start_square),
+                    starting_square: Square::from_index(start_square),
                     destination_square: Some(killer_square),
                     piece: Piece::Pawn(colour),
                     move_type: MoveType::EnPassent,
@@ -341,10 +325,11 @@ impl bitboard_base {
         occupancy: u64,
         res: &mut Vec<(ChessMoveBase, Option<MutateFunction>)>,
     ) {
-        for starting_square in extract_squares(pawns) {
+        for starting_square in BitBoard(pawns).iter().map(|s| s.as_int()) {
             unsafe {
-                for target in
-                    extract_squares(pawnTargets(starting_square, colour.as_int(), occupancy))
+                for target in BitBoard(pawnTargets(starting_square, colour.as_int(), occupancy))
+                    .iter()
+                    .map(|s| s.as_int())
                 {
                     if 1u64 << target & back_rank != 0 {
                         for p in [
@@ -355,8 +340,8 @@ impl bitboard_base {
                         ] {
                             res.push((
                                 ChessMoveBase {
-                                    starting_square: Some(starting_square),
-                                    destination_square: Some(target),
+                                    starting_square: Square::from_index(starting_square),
+                                    destination_square: Square::from_index(target),
                                     piece: Piece::Pawn(colour),
                                     move_type: MoveType::Promotion(p),
                                     colour,
@@ -395,7 +380,8 @@ impl bitboard_base {
     pub fn get_pseudo_legal_moves(
         &self,
         colour: Colour,
-        killer_square: i32,
+        killer_square: Option<Square>,
+        castling_rights: castling_rights,
     ) -> Vec<(ChessMoveBase, Option<MutateFunction>)> {
         let occupancy = self.white_occupied + self.black_occupied;
         let mut res = Vec::new();
@@ -409,10 +395,13 @@ impl bitboard_base {
             Self::pawn_target_function(colour, occupancy),
         ));
         // En passent is only handled if the board has a killer square
-        if killer_square >= 0 {
+        if let Some(killer_square) = killer_square {
             unsafe {
-                for square in extract_squares(self.get_side(colour).pawns) {
-                    if 1 << killer_square as u32 & pawnAttackTargets(square, colour.as_int()) != 0 {
+                for square in BitBoard(self.get_side(colour).pawns)
+                    .iter()
+                    .map(|s| s.as_int())
+                {
+                    if killer_square.bitboard() & pawnAttackTargets(square, colour.as_int()) != 0 {
                         res.push(self.get_enpassent_move(square, killer_square, colour));
                     }
                 }
@@ -426,8 +415,8 @@ impl bitboard_base {
             self.get_side(colour).king,
             Self::king_target_function(),
         ));
-        // castling is always generated as pseudo-legal and might be removed when checking legality
-        res.append(&mut self.get_pseudo_legal_castling(colour));
+        // castling is only generated if the path of the king and rook is free and safe
+        res.append(&mut self.get_pseudo_legal_castling(colour, castling_rights));
 
         // --- Queen Moves ---
         res.append(&mut self.get_pseudo_legal_piece_moves(
@@ -486,20 +475,15 @@ impl bitboard_base {
 
 #[derive(Debug, Clone, Copy)]
 pub struct ChessMoveBase {
-    pub starting_square: Option<i32>,
-    pub destination_square: Option<i32>,
+    pub starting_square: Option<Square>,
+    pub destination_square: Option<Square>,
     pub piece: Piece,
     pub move_type: MoveType,
     pub colour: Colour,
     pub res_board: bitboard_base,
 }
 impl ChessMoveBase {
-    pub fn is_legal(
-        &self,
-        castling_rights: castling_rights,
-        occupancy: u64,
-        rooks: (u64, u64),
-    ) -> bool {
+    pub fn is_legal(&self) -> bool {
         let side = self.res_board.get_side(self.colour);
         let other_side = self.res_board.get_side(self.colour.other());
 
@@ -522,38 +506,6 @@ impl ChessMoveBase {
             if side.pawns + other_side.pawns & 18374686479671623935 != 0 {
                 return false;
             }
-            if let MoveType::Castling(castling_side) = self.move_type {
-                // When castling, the coresponding castling right must be true, there must be no
-                // pieces between the king and rook, the rooks must not have been captured, and the
-                // squares over which the king passes must not be attacked by enemy pieces
-                if !((castling_side == Side::King
-                    && self.colour == Colour::White
-                    && castling_rights.white_king
-                    && (96 & occupancy == 0)
-                    && (128 & rooks.0 != 0)
-                    && (112 & other_attacks == 0))
-                    || (castling_side == Side::Queen
-                        && self.colour == Colour::White
-                        && castling_rights.white_queen
-                        && (14 & occupancy == 0)
-                        && (1 & rooks.0 != 0)
-                        && (30 & other_attacks == 0))
-                    || (castling_side == Side::King
-                        && self.colour == Colour::Black
-                        && castling_rights.black_king
-                        && (6917529027641081856 & occupancy == 0)
-                        && (9223372036854775808 & rooks.1 != 0)
-                        && (8070450532247928832 & other_attacks == 0))
-                    || (castling_side == Side::Queen
-                        && self.colour == Colour::Black
-                        && castling_rights.black_queen
-                        && (1008806316530991104 & occupancy == 0)
-                        && (72057594037927936 & rooks.1 != 0)
-                        && (2017612633061982208 & other_attacks == 0)))
-                {
-                    return false;
-                }
-            }
             true
         }
     }
@@ -571,27 +523,56 @@ impl ChessMove {
     pub fn from_base(
         base: ChessMoveBase,
         board: &Board,
-        castling_rights: castling_rights,
-        occupancy: u64,
-        rooks: (u64, u64),
         mutate_function: Option<MutateFunction>,
     ) -> Option<Self> {
-        if base.is_legal(castling_rights, occupancy, rooks) {
+        if base.is_legal() {
             let mut new_board = *board;
+            // The killer square is rehashed once the new position is complete
+            new_board.hash ^= board.en_passent_hash();
             new_board.base = base.res_board;
+            // Only the squares that changed are rehashed
+            new_board.hash ^=
+                pieces_diff_key(Colour::White, board.base.white, base.res_board.white)
+                    ^ pieces_diff_key(Colour::Black, board.base.black, base.res_board.black);
             new_board.side_to_move = board.side_to_move.other();
-            new_board.halfmove += 1;
+            new_board.hash ^= ZOBRIST_KEYS.side;
+            // The halfmove clock is reset by captures and pawn moves (including promotions)
+            let other = base.colour.other();
+            if board.base.get_side_occupied(other) != base.res_board.get_side_occupied(other)
+                || board.base.get_side(base.colour).pawns
+                    != base.res_board.get_side(base.colour).pawns
+            {
+                new_board.halfmove = 0;
+            } else {
+                new_board.halfmove += 1;
+            }
             if base.colour == Colour::Black {
                 new_board.fullmove += 1;
             }
 
             // Reset killer square
-            new_board.killer_square = -1;
+            new_board.set_killer_square(None);
 
             if let Some(f) = mutate_function {
                 f(&mut new_board);
             }
 
+            // Castling rights are lost once the king or the castling rook has moved, or the rook
+            // has been captured
+            for colour in [Colour::White, Colour::Black] {
+                let side = new_board.base.get_side(colour);
+                // In Chess960 the king can castle without leaving its square
+                let moved_king = base.piece == Piece::King(colour);
+                let back_rank = if colour == Colour::White { 0 } else { 56 };
+                for castling_side in [Side::King, Side::Queen] {
+                    let file = new_board.castling_rights.file(colour, castling_side);
+                    if file >= 0 && (moved_king || side.rooks & 1 << (back_rank + file) == 0) {
+                        new_board.revoke_castling(colour, castling_side);
+                    }
+                }
+            }
+            new_board.hash ^= new_board.en_passent_hash();
+
             Some(Self {
                 base,
                 board: new_board,
@@ -612,6 +593,7 @@ impl PartialEq for piece_map_bitboards {
             && self.pawns == other.pawns
     }
 }
+impl Eq for piece_map_bitboards {}
 
 impl PartialEq for bitboard_base {
     fn eq(&self, other: &Self) -> bool {
@@ -621,6 +603,7 @@ impl PartialEq for bitboard_base {
             && self.black_occupied == other.black_occupied
     }
 }
+impl Eq for bitboard_base {}
 impl PartialEq for castling_rights {
     fn eq(&self, other: &Self) -> bool {
         self.white_king == other.white_king
@@ -629,44 +612,125 @@ impl PartialEq for castling_rights {
             && self.black_queen == other.black_queen
     }
 }
+impl Eq for castling_rights {}
+impl castling_rights {
+    /// No castling rights for either side
+    pub fn none() -> Self {
+        Self {
+            white_king: -1,
+            white_queen: -1,
+            black_king: -1,
+            black_queen: -1,
+        }
+    }
+    /// Index of the castling right, in the same order as `Board::castling_rights_as_arr`
+    pub(crate) fn index(colour: Colour, side: Side) -> usize {
+        match (colour, side) {
+            (Colour::White, Side::King) => 0,
+            (Colour::White, Side::Queen) => 1,
+            (Colour::Black, Side::King) => 2,
+            (Colour::Black, Side::Queen) => 3,
+        }
+    }
+    /// File of the rook for the castling right, or -1 if the right has been lost
+    pub fn file(&self, colour: Colour, side: Side) -> i32 {
+        self.as_arr()[Self::index(colour, side)]
+    }
+    pub fn set_file(&mut self, colour: Colour, side: Side, file: i32) {
+        *match (colour, side) {
+            (Colour::White, Side::King) => &mut self.white_king,
+            (Colour::White, Side::Queen) => &mut self.white_queen,
+            (Colour::Black, Side::King) => &mut self.black_king,
+            (Colour::Black, Side::Queen) => &mut self.black_queen,
+        } = file;
+    }
+    pub fn as_arr(&self) -> [i32; 4] {
+        [
+            self.white_king,
+            self.white_queen,
+            self.black_king,
+            self.black_queen,
+        ]
+    }
+}
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Board {
     pub base: bitboard_base,
-    killer_square: i32,
-    castling_rights: castling_rights,
-    side_to_move: Colour,
-    halfmove: i32,
-    fullmove: i32,
+    pub(crate) killer_square: Option<Square>,
+    pub(crate) castling_rights: castling_rights,
+    pub(crate) side_to_move: Colour,
+    pub(crate) halfmove: i32,
+    pub(crate) fullmove: i32,
+    // Zobrist key of the position, updated incrementally as moves are made
+    pub(crate) hash: u64,
+    // Only affects notation (castling in FEN and UCI), the rules are the same in both variants
+    chess960: bool,
 }
 impl Board {
+    /// Creates a board from its parts, rejecting positions that fail `Board::validate`
     pub fn new(
         base: bitboard_base,
-        killer_square: i32,
+        killer_square: Option<Square>,
+        castling_rights: castling_rights,
+        side_to_move: Colour,
+        halfmove: i32,
+        fullmove: i32,
+    ) -> Result<Self, PositionError> {
+        let res = Self::new_unchecked(
+            base,
+            killer_square,
+            castling_rights,
+            side_to_move,
+            halfmove,
+            fullmove,
+        );
+        res.validate()?;
+        Ok(res)
+    }
+    // Creates a board from its parts without checking that the position is valid
+    pub(crate) fn new_unchecked(
+        base: bitboard_base,
+        killer_square: Option<Square>,
         castling_rights: castling_rights,
         side_to_move: Colour,
         halfmove: i32,
         fullmove: i32,
     ) -> Self {
-        Self {
+        let mut res = Self {
             base,
             killer_square,
             castling_rights,
             side_to_move,
             halfmove,
             fullmove,
-        }
+            hash: 0,
+            chess960: false,
+        };
+        res.hash = res.calculate_hash();
+        res
     }
     pub fn side_to_move(&self) -> Colour {
         self.side_to_move
     }
     pub fn castling_rights_as_arr(&self) -> [bool; 4] {
-        [
-            self.castling_rights.white_king,
-            self.castling_rights.white_queen,
-            self.castling_rights.black_king,
-            self.castling_rights.black_queen,
-        ]
+        self.castling_rights.as_arr().map(|file| file >= 0)
+    }
+    pub fn castling_rights(&self) -> castling_rights {
+        self.castling_rights
+    }
+    /// File of the rook that the given side can castle with, if castling is still allowed
+    pub fn castling_rook_file(&self, colour: Colour, side: Side) -> Option<i32> {
+        let file = self.castling_rights.file(colour, side);
+        (file >= 0).then_some(file)
+    }
+    /// Whether castling moves are written the Chess960 way (king takes own rook in UCI, and
+    /// rook files in FEN when needed). Set automatically when parsing Chess960 FEN.
+    pub fn is_chess960(&self) -> bool {
+        self.chess960
+    }
+    pub fn set_chess960(&mut self, chess960: bool) {
+        self.chess960 = chess960;
     }
     pub fn fullmove(&self) -> i32 {
         self.fullmove
@@ -674,49 +738,116 @@ impl Board {
     pub fn halfmove(&self) -> i32 {
         self.halfmove
     }
-    pub fn killer_square(&self) -> i32 {
+    /// The square a pawn can capture en passent on
+    pub fn killer_square(&self) -> Option<Square> {
         self.killer_square
     }
-    pub fn view_bitboard(&self, colour: Colour, piece: Piece) {
-        let bitboard_maps = match colour {
-            Colour::White => self.base.white,
-            Colour::Black => self.base.black,
-        };
+    /// 64-bit Zobrist key of the position (pieces, side to move, castling rights and killer
+    /// square). The clocks are not part of the key, and neither is a killer square that no pawn
+    /// can take en passent on.
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
 
-        let map = match piece {
-            Piece::Pawn(_) => bitboard_maps.pawns,
-            Piece::King(_) => bitboard_maps.king,
-            Piece::Queen(_) => bitboard_maps.queens,
-            Piece::Bishop(_) => bitboard_maps.bishops,
-            Piece::Knight(_) => bitboard_maps.knights,
-            Piece::Rook(_) => bitboard_maps.rooks,
-        };
+    /// Computes the Zobrist key from scratch. Should always agree with `hash`.
+    pub(crate) fn calculate_hash(&self) -> u64 {
+        let mut res =
+            pieces_key(Colour::White, self.base.white) ^ pieces_key(Colour::Black, self.base.black);
+        if self.side_to_move == Colour::Black {
+            res ^= ZOBRIST_KEYS.side;
+        }
+        for (i, right) in self.castling_rights_as_arr().iter().enumerate() {
+            if *right {
+                res ^= ZOBRIST_KEYS.castling[i];
+            }
+        }
+        res ^ self.en_passent_hash()
+    }
+
+    // Leaves the hash to the caller, since whether the killer square is hashed depends on the
+    // rest of the position
+    pub(crate) fn set_killer_square(&mut self, killer_square: Option<Square>) {
+        self.killer_square = killer_square;
+    }
 
-        println!("{}", BitBoard(map));
+    pub(crate) fn revoke_castling(&mut self, colour: Colour, side: Side) {
+        if self.castling_rights.file(colour, side) >= 0 {
+            self.castling_rights.set_file(colour, side, -1);
+            self.hash ^= ZOBRIST_KEYS.castling[castling_rights::index(colour, side)];
+        }
+    }
+    /// Squares occupied by the given piece (of the piece's colour)
+    pub fn pieces(&self, piece: Piece) -> BitBoard {
+        let side = self.base.get_side(piece.colour());
+        BitBoard(match piece {
+            Piece::Pawn(_) => side.pawns,
+            Piece::King(_) => side.king,
+            Piece::Queen(_) => side.queens,
+            Piece::Bishop(_) => side.bishops,
+            Piece::Knight(_) => side.knights,
+            Piece::Rook(_) => side.rooks,
+        })
+    }
+    /// Squares occupied by pieces of the given colour
+    pub fn occupied(&self, colour: Colour) -> BitBoard {
+        BitBoard(self.base.get_side_occupied(colour))
+    }
+    pub fn view_bitboard(&self, colour: Colour, piece: Piece) {
+        println!("{}", self.pieces(piece.with_colour(colour)));
     }
 
     pub fn generate_legal_moves(&self) -> Vec<ChessMove> {
         let mut res = Vec::new();
-        let occupancy = self.base.black_occupied + self.base.white_occupied;
-
-        for (m, f) in self
-            .base
-            .get_pseudo_legal_moves(self.side_to_move, self.killer_square)
-        {
-            if let Some(legal_move) = ChessMove::from_base(
-                m,
-                self,
-                self.castling_rights,
-                occupancy,
-                (self.base.white.rooks, self.base.black.rooks),
-                f,
-            ) {
+        for (m, f) in self.base.get_pseudo_legal_moves(
+            self.side_to_move,
+            self.killer_square,
+            self.castling_rights,
+        ) {
+            if let Some(legal_move) = ChessMove::from_base(m, self, f) {
                 res.push(legal_move);
             }
         }
         res
     }
 
+    /// Why the move given by `input` is not legal, assuming that no legal move matches it.
+    /// `matches` tells whether a (pseudo-legal) move is the one given by `input`.
+    pub(crate) fn rejection(
+        &self,
+        input: &str,
+        matches: impl Fn(&ChessMoveBase) -> bool,
+    ) -> ChessError {
+        let pseudo_legal = self.base.get_pseudo_legal_moves(
+            self.side_to_move,
+            self.killer_square,
+            self.castling_rights,
+        );
+        match pseudo_legal.iter().find(|(m, _)| matches(m)) {
+            Some((m, _)) => match m.move_type {
+                MoveType::Castling(side) => ChessError::CastlingBlocked(side),
+                // A king moving into check would be in check on the square it moves to
+                _ => ChessError::KingInCheck(
+                    input.to_string(),
+                    match (m.piece, m.destination_square) {
+                        (Piece::King(_), Some(dest)) => dest,
+                        _ => self
+                            .pieces(Piece::King(self.side_to_move))
+                            .lsb()
+                            .expect("Position should have a king"),
+                    },
+                ),
+            },
+            None => ChessError::IllegalMove(input.to_string()),
+        }
+    }
+    /// Why castling on the given side is not legal, assuming that it is not
+    pub(crate) fn castling_rejection(&self, side: Side) -> ChessError {
+        match self.castling_rook_file(self.side_to_move, side) {
+            Some(_) => ChessError::CastlingBlocked(side),
+            None => ChessError::NoCastlingRights(side),
+        }
+    }
+
     pub fn get_game_state(&self) -> GameState {
         self.get_unchecked_game_state(self.generate_legal_moves().len())
     }
@@ -727,34 +858,29 @@ impl Board {
         // regardless of if there are "legal" moves.
 
         // --- Insufficient Material ---
-        // Easiest way to skip further calculations is to check if there are pawns
-        // on either side. This should skip further calculations in most positions.
-        if self.base.white.pawns + self.base.black.pawns == 0 {
-            if self.base.white.queens
-                + self.base.black.queens
-                + self.base.white.rooks
-                + self.base.black.rooks
-                == 0
-            {
-                // If at least one side has at least one queen or one rook, there is sufficient
-                // material
-                // There should be more checks here to allow for bishop and knights
-                // TEMPORARY FOR TESTING
-                return GameState::Finished(FinishedState::Draw(ReasonDraw::InsufficientMaterial));
-            }
+        if self.is_insufficient_material() {
+            return GameState::Finished(FinishedState::Draw(ReasonDraw::InsufficientMaterial));
         }
 
-        // --- Halfmove limit (yet to be implemented) ---
-
         // --- Resignation & Agreement (yet to be implemented) ---
 
         // From here on out, we require legal moves of this position
         if mobility != 0 {
+            // --- Halfmove limit ---
+            // Checked after the legal moves since checkmate on the last move takes precedence
+            if self.halfmove >= 150 {
+                return GameState::Finished(FinishedState::Draw(ReasonDraw::SeventyFiveMoveRule));
+            }
+            if self.halfmove >= 100 {
+                return GameState::Finished(FinishedState::Draw(ReasonDraw::HalfmoveLimit));
+            }
+
             // --- Ongoing ---
             // Easiest way to check if the game is still ongoing is to check if there
             // are any legal moves in the current position.
             // If we have gotten this far in the game state checking and there are
             // legal moves, the game is still ongoing.
+            // Note that repetitions can not be detected from a single board, see `Game`.
             GameState::Ongoing
         } else {
             // The game is over. Now it's a matter of figuring out why
@@ -776,19 +902,81 @@ impl Board {
             )
         }
     }
+    /// Whether neither side can checkmate by any sequence of legal moves (a dead position).
+    /// This is the case for K vs K, K + minor piece vs K and positions where all remaining pieces
+    /// besides the kings are bishops on squares of the same colour.
+    pub fn is_insufficient_material(&self) -> bool {
+        let (white, black) = (self.base.white, self.base.black);
+        // Easiest way to skip further calculations is to check if there are pawns, rooks or
+        // queens on either side. This should skip further calculations in most positions.
+        if white.pawns | black.pawns | white.rooks | black.rooks | white.queens | black.queens != 0
+        {
+            return false;
+        }
+
+        let knights = white.knights | black.knights;
+        let bishops = white.bishops | black.bishops;
+        if (knights | bishops).count_ones() <= 1 {
+            return true;
+        }
+
+        knights == 0 && (bishops & LIGHT_SQUARES == 0 || bishops & !LIGHT_SQUARES == 0)
+    }
+
+    /// Whether the given side has the material to checkmate without help from the other side's
+    /// pieces. Unlike `is_insufficient_material` this does not end the game (e.g. K + N vs K + P
+    /// is not a dead position), but it can be used to scale evaluations of positions that can
+    /// not be won.
+    pub fn can_checkmate(&self, colour: Colour) -> bool {
+        let side = self.base.get_side(colour);
+        if side.pawns | side.rooks | side.queens != 0 {
+            return true;
+        }
+        if (side.knights | side.bishops).count_ones() <= 1 {
+            return false;
+        }
+
+        // Bishops on a single colour can never mate a lone king, but any other pair of minor
+        // pieces can (including two knights, though that can not be forced)
+        side.knights != 0
+            || (side.bishops & LIGHT_SQUARES != 0 && side.bishops & !LIGHT_SQUARES != 0)
+    }
+
     pub fn is_check(&self) -> bool {
-        let side = self.base.get_side(self.side_to_move);
-        let other_side = self.base.get_side(self.side_to_move.other());
+        self.checkers().0 != 0
+    }
 
-        let other_attacks = unsafe {
-            generateAttackTargets(
-                other_side,
-                self.side_to_move.other().as_int(),
-                self.base.white_occupied + self.base.black_occupied,
-            )
-        };
+    /// Counts the number of leaf nodes of the legal move tree at the given depth.
+    pub fn perft(&self, depth: usize) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+
+        let moves = self.generate_legal_moves();
+        if depth == 1 {
+            // Bulk counting, no need to create the children only to count them
+            moves.len() as u64
+        } else {
+            moves.iter().map(|m| m.board.perft(depth - 1)).sum()
+        }
+    }
+
+    /// Same as `perft`, but the node count is split by the first move made from this position.
+    pub fn divide(&self, depth: usize) -> Vec<(ChessMove, u64)> {
+        if depth == 0 {
+            return vec![];
+        }
 
-        side.king & other_attacks != 0
+        self.generate_legal_moves()
+            .into_iter()
+            .map(|m| (m, m.board.perft(depth - 1)))
+            .collect()
+    }
+}
+impl Eq for Board {}
+impl Hash for Board {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash);
     }
 }
 impl Default for Board {
@@ -822,7 +1010,7 @@ impl Display for Board {
             Colour::Black => writeln!(f, "Black to move")?,
         }
 
-        if let Some(square) = to_str(self.killer_square) {
+        if let Some(square) = self.killer_square {
             writeln!(f, "Board has killer square {square}")?;
         } else {
             writeln!(f, "Board has no killer square")?;
@@ -863,46 +1051,3 @@ fn find_on_square(square: i32, white: &Pieces, black: &Pieces) -> Option<Piece>
         None
     }
 }
-
-#[derive(Debug)]
-pub struct BitBoard(pub u64);
-impl Display for BitBoard {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut bits: Vec<char> = format!("{:#0b}", self.0)[2..].chars().collect();
-        for _ in 0..(64 - bits.len()) {
-            bits.insert(0, '0');
-        }
-        let mut ranks: Vec<Vec<char>> = bits.chunks(8).map(|s| s.into()).collect();
-        for rank in &mut ranks {
-            rank.reverse();
-            for square in rank {
-                if *square == '0' {
-                    write!(f, ". ")?;
-                } else {
-                    write!(f, "{square} ")?;
-                }
-            }
-            write!(f, "\n")?;
-        }
-        Ok(())
-    }
-}
-impl BitBoard {
-    // Ranks should be ordered 8-1 and squares in rank should be in order a-h
-    pub fn from_ranks(mut ranks: [[u8; 8]; 8]) -> Self {
-        let mut res = 0;
-        let mut exp = 0;
-        ranks.reverse();
-        for rank in ranks {
-            for square in rank {
-                if square == 1 {
-                    // only calculated if sqare is not 0
-                    res += 2u64.pow(exp);
-                }
-                exp += 1;
-            }
-        }
-
-        Self(res)
-    }
-}
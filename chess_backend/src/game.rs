@@ -0,0 +1,93 @@
+use crate::{
+    board::{FinishedState, GameState, ReasonDraw},
+    utils::{san::SanMove, ChessError},
+    Board, ChessMove,
+};
+
+/// A `Board` together with the history of the positions that led to it, which (unlike a single
+/// `Board`) is enough to detect draws by repetition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Game {
+    board: Board,
+    // Zobrist keys of every earlier position, oldest first
+    history: Vec<u64>,
+}
+impl Game {
+    pub fn new(board: Board) -> Self {
+        Self {
+            board,
+            history: Vec::new(),
+        }
+    }
+
+    pub fn board(&self) -> Board {
+        self.board
+    }
+    pub fn history(&self) -> &[u64] {
+        &self.history
+    }
+
+    /// Plays a move that was generated from the current board. Does no legality checking.
+    pub fn play(&mut self, m: &ChessMove) {
+        self.push(m.board);
+    }
+    pub fn play_san(&mut self, m: SanMove) -> Result<(), ChessError> {
+        let mut board = self.board;
+        board.make_san_move(m)?;
+        self.push(board);
+        Ok(())
+    }
+    /// Plays the move leading to `board` from the current board. Does no legality checking.
+    pub fn push(&mut self, board: Board) {
+        self.history.push(self.board.hash());
+        self.board = board;
+    }
+
+    /// Number of times the current position has occurred, including the current occurrence
+    pub fn repetitions(&self) -> usize {
+        // Captures and pawn moves can not be undone, so only positions since the last reset of
+        // the halfmove clock can be repetitions of the current one
+        let hash = self.board.hash();
+        1 + self
+            .history
+            .iter()
+            .rev()
+            .take(self.board.halfmove() as usize)
+            .filter(|&&h| h == hash)
+            .count()
+    }
+
+    /// Same as `Board::get_game_state`, but also reports draws by repetition
+    pub fn get_game_state(&self) -> GameState {
+        self.get_unchecked_game_state(self.board.generate_legal_moves().len())
+    }
+
+    pub fn get_unchecked_game_state(&self, mobility: usize) -> GameState {
+        let state = self.board.get_unchecked_game_state(mobility);
+        match state {
+            GameState::Ongoing
+            | GameState::Finished(FinishedState::Draw(ReasonDraw::HalfmoveLimit)) => {
+                // Automatic draws take precedence over draws that can only be claimed
+                let repetitions = self.repetitions();
+                if repetitions >= 5 {
+                    GameState::Finished(FinishedState::Draw(ReasonDraw::FivefoldRepetition))
+                } else if state == GameState::Ongoing && repetitions >= 3 {
+                    GameState::Finished(FinishedState::Draw(ReasonDraw::ThreefoldRepetition))
+                } else {
+                    state
+                }
+            }
+            _ => state,
+        }
+    }
+}
+impl From<Board> for Game {
+    fn from(value: Board) -> Self {
+        Self::new(value)
+    }
+}
+impl Default for Game {
+    fn default() -> Self {
+        Self::new(Board::default())
+    }
+}
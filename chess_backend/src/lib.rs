@@ -8,15 +8,45 @@ mod utils;
 use utils::fen::*;
 
 mod board;
-pub use board::{BitBoard, Board, ChessMove, FinishedState, GameState, MoveType};
+pub use board::{
+    Board, ChessMove, FinishedState, GameState, MoveType, ReasonDraw, ReasonWin, Side,
+};
+
+mod bitboard;
+pub use bitboard::{BitBoard, Direction, Squares};
+
+mod game;
+pub use game::Game;
+
+mod moves;
+pub use moves::{Move, Undo};
+
+mod attacks;
+mod see;
+pub use see::see_value;
+
+mod validate;
+pub use validate::PositionError;
+
+mod packed;
+pub use packed::{PackedError, PACKED_SIZE};
+
+pub mod pgn;
+
+#[cfg(feature = "serde")]
+mod serialization;
+
 pub use utils::{
+    epd::{Epd, EpdError, EpdOperation},
     extract_squares as wrap_extract_squares,
     fen::{
-        CASTLE_KINGSIDE_POSITION, CASTLE_QUEENSIDE_POSITION, CHECK_POSITION, CMK_POSITION,
-        KILLER_POSITION, PROMOTION_POSITION, START_POSITION, TRICKY_POSITION,
+        Fen, FenError, FenField, CASTLE_KINGSIDE_POSITION, CASTLE_QUEENSIDE_POSITION,
+        CHECK_POSITION, CMK_POSITION, KILLER_POSITION, PROMOTION_POSITION, START_POSITION,
+        TRICKY_POSITION,
     },
     san::SanMove,
     squares::*,
+    uci::UciMove,
     ChessError, Colour, Piece, Pieces,
 };
 
@@ -0,0 +1,574 @@
+use std::fmt::Display;
+
+use crate::{
+    bishopTargets,
+    board::ChessMoveBase,
+    castling_rights, kingTargets, knightTargets, pawnAttackTargets, pawnTargets,
+    piece_map_bitboards, queenTargets, rookTargets,
+    utils::{
+        squares::{Rank, Square},
+        zobrist::{en_passent_key, piece_square_key, ZOBRIST_KEYS},
+    },
+    BitBoard, Board, ChessMove, Colour, MoveType, Piece, Side,
+};
+
+// Targets of a piece on a square given the occupancy
+type TargetFunction = fn(i32, u64) -> u64;
+
+// Flags stored in the upper four bits of a `Move`
+const QUIET: u16 = 0;
+const DOUBLE_PUSH: u16 = 1;
+const KING_CASTLE: u16 = 2;
+const QUEEN_CASTLE: u16 = 3;
+const CAPTURE: u16 = 4;
+const EN_PASSENT: u16 = 5;
+// The promotion piece is given by the two lowest bits, and can be combined with `CAPTURE`
+const PROMOTION: u16 = 8;
+
+/// A move packed into 16 bits: the origin square (bits 0-5), the destination square (bits 6-11)
+/// and flags for the kind of move (bits 12-15). Unlike `ChessMove` it does not carry the
+/// resulting board, so it is meant to be played with `Board::make_move` and taken back with
+/// `Board::unmake_move`.
+///
+/// As with `ChessMoveBase`, castling moves have the king's square as origin and the castling
+/// rook's square as destination.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Move(u16);
+impl Move {
+    /// Placeholder that is not a move in any position (a1 to a1)
+    pub const NULL: Self = Self(0);
+
+    fn new(origin: i32, dest: i32, flags: u16) -> Self {
+        Self(origin as u16 | (dest as u16) << 6 | flags << 12)
+    }
+    fn promotion_move(origin: i32, dest: i32, piece: Piece, capture: bool) -> Self {
+        let index = match piece {
+            Piece::Knight(_) => 0,
+            Piece::Bishop(_) => 1,
+            Piece::Rook(_) => 2,
+            Piece::Queen(_) => 3,
+            _ => panic!("Invalid promotion Piece"),
+        };
+        let capture = if capture { CAPTURE } else { 0 };
+        Self::new(origin, dest, PROMOTION | capture | index)
+    }
+
+    fn flags(&self) -> u16 {
+        self.0 >> 12
+    }
+    pub fn origin(&self) -> Square {
+        Square::from_index_unchecked((self.0 & 63) as i32)
+    }
+    pub fn dest(&self) -> Square {
+        Square::from_index_unchecked((self.0 >> 6 & 63) as i32)
+    }
+    /// The piece that a pawn is promoted to, with its colour given by the promotion rank
+    pub fn promotion(&self) -> Option<Piece> {
+        if self.flags() & PROMOTION == 0 {
+            return None;
+        }
+        let colour = if self.dest().rank() == Rank::Eighth {
+            Colour::White
+        } else {
+            Colour::Black
+        };
+        Some(match self.flags() & 3 {
+            0 => Piece::Knight(colour),
+            1 => Piece::Bishop(colour),
+            2 => Piece::Rook(colour),
+            _ => Piece::Queen(colour),
+        })
+    }
+    pub fn is_capture(&self) -> bool {
+        self.flags() & CAPTURE != 0
+    }
+    pub fn is_en_passent(&self) -> bool {
+        self.flags() == EN_PASSENT
+    }
+    pub fn is_double_push(&self) -> bool {
+        self.flags() == DOUBLE_PUSH
+    }
+    pub fn castling(&self) -> Option<Side> {
+        match self.flags() {
+            KING_CASTLE => Some(Side::King),
+            QUEEN_CASTLE => Some(Side::Queen),
+            _ => None,
+        }
+    }
+}
+impl From<Move> for u16 {
+    fn from(value: Move) -> Self {
+        value.0
+    }
+}
+impl Display for Move {
+    /// Long algebraic notation, with castling written as the king moving onto its rook
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.origin(), self.dest())?;
+        if let Some(piece) = self.promotion() {
+            write!(f, "{}", piece.letter().to_lowercase())?;
+        }
+        Ok(())
+    }
+}
+
+// Which of the moves of a position to generate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum GenerationType {
+    All,
+    Captures,
+    Quiets,
+}
+
+/// Everything about a board that `Board::make_move` can not recover from the move itself
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Undo {
+    captured: Option<Piece>,
+    castling_rights: castling_rights,
+    killer_square: Option<Square>,
+    halfmove: i32,
+    hash: u64,
+}
+
+fn piece_bitboard(side: &mut piece_map_bitboards, piece: Piece) -> &mut u64 {
+    match piece {
+        Piece::Pawn(_) => &mut side.pawns,
+        Piece::King(_) => &mut side.king,
+        Piece::Queen(_) => &mut side.queens,
+        Piece::Bishop(_) => &mut side.bishops,
+        Piece::Knight(_) => &mut side.knights,
+        Piece::Rook(_) => &mut side.rooks,
+    }
+}
+
+// Squares of the king and rook after castling, given the king's square
+fn castling_squares(king: i32, side: Side) -> (i32, i32) {
+    let back_rank = king - king % 8;
+    match side {
+        Side::King => (back_rank + 6, back_rank + 5),
+        Side::Queen => (back_rank + 2, back_rank + 3),
+    }
+}
+
+impl Board {
+    /// Compact encoding of a move generated from this board
+    pub fn encode_move(&self, m: &ChessMove) -> Move {
+        let base = m.base;
+        let origin = base
+            .starting_square
+            .expect("Move should have a starting square")
+            .as_int();
+        let dest = base
+            .destination_square
+            .expect("Move should have a destination square")
+            .as_int();
+        let capture = self.base.get_side_occupied(base.colour.other()) & 1 << dest != 0;
+        match base.move_type {
+            MoveType::Castling(Side::King) => Move::new(origin, dest, KING_CASTLE),
+            MoveType::Castling(Side::Queen) => Move::new(origin, dest, QUEEN_CASTLE),
+            MoveType::EnPassent => Move::new(origin, dest, EN_PASSENT),
+            MoveType::Promotion(piece) => Move::promotion_move(origin, dest, piece, capture),
+            MoveType::Standard => {
+                if capture {
+                    Move::new(origin, dest, CAPTURE)
+                } else if base.piece == Piece::Pawn(base.colour) && (dest - origin).abs() == 16 {
+                    Move::new(origin, dest, DOUBLE_PUSH)
+                } else {
+                    Move::new(origin, dest, QUIET)
+                }
+            }
+        }
+    }
+
+    /// The piece on the given square, if any
+    pub fn piece_on(&self, square: Square) -> Option<Piece> {
+        let bit = square.bitboard();
+        for colour in [Colour::White, Colour::Black] {
+            let side = self.base.get_side(colour);
+            for (bitboard, piece) in [
+                (side.pawns, Piece::Pawn(colour)),
+                (side.knights, Piece::Knight(colour)),
+                (side.bishops, Piece::Bishop(colour)),
+                (side.rooks, Piece::Rook(colour)),
+                (side.queens, Piece::Queen(colour)),
+                (side.king, Piece::King(colour)),
+            ] {
+                if bitboard & bit != 0 {
+                    return Some(piece);
+                }
+            }
+        }
+        None
+    }
+
+    // Adds or removes a piece, keeping the occupancy and hash up to date
+    fn toggle_piece(&mut self, piece: Piece, square: i32) {
+        let bit = 1u64 << square;
+        match piece.colour() {
+            Colour::White => {
+                *piece_bitboard(&mut self.base.white, piece) ^= bit;
+                self.base.white_occupied ^= bit;
+            }
+            Colour::Black => {
+                *piece_bitboard(&mut self.base.black, piece) ^= bit;
+                self.base.black_occupied ^= bit;
+            }
+        }
+        self.hash ^= piece_square_key(piece, square);
+    }
+
+    // Key of the killer square for the hash, which is only part of the position when a pawn can
+    // take en passent without leaving its king in check. Otherwise a double push that can not be
+    // answered by en passent would keep the position from repeating the same one without it.
+    pub(crate) fn en_passent_hash(&self) -> u64 {
+        let Some(square) = self.killer_square else {
+            return 0;
+        };
+        // The side that can take is the one that did not make the double push
+        let colour = if square.rank() == Rank::Third {
+            Colour::Black
+        } else {
+            Colour::White
+        };
+        let Some(pushed) = square.backward(colour) else {
+            return 0;
+        };
+        let pawns = self.attackers_to(square, colour) & self.pieces(Piece::Pawn(colour));
+        let legal = pawns.into_iter().any(|origin| {
+            let mut board = *self;
+            board.toggle_piece(Piece::Pawn(colour), origin.as_int());
+            board.toggle_piece(Piece::Pawn(colour), square.as_int());
+            board.toggle_piece(Piece::Pawn(colour.other()), pushed.as_int());
+            !board.king_attacked(colour)
+        });
+        if legal {
+            en_passent_key(square)
+        } else {
+            0
+        }
+    }
+
+    // Whether the king of the given colour is attacked
+    fn king_attacked(&self, colour: Colour) -> bool {
+        self.pieces(Piece::King(colour))
+            .lsb()
+            .is_some_and(|king| self.is_square_attacked(king, colour.other()))
+    }
+
+    /// Plays a move in place. The move must have been generated for this position (e.g. by
+    /// `generate_moves`), otherwise the board is left in an invalid state.
+    pub fn make_move(&mut self, m: Move) -> Undo {
+        let mut undo = Undo {
+            captured: None,
+            castling_rights: self.castling_rights,
+            killer_square: self.killer_square,
+            halfmove: self.halfmove,
+            hash: self.hash,
+        };
+        let colour = self.side_to_move;
+        let (origin, dest) = (m.origin().as_int(), m.dest().as_int());
+        let piece = self
+            .piece_on(m.origin())
+            .expect("Move should start on a square with a piece");
+
+        // The killer square is rehashed once the new position is complete
+        self.hash ^= self.en_passent_hash();
+        self.halfmove += 1;
+        if let Some(side) = m.castling() {
+            // Both pieces are removed first since the king and rook can swap squares in Chess960
+            let (king_dest, rook_dest) = castling_squares(origin, side);
+            self.toggle_piece(Piece::King(colour), origin);
+            self.toggle_piece(Piece::Rook(colour), dest);
+            self.toggle_piece(Piece::King(colour), king_dest);
+            self.toggle_piece(Piece::Rook(colour), rook_dest);
+        } else {
+            if m.is_capture() {
+                let square = self.captured_square(m);
+                let captured = self
+                    .piece_on(square)
+                    .expect("Capture should have a piece to capture");
+                self.toggle_piece(captured, square.as_int());
+                undo.captured = Some(captured);
+                self.halfmove = 0;
+            }
+            if piece == Piece::Pawn(colour) {
+                self.halfmove = 0;
+            }
+            self.toggle_piece(piece, origin);
+            self.toggle_piece(m.promotion().unwrap_or(piece), dest);
+        }
+
+        self.set_killer_square(if m.is_double_push() {
+            Square::from_index((origin + dest) / 2)
+        } else {
+            None
+        });
+
+        // Castling rights are lost once the king or the castling rook has moved, or the rook has
+        // been captured
+        for c in [Colour::White, Colour::Black] {
+            let back_rank = if c == Colour::White { 0 } else { 56 };
+            for castling_side in [Side::King, Side::Queen] {
+                let file = self.castling_rights.file(c, castling_side);
+                if file >= 0
+                    && (piece == Piece::King(c)
+                        || self.base.get_side(c).rooks & 1 << (back_rank + file) == 0)
+                {
+                    self.revoke_castling(c, castling_side);
+                }
+            }
+        }
+
+        if colour == Colour::Black {
+            self.fullmove += 1;
+        }
+        self.side_to_move = colour.other();
+        self.hash ^= ZOBRIST_KEYS.side ^ self.en_passent_hash();
+        undo
+    }
+
+    /// Takes back a move made with `make_move`, given the `Undo` it returned
+    pub fn unmake_move(&mut self, m: Move, undo: Undo) {
+        let colour = self.side_to_move.other();
+        let (origin, dest) = (m.origin().as_int(), m.dest().as_int());
+
+        if let Some(side) = m.castling() {
+            let (king_dest, rook_dest) = castling_squares(origin, side);
+            self.toggle_piece(Piece::King(colour), king_dest);
+            self.toggle_piece(Piece::Rook(colour), rook_dest);
+            self.toggle_piece(Piece::King(colour), origin);
+            self.toggle_piece(Piece::Rook(colour), dest);
+        } else {
+            let piece = self
+                .piece_on(m.dest())
+                .expect("Move should end on a square with a piece");
+            self.toggle_piece(piece, dest);
+            let piece = if m.promotion().is_some() {
+                Piece::Pawn(colour)
+            } else {
+                piece
+            };
+            self.toggle_piece(piece, origin);
+            if let Some(captured) = undo.captured {
+                let square = self.captured_square(m);
+                self.toggle_piece(captured, square.as_int());
+            }
+        }
+
+        if colour == Colour::Black {
+            self.fullmove -= 1;
+        }
+        self.side_to_move = colour;
+        self.castling_rights = undo.castling_rights;
+        self.killer_square = undo.killer_square;
+        self.halfmove = undo.halfmove;
+        self.hash = undo.hash;
+    }
+
+    // The square of the piece taken by a capture, which is behind the destination for en passent
+    fn captured_square(&self, m: Move) -> Square {
+        let dest = m.dest();
+        if !m.is_en_passent() {
+            return dest;
+        }
+        match dest.rank() {
+            Rank::Sixth => dest.offset(0, -1),
+            _ => dest.offset(0, 1),
+        }
+        .expect("En passent should not end on a back rank")
+    }
+
+    /// The `ChessMove` (including the resulting board) of a move generated for this board
+    pub fn decode_move(&self, m: Move) -> ChessMove {
+        let piece = self
+            .piece_on(m.origin())
+            .expect("Move should start on a square with a piece");
+        let move_type = if let Some(side) = m.castling() {
+            MoveType::Castling(side)
+        } else if m.is_en_passent() {
+            MoveType::EnPassent
+        } else if let Some(piece) = m.promotion() {
+            MoveType::Promotion(piece)
+        } else {
+            MoveType::Standard
+        };
+        let mut board = *self;
+        board.make_move(m);
+        ChessMove {
+            base: ChessMoveBase {
+                starting_square: Some(m.origin()),
+                destination_square: Some(m.dest()),
+                piece,
+                move_type,
+                colour: self.side_to_move,
+                res_board: board.base,
+            },
+            board,
+        }
+    }
+
+    /// Generates the legal moves of the position as `Move`s, without creating a board for each
+    /// of them. These are the same moves as those of `generate_legal_moves`.
+    pub fn generate_moves(&self) -> Vec<Move> {
+        self.generate_filtered_moves(GenerationType::All)
+    }
+
+    /// Legal moves that capture a piece, including en passent and capturing promotions
+    pub fn generate_captures(&self) -> Vec<ChessMove> {
+        self.generate_filtered_moves(GenerationType::Captures)
+            .into_iter()
+            .map(|m| self.decode_move(m))
+            .collect()
+    }
+
+    /// Legal moves that do not capture a piece, including castling and non-capturing promotions
+    pub fn generate_quiets(&self) -> Vec<ChessMove> {
+        self.generate_filtered_moves(GenerationType::Quiets)
+            .into_iter()
+            .map(|m| self.decode_move(m))
+            .collect()
+    }
+
+    /// Legal moves that give check, whether directly or by discovery
+    pub fn generate_checks(&self) -> Vec<ChessMove> {
+        let mut board = *self;
+        let other = self.side_to_move.other();
+        self.generate_moves()
+            .into_iter()
+            .filter(|&m| {
+                let undo = board.make_move(m);
+                let check = board.king_attacked(other);
+                board.unmake_move(m, undo);
+                check
+            })
+            .map(|m| self.decode_move(m))
+            .collect()
+    }
+
+    fn generate_filtered_moves(&self, generation_type: GenerationType) -> Vec<Move> {
+        let mut board = *self;
+        let colour = self.side_to_move;
+        self.generate_pseudo_legal_moves(generation_type)
+            .into_iter()
+            .filter(|&m| {
+                let undo = board.make_move(m);
+                let legal = !board.king_attacked(colour);
+                board.unmake_move(m, undo);
+                legal
+            })
+            .collect()
+    }
+
+    fn generate_pseudo_legal_moves(&self, generation_type: GenerationType) -> Vec<Move> {
+        let mut res = Vec::with_capacity(64);
+        let colour = self.side_to_move;
+        let side = self.base.get_side(colour);
+        let own = self.base.get_side_occupied(colour);
+        let other = self.base.get_side_occupied(colour.other());
+        let occupancy = own | other;
+        // Squares that moves may end on
+        let dest_mask = match generation_type {
+            GenerationType::All => !own,
+            GenerationType::Captures => other,
+            GenerationType::Quiets => !occupancy,
+        };
+
+        let add_targets = |origin: i32, targets: u64, res: &mut Vec<Move>| {
+            let mut targets = targets & dest_mask;
+            while targets != 0 {
+                let dest = targets.trailing_zeros() as i32;
+                let flags = if other & 1 << dest != 0 {
+                    CAPTURE
+                } else {
+                    QUIET
+                };
+                res.push(Move::new(origin, dest, flags));
+                targets &= targets - 1;
+            }
+        };
+
+        // --- Pawn Moves ---
+        let promotion_rank = match colour {
+            Colour::White => 7,
+            Colour::Black => 0,
+        };
+        let mut pawns = side.pawns;
+        while pawns != 0 {
+            let origin = pawns.trailing_zeros() as i32;
+            let mut targets =
+                unsafe { pawnTargets(origin, colour.as_int(), occupancy) } & dest_mask;
+            while targets != 0 {
+                let dest = targets.trailing_zeros() as i32;
+                let capture = other & 1 << dest != 0;
+                if dest / 8 == promotion_rank {
+                    for piece in [
+                        Piece::Queen(colour),
+                        Piece::Rook(colour),
+                        Piece::Knight(colour),
+                        Piece::Bishop(colour),
+                    ] {
+                        res.push(Move::promotion_move(origin, dest, piece, capture));
+                    }
+                } else if capture {
+                    res.push(Move::new(origin, dest, CAPTURE));
+                } else if (dest - origin).abs() == 16 {
+                    res.push(Move::new(origin, dest, DOUBLE_PUSH));
+                } else {
+                    res.push(Move::new(origin, dest, QUIET));
+                }
+                targets &= targets - 1;
+            }
+            // En passent is only possible if the board has a killer square
+            if let Some(square) = self.killer_square {
+                let attacks = BitBoard(unsafe { pawnAttackTargets(origin, colour.as_int()) });
+                if generation_type != GenerationType::Quiets && attacks.contains(square) {
+                    res.push(Move::new(origin, square.as_int(), EN_PASSENT));
+                }
+            }
+            pawns &= pawns - 1;
+        }
+
+        // --- Piece Moves ---
+        let pieces: [(u64, TargetFunction); 5] = [
+            (side.king, |s, _| unsafe { kingTargets(s) }),
+            (side.queens, |s, o| unsafe { queenTargets(s, o) }),
+            (side.bishops, |s, o| unsafe { bishopTargets(s, o) }),
+            (side.knights, |s, _| unsafe { knightTargets(s) }),
+            (side.rooks, |s, o| unsafe { rookTargets(s, o) }),
+        ];
+        for (bitboard, target_function) in pieces {
+            let mut bitboard = bitboard;
+            while bitboard != 0 {
+                let origin = bitboard.trailing_zeros() as i32;
+                add_targets(origin, target_function(origin, occupancy), &mut res);
+                bitboard &= bitboard - 1;
+            }
+        }
+
+        // --- Castling ---
+        // Only generated if the path of the king and rook is free and safe
+        if generation_type == GenerationType::Captures {
+            return res;
+        }
+        for (m, _) in self
+            .base
+            .get_pseudo_legal_castling(colour, self.castling_rights)
+        {
+            let flags = match m.move_type {
+                MoveType::Castling(Side::King) => KING_CASTLE,
+                _ => QUEEN_CASTLE,
+            };
+            res.push(Move::new(
+                m.starting_square
+                    .expect("Castling should have a king square")
+                    .as_int(),
+                m.destination_square
+                    .expect("Castling should have a rook square")
+                    .as_int(),
+                flags,
+            ));
+        }
+
+        res
+    }
+}
@@ -0,0 +1,231 @@
+// Fixed-size binary encoding of positions, for databases and training data
+use crate::{
+    board::{Board, Side},
+    castling_rights, createBase, piece_map_bitboards,
+    utils::squares::{File, Rank, Square},
+    BitBoard, Colour, Piece, Pieces, PositionError,
+};
+use std::{error::Error, fmt::Display};
+
+/// Size of `Board::to_packed` in bytes
+pub const PACKED_SIZE: usize = 32;
+
+// Piece codes are stored as one nibble per occupied square. Pawns that can be captured en passent
+// and rooks that can still castle get codes of their own, so that neither the killer square nor
+// the castling rights (including Chess960 rook files) need any extra space.
+const WHITE_PIECES: u8 = 0;
+const BLACK_PIECES: u8 = 6;
+const EN_PASSENT_PAWN: u8 = 12;
+const WHITE_CASTLING_ROOK: u8 = 13;
+const BLACK_CASTLING_ROOK: u8 = 14;
+
+// Offsets of the fields that follow the occupancy (bytes 0-7) and the piece codes (bytes 8-23)
+const PIECE_CODES: usize = 8;
+const FLAGS: usize = 24;
+const HALFMOVE: usize = 25;
+const FULLMOVE: usize = 27;
+
+const SIDE_TO_MOVE_FLAG: u8 = 1;
+const CHESS960_FLAG: u8 = 2;
+
+/// Reason for a packed position being rejected by `Board::from_packed` (or a position not fitting
+/// into `Board::to_packed`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackedError {
+    /// There are more than 32 pieces on the board (found count is attached)
+    TooManyPieces(u32),
+    /// Unknown piece code on the square
+    InvalidPiece(Square, u8),
+    /// The encoding is well-formed, but the position it describes is not valid
+    InvalidPosition(PositionError),
+}
+impl Display for PackedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid packed position: ")?;
+        match self {
+            Self::TooManyPieces(n) => write!(f, "{n} pieces do not fit into 32"),
+            Self::InvalidPiece(s, code) => write!(f, "invalid piece code {code} on {s}"),
+            Self::InvalidPosition(e) => write!(f, "{e}"),
+        }
+    }
+}
+impl Error for PackedError {}
+impl From<PositionError> for PackedError {
+    fn from(value: PositionError) -> Self {
+        Self::InvalidPosition(value)
+    }
+}
+
+fn piece_index(piece: Piece) -> u8 {
+    match piece {
+        Piece::Pawn(_) => 0,
+        Piece::Knight(_) => 1,
+        Piece::Bishop(_) => 2,
+        Piece::Rook(_) => 3,
+        Piece::Queen(_) => 4,
+        Piece::King(_) => 5,
+    }
+}
+
+fn piece_from_index(index: u8, colour: Colour) -> Piece {
+    match index {
+        0 => Piece::Pawn(colour),
+        1 => Piece::Knight(colour),
+        2 => Piece::Bishop(colour),
+        3 => Piece::Rook(colour),
+        4 => Piece::Queen(colour),
+        _ => Piece::King(colour),
+    }
+}
+
+fn push_piece(white: &mut Pieces, black: &mut Pieces, piece: Piece, square: i32) {
+    let pieces = match piece.colour() {
+        Colour::White => white,
+        Colour::Black => black,
+    };
+    match piece {
+        Piece::Pawn(_) => pieces.pawns.push(square),
+        Piece::King(_) => pieces.king.push(square),
+        Piece::Queen(_) => pieces.queens.push(square),
+        Piece::Bishop(_) => pieces.bishops.push(square),
+        Piece::Knight(_) => pieces.knights.push(square),
+        Piece::Rook(_) => pieces.rooks.push(square),
+    }
+}
+
+impl Board {
+    /// Encodes the position, including castling rights, killer square, side to move and clocks,
+    /// in 32 bytes: the occupancy as a little-endian bitboard, a 4-bit code for each occupied
+    /// square from a1 to h8, a flag byte and both clocks as little-endian `u16` (saturated),
+    /// with the last three bytes unused (zero).
+    ///
+    /// Only positions with more than 32 pieces, which can not be reached in a game, do not fit.
+    pub fn to_packed(&self) -> Result<[u8; PACKED_SIZE], PackedError> {
+        let occupancy = BitBoard(self.base.white_occupied | self.base.black_occupied);
+        if occupancy.popcount() > 32 {
+            return Err(PackedError::TooManyPieces(occupancy.popcount()));
+        }
+
+        let en_passent_pawn = self
+            .killer_square
+            .and_then(|s| s.backward(self.side_to_move));
+        let castling_rooks: BitBoard = [Colour::White, Colour::Black]
+            .into_iter()
+            .flat_map(|colour| {
+                [Side::King, Side::Queen]
+                    .into_iter()
+                    .filter_map(move |side| {
+                        let file = File::from_index(self.castling_rook_file(colour, side)?)?;
+                        Some(Square::new(file, Rank::First.relative(colour)))
+                    })
+            })
+            .collect();
+
+        let mut res = [0; PACKED_SIZE];
+        res[..PIECE_CODES].copy_from_slice(&occupancy.0.to_le_bytes());
+        for (i, square) in occupancy.iter().enumerate() {
+            let piece = self
+                .piece_on(square)
+                .expect("Occupied square should have a piece");
+            let code = if Some(square) == en_passent_pawn {
+                EN_PASSENT_PAWN
+            } else if castling_rooks.contains(square) {
+                match piece.colour() {
+                    Colour::White => WHITE_CASTLING_ROOK,
+                    Colour::Black => BLACK_CASTLING_ROOK,
+                }
+            } else {
+                piece_index(piece)
+                    + match piece.colour() {
+                        Colour::White => WHITE_PIECES,
+                        Colour::Black => BLACK_PIECES,
+                    }
+            };
+            res[PIECE_CODES + i / 2] |= code << (4 * (i % 2));
+        }
+
+        if self.side_to_move == Colour::Black {
+            res[FLAGS] |= SIDE_TO_MOVE_FLAG;
+        }
+        if self.is_chess960() {
+            res[FLAGS] |= CHESS960_FLAG;
+        }
+        let clock = |n: i32| n.clamp(0, u16::MAX as i32) as u16;
+        res[HALFMOVE..HALFMOVE + 2].copy_from_slice(&clock(self.halfmove).to_le_bytes());
+        res[FULLMOVE..FULLMOVE + 2].copy_from_slice(&clock(self.fullmove).to_le_bytes());
+        Ok(res)
+    }
+
+    /// Decodes a position written by `to_packed`. The position is checked with `Board::validate`.
+    pub fn from_packed(packed: &[u8; PACKED_SIZE]) -> Result<Self, PackedError> {
+        let mut occupancy = [0; 8];
+        occupancy.copy_from_slice(&packed[..PIECE_CODES]);
+        let occupancy = BitBoard(u64::from_le_bytes(occupancy));
+        if occupancy.popcount() > 32 {
+            return Err(PackedError::TooManyPieces(occupancy.popcount()));
+        }
+
+        let side_to_move = match packed[FLAGS] & SIDE_TO_MOVE_FLAG {
+            0 => Colour::White,
+            _ => Colour::Black,
+        };
+        let mut white = Pieces::default();
+        let mut black = Pieces::default();
+        let mut killer_square = None;
+        let mut castling_rooks = Vec::new();
+        for (i, square) in occupancy.iter().enumerate() {
+            let code = (packed[PIECE_CODES + i / 2] >> (4 * (i % 2))) & 0xf;
+            let piece = match code {
+                WHITE_PIECES..=5 => piece_from_index(code - WHITE_PIECES, Colour::White),
+                BLACK_PIECES..=11 => piece_from_index(code - BLACK_PIECES, Colour::Black),
+                EN_PASSENT_PAWN => {
+                    // The pawn has just moved, so it belongs to the side not to move
+                    killer_square = square.forward(side_to_move);
+                    Piece::Pawn(side_to_move.other())
+                }
+                WHITE_CASTLING_ROOK => {
+                    castling_rooks.push((Colour::White, square));
+                    Piece::Rook(Colour::White)
+                }
+                BLACK_CASTLING_ROOK => {
+                    castling_rooks.push((Colour::Black, square));
+                    Piece::Rook(Colour::Black)
+                }
+                _ => return Err(PackedError::InvalidPiece(square, code)),
+            };
+            push_piece(&mut white, &mut black, piece, square.as_int());
+        }
+
+        // The castling side is given by the side of the king the rook is on
+        let mut rights = castling_rights::none();
+        for (colour, rook) in castling_rooks {
+            let king = match colour {
+                Colour::White => &white.king,
+                Colour::Black => &black.king,
+            };
+            let side = match king.first() {
+                Some(&k) if rook.as_int() < k => Side::Queen,
+                _ => Side::King,
+            };
+            rights.set_file(colour, side, rook.file().index());
+        }
+
+        let clock = |offset: usize| u16::from_le_bytes([packed[offset], packed[offset + 1]]);
+        let mut board = unsafe {
+            let base = createBase(
+                piece_map_bitboards::from(&mut white),
+                piece_map_bitboards::from(&mut black),
+            );
+            Self::new(
+                base,
+                killer_square,
+                rights,
+                side_to_move,
+                clock(HALFMOVE) as i32,
+                clock(FULLMOVE) as i32,
+            )?
+        };
+        board.set_chess960(packed[FLAGS] & CHESS960_FLAG != 0);
+        Ok(board)
+    }
+}
@@ -0,0 +1,159 @@
+// Portable Game Notation PGN
+mod reader;
+mod writer;
+
+pub use reader::PgnReader;
+
+use std::{error::Error, fmt::Display, io};
+
+use crate::{utils::fen::FenError, Board, ChessMove, SanMove, START_POSITION};
+
+/// Result of a game as given by the game termination marker
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PgnResult {
+    WhiteWins,
+    BlackWins,
+    Draw,
+    /// Game still in progress, abandoned or result otherwise unknown (`*`)
+    Unknown,
+}
+impl PgnResult {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::WhiteWins => "1-0",
+            Self::BlackWins => "0-1",
+            Self::Draw => "1/2-1/2",
+            Self::Unknown => "*",
+        }
+    }
+    pub fn from_marker(s: &str) -> Option<Self> {
+        match s {
+            "1-0" => Some(Self::WhiteWins),
+            "0-1" => Some(Self::BlackWins),
+            "1/2-1/2" => Some(Self::Draw),
+            "*" => Some(Self::Unknown),
+            _ => None,
+        }
+    }
+}
+impl Display for PgnResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// A single move of the movetext along with its annotations
+#[derive(Debug, Clone, PartialEq)]
+pub struct PgnMove {
+    pub san: SanMove,
+    /// The position after the move has been made
+    pub board: Board,
+    /// Numeric annotation glyphs, with suffix annotations (`!`, `?!`, ...) converted to their NAG
+    pub nags: Vec<u8>,
+    /// Comments following the move
+    pub comments: Vec<String>,
+    /// Alternatives to this move, each starting from the position before it
+    pub variations: Vec<Variation>,
+}
+impl PgnMove {
+    pub fn new(san: SanMove, board: Board) -> Self {
+        Self {
+            san,
+            board,
+            nags: Vec::new(),
+            comments: Vec::new(),
+            variations: Vec::new(),
+        }
+    }
+}
+
+/// A sequence of moves, either the main line of a game or a (recursive) variation
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Variation {
+    /// Comment before the first move
+    pub comment: Option<String>,
+    pub moves: Vec<PgnMove>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PgnGame {
+    /// Tag pairs in the order they were read
+    pub tags: Vec<(String, String)>,
+    /// The position the game starts from, given by the FEN tag if there is one
+    pub start: Board,
+    pub main_line: Variation,
+    pub result: PgnResult,
+}
+impl PgnGame {
+    /// Creates a game without any moves from the given position. The FEN and SetUp tags are
+    /// added if the position is not the standard starting position.
+    pub fn new(start: Board) -> Self {
+        let mut res = Self {
+            tags: Vec::new(),
+            start,
+            main_line: Variation::default(),
+            result: PgnResult::Unknown,
+        };
+        if start != Board::from(START_POSITION) {
+            res.set_tag("SetUp", "1");
+            res.set_tag("FEN", &start.into_fen());
+        }
+        res
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+    pub fn set_tag(&mut self, name: &str, value: &str) {
+        if let Some(tag) = self.tags.iter_mut().find(|(n, _)| n == name) {
+            tag.1 = value.to_string();
+        } else {
+            self.tags.push((name.to_string(), value.to_string()));
+        }
+    }
+
+    pub fn moves(&self) -> &[PgnMove] {
+        &self.main_line.moves
+    }
+    /// The position at the end of the main line
+    pub fn final_board(&self) -> Board {
+        self.main_line.moves.last().map_or(self.start, |m| m.board)
+    }
+
+    /// Adds a move to the main line. The move is assumed to be legal in the final position.
+    pub fn play(&mut self, m: &ChessMove) {
+        let san = self.final_board().get_san(&m.board);
+        self.main_line.moves.push(PgnMove::new(san, m.board));
+    }
+}
+
+#[derive(Debug)]
+pub enum PgnError {
+    Io(io::Error),
+    /// Malformed PGN on the given line
+    Syntax(usize, String),
+    InvalidFen(usize, FenError),
+    /// A move on the given line that is not legal (or not valid SAN) in its position
+    IllegalMove(usize, String),
+}
+impl Display for PgnError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Failed to read PGN: {e}"),
+            Self::Syntax(line, message) => write!(f, "Invalid PGN on line {line}: {message}"),
+            Self::InvalidFen(line, e) => write!(f, "Invalid PGN on line {line}: {e}"),
+            Self::IllegalMove(line, san) => {
+                write!(f, "Invalid PGN on line {line}: illegal move '{san}'")
+            }
+        }
+    }
+}
+impl Error for PgnError {}
+impl From<io::Error> for PgnError {
+    fn from(value: io::Error) -> Self {
+        Self::Io(value)
+    }
+}
@@ -0,0 +1,360 @@
+use std::io::{BufRead, BufReader, Read};
+
+use super::{PgnError, PgnGame, PgnMove, PgnResult, Variation};
+use crate::{Board, START_POSITION};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Tag(String, String),
+    Comment(String),
+    Nag(u8),
+    MoveNumber,
+    OpenVariation,
+    CloseVariation,
+    Result(PgnResult),
+    San(String),
+}
+
+struct Lexer<R: BufRead> {
+    reader: R,
+    line: Vec<char>,
+    pos: usize,
+    line_number: usize,
+    peeked: Option<Token>,
+}
+impl<R: BufRead> Lexer<R> {
+    fn syntax_error(&self, message: impl Into<String>) -> PgnError {
+        PgnError::Syntax(self.line_number, message.into())
+    }
+
+    fn peek_char(&mut self) -> Result<Option<char>, PgnError> {
+        if self.pos >= self.line.len() {
+            let mut buf = String::new();
+            if self.reader.read_line(&mut buf)? == 0 {
+                return Ok(None);
+            }
+            self.line = buf.chars().collect();
+            self.pos = 0;
+            self.line_number += 1;
+        }
+        Ok(Some(self.line[self.pos]))
+    }
+    fn next_char(&mut self) -> Result<Option<char>, PgnError> {
+        let c = self.peek_char()?;
+        self.pos += 1;
+        Ok(c)
+    }
+    fn skip_line(&mut self) {
+        self.pos = self.line.len();
+    }
+
+    fn peek(&mut self) -> Result<Option<&Token>, PgnError> {
+        if self.peeked.is_none() {
+            self.peeked = self.read_token()?;
+        }
+        Ok(self.peeked.as_ref())
+    }
+    fn next(&mut self) -> Result<Option<Token>, PgnError> {
+        match self.peeked.take() {
+            Some(token) => Ok(Some(token)),
+            None => self.read_token(),
+        }
+    }
+
+    fn read_token(&mut self) -> Result<Option<Token>, PgnError> {
+        loop {
+            let c = match self.next_char()? {
+                Some(c) => c,
+                None => return Ok(None),
+            };
+            match c {
+                // Escape mechanism, the rest of the line is ignored
+                '%' if self.pos == 1 => self.skip_line(),
+                c if c.is_whitespace() || c == '.' => {}
+                ';' => {
+                    let comment: String = self.line[self.pos..].iter().collect();
+                    self.skip_line();
+                    return Ok(Some(Token::Comment(comment.trim().to_string())));
+                }
+                '{' => return self.read_comment().map(Some),
+                '[' => return self.read_tag().map(Some),
+                '(' => return Ok(Some(Token::OpenVariation)),
+                ')' => return Ok(Some(Token::CloseVariation)),
+                '$' => {
+                    let digits = self.read_while(|c| c.is_ascii_digit())?;
+                    return match digits.parse::<u8>() {
+                        Ok(nag) => Ok(Some(Token::Nag(nag))),
+                        Err(_) => Err(self.syntax_error(format!("invalid NAG '${digits}'"))),
+                    };
+                }
+                '*' => return Ok(Some(Token::Result(PgnResult::Unknown))),
+                c if c.is_alphanumeric() => {
+                    let symbol = c.to_string()
+                        + &self.read_while(|c| c.is_alphanumeric() || "_+#=:-/!?".contains(c))?;
+                    return Ok(Some(Self::classify_symbol(symbol)));
+                }
+                c => return Err(self.syntax_error(format!("unexpected character '{c}'"))),
+            }
+        }
+    }
+
+    fn classify_symbol(symbol: String) -> Token {
+        if let Some(result) = PgnResult::from_marker(&symbol) {
+            Token::Result(result)
+        } else if symbol.chars().all(|c| c.is_ascii_digit()) {
+            // Any periods that follow are skipped as whitespace
+            Token::MoveNumber
+        } else {
+            Token::San(symbol)
+        }
+    }
+
+    fn read_while(&mut self, f: impl Fn(char) -> bool) -> Result<String, PgnError> {
+        let mut res = String::new();
+        // Symbols never span multiple lines
+        while self.pos < self.line.len() && f(self.line[self.pos]) {
+            res.push(self.line[self.pos]);
+            self.pos += 1;
+        }
+        Ok(res)
+    }
+
+    fn read_comment(&mut self) -> Result<Token, PgnError> {
+        let mut res = String::new();
+        loop {
+            match self.next_char()? {
+                Some('}') => break,
+                Some(c) if c.is_whitespace() => res.push(' '),
+                Some(c) => res.push(c),
+                None => return Err(self.syntax_error("unterminated comment")),
+            }
+        }
+        Ok(Token::Comment(
+            res.split_whitespace().collect::<Vec<&str>>().join(" "),
+        ))
+    }
+
+    fn read_tag(&mut self) -> Result<Token, PgnError> {
+        self.read_while(char::is_whitespace)?;
+        let name = self.read_while(|c| c.is_alphanumeric() || c == '_')?;
+        if name.is_empty() {
+            return Err(self.syntax_error("missing tag name"));
+        }
+        self.read_while(char::is_whitespace)?;
+        if self.next_char()? != Some('"') {
+            return Err(self.syntax_error(format!("missing value for tag '{name}'")));
+        }
+
+        let mut value = String::new();
+        loop {
+            match self.next_char()? {
+                Some('"') => break,
+                Some('\\') => match self.next_char()? {
+                    Some(c) => value.push(c),
+                    None => break,
+                },
+                Some('\n') | None => {
+                    return Err(self.syntax_error(format!("unterminated value for tag '{name}'")))
+                }
+                Some(c) => value.push(c),
+            }
+        }
+
+        self.read_while(char::is_whitespace)?;
+        if self.next_char()? != Some(']') {
+            return Err(self.syntax_error(format!("unterminated tag '{name}'")));
+        }
+        Ok(Token::Tag(name, value))
+    }
+}
+
+/// Splits suffix annotations (`!`, `?`, `!!`, ...) off a SAN token, converting them to NAGs
+fn split_suffix(token: &str) -> (&str, Option<u8>) {
+    let san = token.trim_end_matches(['!', '?']);
+    let nag = match &token[san.len()..] {
+        "!" => Some(1),
+        "?" => Some(2),
+        "!!" => Some(3),
+        "??" => Some(4),
+        "!?" => Some(5),
+        "?!" => Some(6),
+        _ => None,
+    };
+    (san, nag)
+}
+
+/// Reads games one at a time from PGN input. Every move is checked against the position it is
+/// played in, so games with illegal moves result in an error.
+pub struct PgnReader<R: Read> {
+    lexer: Lexer<BufReader<R>>,
+    // Set after an error, in which case the rest of the failed game is skipped
+    recovering: bool,
+    failed: bool,
+}
+impl<R: Read> PgnReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            lexer: Lexer {
+                reader: BufReader::new(reader),
+                line: Vec::new(),
+                pos: 0,
+                line_number: 0,
+                peeked: None,
+            },
+            recovering: false,
+            failed: false,
+        }
+    }
+
+    fn skip_game(&mut self) -> Result<(), PgnError> {
+        loop {
+            match self.lexer.peek() {
+                Ok(None) | Ok(Some(Token::Tag(..))) => return Ok(()),
+                Ok(Some(_)) => {
+                    self.lexer.next()?;
+                }
+                Err(PgnError::Io(e)) => return Err(PgnError::Io(e)),
+                // Anything malformed is part of the game being skipped
+                Err(_) => {}
+            }
+        }
+    }
+
+    /// Reads the next game, returning `None` if there are no more games
+    pub fn read_game(&mut self) -> Result<Option<PgnGame>, PgnError> {
+        let mut tags = Vec::new();
+        while let Some(Token::Tag(..)) = self.lexer.peek()? {
+            if let Some(Token::Tag(name, value)) = self.lexer.next()? {
+                tags.push((name, value));
+            }
+        }
+        if tags.is_empty() && self.lexer.peek()?.is_none() {
+            return Ok(None);
+        }
+
+        let start = match tags.iter().find(|(name, _)| name == "FEN") {
+            Some((_, fen)) => {
+                Board::from_fen(fen).map_err(|e| PgnError::InvalidFen(self.lexer.line_number, e))?
+            }
+            None => Board::from(START_POSITION),
+        };
+
+        let (main_line, result) = self.read_variation(start, true)?;
+        // Fall back on the Result tag if the movetext has no termination marker
+        let result = result
+            .or_else(|| {
+                tags.iter()
+                    .find(|(name, _)| name == "Result")
+                    .and_then(|(_, r)| PgnResult::from_marker(r))
+            })
+            .unwrap_or(PgnResult::Unknown);
+
+        Ok(Some(PgnGame {
+            tags,
+            start,
+            main_line,
+            result,
+        }))
+    }
+
+    fn read_variation(
+        &mut self,
+        start: Board,
+        main_line: bool,
+    ) -> Result<(Variation, Option<PgnResult>), PgnError> {
+        let mut variation = Variation::default();
+        loop {
+            // A tag means that a new game has started, even if this one had no termination marker
+            if let None | Some(Token::Tag(..)) = self.lexer.peek()? {
+                if main_line {
+                    return Ok((variation, None));
+                }
+                return Err(self.lexer.syntax_error("unterminated variation"));
+            }
+
+            let line_number = self.lexer.line_number;
+            let moves = &mut variation.moves;
+            match self.lexer.next()? {
+                None | Some(Token::Tag(..)) => unreachable!("End of game is checked above"),
+                Some(Token::Comment(comment)) => match moves.last_mut() {
+                    Some(m) => m.comments.push(comment),
+                    None => match &mut variation.comment {
+                        Some(c) => {
+                            c.push(' ');
+                            c.push_str(&comment);
+                        }
+                        None => variation.comment = Some(comment),
+                    },
+                },
+                Some(Token::Nag(nag)) => match moves.last_mut() {
+                    Some(m) => m.nags.push(nag),
+                    None => return Err(self.lexer.syntax_error("NAG before the first move")),
+                },
+                Some(Token::MoveNumber) => {}
+                Some(Token::San(token)) => {
+                    let board = moves.last().map_or(start, |m| m.board);
+                    let (san, nag) = split_suffix(&token);
+                    let m = board
+                        .parse_san(san)
+                        .map_err(|_| PgnError::IllegalMove(line_number, token.clone()))?;
+
+                    let mut m = PgnMove::new(board.get_san(&m.board), m.board);
+                    m.nags.extend(nag);
+                    moves.push(m);
+                }
+                Some(Token::OpenVariation) => {
+                    // The variation replaces the last move
+                    let before = match moves.len() {
+                        0 => {
+                            return Err(self.lexer.syntax_error("variation before the first move"))
+                        }
+                        1 => start,
+                        n => moves[n - 2].board,
+                    };
+                    let (alternative, _) = self.read_variation(before, false)?;
+                    if let Some(m) = variation.moves.last_mut() {
+                        m.variations.push(alternative);
+                    }
+                }
+                Some(Token::CloseVariation) => {
+                    if main_line {
+                        return Err(self.lexer.syntax_error("unmatched ')'"));
+                    }
+                    return Ok((variation, None));
+                }
+                Some(Token::Result(result)) => {
+                    if !main_line {
+                        return Err(self.lexer.syntax_error("game result inside a variation"));
+                    }
+                    return Ok((variation, Some(result)));
+                }
+            }
+        }
+    }
+}
+impl<R: Read> Iterator for PgnReader<R> {
+    type Item = Result<PgnGame, PgnError>;
+    /// Games that can not be read are reported as errors, after which reading continues from the
+    /// next game. Reading stops after the first IO error.
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let res = if self.recovering {
+            self.skip_game().and_then(|_| self.read_game())
+        } else {
+            self.read_game()
+        };
+        match res {
+            Ok(game) => {
+                self.recovering = false;
+                game.map(Ok)
+            }
+            Err(e) => {
+                self.failed = matches!(e, PgnError::Io(_));
+                self.recovering = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
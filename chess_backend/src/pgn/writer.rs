@@ -0,0 +1,148 @@
+use std::{
+    fmt::Display,
+    io::{self, Write},
+};
+
+use super::{PgnGame, Variation};
+use crate::{Board, Colour};
+
+// Export format keeps lines of movetext within 80 columns
+const MAX_LINE_LENGTH: usize = 80;
+
+// Tags that are always written (in this order) along with their values when unknown
+const SEVEN_TAG_ROSTER: [(&str, &str); 7] = [
+    ("Event", "?"),
+    ("Site", "?"),
+    ("Date", "????.??.??"),
+    ("Round", "?"),
+    ("White", "?"),
+    ("Black", "?"),
+    ("Result", "*"),
+];
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+// Token ending the current line of movetext
+const LINE_BREAK: &str = "\n";
+
+fn push_comment(tokens: &mut Vec<String>, comment: &str) {
+    let mut words: Vec<String> = comment.split_whitespace().map(String::from).collect();
+    // A `}` would end a brace comment early, so such comments run to the end of the line instead
+    if comment.contains('}') {
+        tokens.push(format!("; {}", words.join(" ")));
+        tokens.push(String::from(LINE_BREAK));
+        return;
+    }
+    // Each word is a separate token so that comments can be wrapped
+    if words.is_empty() {
+        tokens.push(String::from("{}"));
+        return;
+    }
+    words[0].insert(0, '{');
+    if let Some(last) = words.last_mut() {
+        last.push('}');
+    }
+    tokens.extend(words);
+}
+
+fn push_variation(tokens: &mut Vec<String>, start: Board, variation: &Variation) {
+    if let Some(comment) = &variation.comment {
+        push_comment(tokens, comment);
+    }
+
+    let mut board = start;
+    // Black moves are only numbered at the start of a line or after an interruption
+    let mut interrupted = true;
+    for m in &variation.moves {
+        if board.side_to_move() == Colour::White {
+            tokens.push(format!("{}.", board.fullmove()));
+        } else if interrupted {
+            tokens.push(format!("{}...", board.fullmove()));
+        }
+        tokens.push(m.san.to_string());
+        interrupted = false;
+
+        for nag in &m.nags {
+            tokens.push(format!("${nag}"));
+        }
+        for comment in &m.comments {
+            push_comment(tokens, comment);
+            interrupted = true;
+        }
+        for alternative in &m.variations {
+            let mut sub_tokens = Vec::new();
+            push_variation(&mut sub_tokens, board, alternative);
+            match sub_tokens.first_mut() {
+                Some(first) => first.insert(0, '('),
+                None => sub_tokens.push(String::from("(")),
+            }
+            match sub_tokens.last_mut() {
+                Some(last) if last != LINE_BREAK => last.push(')'),
+                _ => sub_tokens.push(String::from(")")),
+            }
+            tokens.extend(sub_tokens);
+            interrupted = true;
+        }
+
+        board = m.board;
+    }
+}
+
+impl PgnGame {
+    /// Writes the game in PGN export format, with the seven tag roster first and the movetext
+    /// wrapped to lines of at most 80 characters.
+    pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        for (name, default) in SEVEN_TAG_ROSTER {
+            let value = if name == "Result" {
+                self.result.as_str()
+            } else {
+                self.tag(name).unwrap_or(default)
+            };
+            writeln!(w, "[{name} \"{}\"]", escape(value))?;
+        }
+        for (name, value) in &self.tags {
+            if !SEVEN_TAG_ROSTER.iter().any(|(n, _)| n == name) {
+                writeln!(w, "[{name} \"{}\"]", escape(value))?;
+            }
+        }
+        writeln!(w)?;
+
+        let mut tokens = Vec::new();
+        push_variation(&mut tokens, self.start, &self.main_line);
+        tokens.push(self.result.to_string());
+
+        let mut line = String::new();
+        for token in tokens {
+            if token == LINE_BREAK {
+                writeln!(w, "{line}")?;
+                line.clear();
+                continue;
+            }
+            if !line.is_empty() && line.len() + 1 + token.len() > MAX_LINE_LENGTH {
+                writeln!(w, "{line}")?;
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line += &token;
+        }
+        writeln!(w, "{line}")?;
+        // Games are separated by a blank line
+        writeln!(w)
+    }
+
+    pub fn to_pgn(&self) -> String {
+        let mut res = Vec::new();
+        self.write(&mut res)
+            .expect("Writing to a Vec should not fail");
+        String::from_utf8(res).expect("PGN should be valid UTF-8")
+    }
+}
+impl Display for PgnGame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_pgn())
+    }
+}
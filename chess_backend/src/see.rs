@@ -0,0 +1,105 @@
+// Static exchange evaluation SEE
+use crate::{Board, Colour, Move, Piece};
+
+/// Values of the pieces in centipawns as used by `Board::see`. The king is worth more than all
+/// other pieces together so that it is never exchanged.
+pub fn see_value(piece: Piece) -> i32 {
+    match piece {
+        Piece::Pawn(_) => 100,
+        Piece::Knight(_) => 320,
+        Piece::Bishop(_) => 330,
+        Piece::Rook(_) => 500,
+        Piece::Queen(_) => 900,
+        Piece::King(_) => 20000,
+    }
+}
+
+impl Board {
+    // The least valuable of the given attackers, along with its square
+    fn least_valuable_attacker(&self, attackers: u64, colour: Colour) -> Option<(Piece, i32)> {
+        let side = self.base.get_side(colour);
+        [
+            (side.pawns, Piece::Pawn(colour)),
+            (side.knights, Piece::Knight(colour)),
+            (side.bishops, Piece::Bishop(colour)),
+            (side.rooks, Piece::Rook(colour)),
+            (side.queens, Piece::Queen(colour)),
+            (side.king, Piece::King(colour)),
+        ]
+        .into_iter()
+        .find(|(bitboard, _)| bitboard & attackers != 0)
+        .map(|(bitboard, piece)| (piece, (bitboard & attackers).trailing_zeros() as i32))
+    }
+
+    /// Static exchange evaluation of a move: the material (in centipawns, see `see_value`) won
+    /// by the side making the move if both sides keep recapturing on the destination square with
+    /// their least valuable piece for as long as it is favourable. Pieces lined up behind each
+    /// other (x-rays) are taken into account, pins are not.
+    ///
+    /// Non-captures are evaluated as well, e.g. a piece moving to an attacked square has a
+    /// negative SEE. Castling always has a SEE of 0.
+    pub fn see(&self, m: Move) -> i32 {
+        if m.castling().is_some() {
+            return 0;
+        }
+        let (origin, dest) = (m.origin(), m.dest());
+        let colour = self.side_to_move();
+        let mut occupancy = self.base.white_occupied | self.base.black_occupied;
+
+        let mut gain = [0; 32];
+        if m.is_en_passent() {
+            gain[0] = see_value(Piece::Pawn(colour.other()));
+            let captured = dest
+                .backward(colour)
+                .expect("En passent should not end on a back rank");
+            occupancy ^= captured.bitboard();
+        } else if let Some(captured) = self.piece_on(dest) {
+            gain[0] = see_value(captured);
+        }
+        // The piece standing on the destination square, which is the next one to be captured
+        let mut on_dest = match m.promotion() {
+            Some(piece) => {
+                gain[0] += see_value(piece) - see_value(Piece::Pawn(colour));
+                piece
+            }
+            None => self
+                .piece_on(origin)
+                .expect("Move should start on a square with a piece"),
+        };
+        occupancy ^= origin.bitboard();
+
+        let mut depth = 0;
+        let mut side = colour.other();
+        loop {
+            let attackers = self.attackers_with_occupancy(dest.as_int(), occupancy);
+            let Some((attacker, square)) = self.least_valuable_attacker(attackers, side) else {
+                break;
+            };
+            // The king can only recapture if the square is no longer defended
+            if attacker == Piece::King(side)
+                && attackers & self.base.get_side_occupied(side.other()) != 0
+            {
+                break;
+            }
+
+            // Material won by `side` if it recaptures, given what has been won so far
+            depth += 1;
+            gain[depth] = see_value(on_dest) - gain[depth - 1];
+            occupancy ^= 1 << square;
+            on_dest = attacker;
+            side = side.other();
+        }
+
+        // Either side can choose to stop recapturing
+        while depth > 0 {
+            gain[depth - 1] = -(-gain[depth - 1]).max(gain[depth]);
+            depth -= 1;
+        }
+        gain[0]
+    }
+
+    /// Whether the SEE of the move is at least `threshold`
+    pub fn see_ge(&self, m: Move, threshold: i32) -> bool {
+        self.see(m) >= threshold
+    }
+}
@@ -0,0 +1,60 @@
+use crate::{init, utils::squares::*, BitBoard, Board, Colour, START_POSITION};
+
+fn bitboard(squares: &[Square]) -> BitBoard {
+    BitBoard(squares.iter().fold(0, |res, s| res | s.bitboard()))
+}
+
+#[test]
+fn attackers() {
+    init();
+    let board = Board::from_fen("4k3/8/8/3p4/4P3/2N5/8/3QK3 w - - 0 1").unwrap();
+    assert_eq!(
+        board.attackers_to(d5, Colour::White),
+        bitboard(&[e4, c3, d1])
+    );
+    assert_eq!(board.attackers_to(d5, Colour::Black), bitboard(&[]));
+    assert_eq!(board.attackers_to(e4, Colour::Black), bitboard(&[d5]));
+    assert!(board.is_square_attacked(d5, Colour::White));
+    assert!(!board.is_square_attacked(d4, Colour::Black));
+
+    let board = Board::from(START_POSITION);
+    let attacked = board.attacked_squares(Colour::White);
+    // Every square on the third rank is attacked, nothing beyond it
+    assert_eq!(attacked.0 & 0xFF0000, 0xFF0000);
+    assert_eq!(attacked.0 & !0xFFFFFF, 0);
+    assert!(board.is_square_attacked(f6, Colour::Black));
+    assert!(!board.is_square_attacked(e5, Colour::White));
+
+    // Pieces of the same colour are included, as they are defended
+    assert_eq!(board.attacks_from(b1), bitboard(&[a3, c3, d2]));
+    assert_eq!(board.attacks_from(e2), bitboard(&[d3, f3]));
+    assert_eq!(board.attacks_from(a1), bitboard(&[a2, b1]));
+    assert_eq!(board.attacks_from(e4), bitboard(&[]));
+}
+
+#[test]
+fn checkers() {
+    init();
+    assert_eq!(Board::from(START_POSITION).checkers(), bitboard(&[]));
+    let board = Board::from_fen("4k3/8/8/8/8/5n2/8/r3K3 w - - 0 1").unwrap();
+    assert_eq!(board.checkers(), bitboard(&[a1, f3]));
+    assert!(board.is_check());
+}
+
+#[test]
+fn pins() {
+    init();
+    let board = Board::from_fen("6k1/8/8/8/1b2r3/8/3NB3/4K3 w - - 0 1").unwrap();
+    assert_eq!(board.pinned_pieces(Colour::White), bitboard(&[d2, e2]));
+    assert_eq!(board.pinned_pieces(Colour::Black), bitboard(&[]));
+
+    // Two pieces in the way, or an enemy piece in the way, is not a pin
+    let board = Board::from_fen("6k1/8/4r3/8/8/4P3/4B3/4K3 w - - 0 1").unwrap();
+    assert_eq!(board.pinned_pieces(Colour::White), bitboard(&[]));
+    let board = Board::from_fen("4k3/4n3/8/8/8/8/8/4R1K1 w - - 0 1").unwrap();
+    assert_eq!(board.pinned_pieces(Colour::Black), bitboard(&[e7]));
+    let board = Board::from_fen("4k3/4n3/4p3/8/8/8/8/4R1K1 w - - 0 1").unwrap();
+    assert_eq!(board.pinned_pieces(Colour::Black), bitboard(&[]));
+    let board = Board::from_fen("4k3/4n3/8/8/4P3/8/8/4R1K1 w - - 0 1").unwrap();
+    assert_eq!(board.pinned_pieces(Colour::Black), bitboard(&[]));
+}
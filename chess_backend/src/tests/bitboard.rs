@@ -0,0 +1,109 @@
+use crate::{
+    init, utils::squares::*, BitBoard, Board, Colour, Direction, Piece, Pieces, START_POSITION,
+};
+
+#[test]
+fn operators() {
+    let a = BitBoard::from(e4) | BitBoard::from(d5);
+    let b = BitBoard::from(e4) | BitBoard::from(a1);
+    assert_eq!(a & b, BitBoard::from(e4));
+    assert_eq!(a ^ b, [d5, a1].into_iter().collect());
+    assert_eq!((!a).popcount(), 62);
+    assert_eq!(!BitBoard::EMPTY, BitBoard::FULL);
+
+    let mut c = a;
+    c &= b;
+    c |= BitBoard::from(h8);
+    c ^= BitBoard::from(e4);
+    assert_eq!(c, BitBoard::from(h8));
+    c.set(a2);
+    c.clear(h8);
+    assert!(c.contains(a2) && !c.contains(h8));
+}
+
+#[test]
+fn squares() {
+    let mut bitboard: BitBoard = [h8, c3, a1, e4].into_iter().collect();
+    assert_eq!(bitboard.popcount(), 4);
+    assert_eq!(bitboard.lsb(), Some(a1));
+    assert_eq!(bitboard.iter().collect::<Vec<_>>(), vec![a1, c3, e4, h8]);
+    assert_eq!(bitboard.iter().len(), 4);
+
+    assert_eq!(bitboard.pop_lsb(), Some(a1));
+    assert_eq!(bitboard.pop_lsb(), Some(c3));
+    assert_eq!(bitboard, [e4, h8].into_iter().collect());
+    bitboard.pop_lsb();
+    bitboard.pop_lsb();
+    assert!(bitboard.is_empty());
+    assert_eq!(bitboard.pop_lsb(), None);
+    assert_eq!(BitBoard::EMPTY.into_iter().next(), None);
+}
+
+#[test]
+fn masks_and_shifts() {
+    assert_eq!(BitBoard::file(File::A).popcount(), 8);
+    assert!(BitBoard::file(File::H).contains(h5));
+    assert_eq!(BitBoard::rank(Rank::Second), BitBoard(0xff00));
+    assert_eq!(
+        BitBoard::file(File::E) & BitBoard::rank(Rank::Fourth),
+        BitBoard::from(e4)
+    );
+
+    let e4_bitboard = BitBoard::from(e4);
+    for (direction, square) in [
+        (Direction::North, e5),
+        (Direction::South, e3),
+        (Direction::East, f4),
+        (Direction::West, d4),
+        (Direction::NorthEast, f5),
+        (Direction::NorthWest, d5),
+        (Direction::SouthEast, f3),
+        (Direction::SouthWest, d3),
+    ] {
+        assert_eq!(e4_bitboard.shift(direction), BitBoard::from(square));
+    }
+    // Squares on the edge are dropped instead of wrapping around
+    assert!(BitBoard::from(h4).shift(Direction::East).is_empty());
+    assert!(BitBoard::from(a4).shift(Direction::NorthWest).is_empty());
+    assert!(BitBoard::from(e8).shift(Direction::North).is_empty());
+    assert_eq!(
+        BitBoard::file(File::H).shift(Direction::West),
+        BitBoard::file(File::G)
+    );
+}
+
+#[test]
+fn board_bitboards() {
+    init();
+    let board = Board::from(START_POSITION);
+    assert_eq!(
+        board.pieces(Piece::Knight(Colour::White)),
+        [b1, g1].into_iter().collect()
+    );
+    assert_eq!(board.pieces(Piece::King(Colour::Black)), BitBoard::from(e8));
+    assert_eq!(
+        board.occupied(Colour::White),
+        BitBoard::rank(Rank::First) | BitBoard::rank(Rank::Second)
+    );
+
+    // Converting to piece lists and back gives the same bitboards
+    let pieces = Pieces::from(board.base.black);
+    assert_eq!(pieces.rooks, vec![a8.as_int(), h8.as_int()]);
+    assert_eq!(crate::piece_map_bitboards::from(&pieces), board.base.black);
+}
+
+#[test]
+fn display() {
+    let bitboard: BitBoard = [a1, h8].into_iter().collect();
+    let expected = "\
+. . . . . . . 1 
+. . . . . . . . 
+. . . . . . . . 
+. . . . . . . . 
+. . . . . . . . 
+. . . . . . . . 
+. . . . . . . . 
+1 . . . . . . . 
+";
+    assert_eq!(bitboard.to_string(), expected);
+}
@@ -0,0 +1,148 @@
+use crate::{init, utils::squares::*, Board, Side, UciMove, START_POSITION};
+
+// Positions from the Chess960 perft results of the chessprogramming wiki
+const POSITIONS: [(&str, [u64; 4]); 9] = [
+    (
+        "bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9",
+        [21, 528, 12189, 326672],
+    ),
+    (
+        "2nnrbkr/p1qppppp/8/1ppb4/6PP/3PP3/PPP2P2/BQNNRBKR w HEhe - 1 9",
+        [21, 807, 18002, 667366],
+    ),
+    (
+        "b1q1rrkb/pppppppp/3nn3/8/P7/1PPP4/4PPPP/BQNNRKRB w GE - 1 9",
+        [20, 479, 10471, 273318],
+    ),
+    (
+        "qbbnnrkr/2pp2pp/p7/1p2pp2/8/P3PP2/1PPP1KPP/QBBNNR1R w hf - 0 9",
+        [22, 593, 13440, 382958],
+    ),
+    (
+        "1nbbnrkr/p1p1ppp1/3p4/1p3P1p/3Pq2P/8/PPP1P1P1/QNBBNRKR w HFhf - 0 9",
+        [28, 1120, 31058, 1171749],
+    ),
+    (
+        "qnbnr1kr/ppp1b1pp/4p3/3p1p2/8/2NPP3/PPP1BPPP/QNB1R1KR w HEhe - 1 9",
+        [29, 899, 26578, 824055],
+    ),
+    (
+        "q1bnrkr1/ppppp2p/2n2p2/4b1p1/2NP4/8/PPP1PPPP/QNB1RRKB w ge - 1 9",
+        [30, 860, 24566, 732757],
+    ),
+    (
+        "qbn1brkr/ppp1p1p1/2n4p/3p1p2/P7/6PP/QPPPPP2/1BNNBRKR w HFhf - 0 9",
+        [25, 635, 17054, 465806],
+    ),
+    (
+        "qn1rbbkr/ppp2p1p/1n1pp1p1/8/3P4/P6P/1PP1PPPK/QNNRBB1R w hd - 2 9",
+        [28, 811, 23175, 679699],
+    ),
+];
+
+#[test]
+fn perft() {
+    init();
+    for (fen, expected) in POSITIONS {
+        let board = Board::from_fen(fen).unwrap();
+        assert!(board.is_chess960(), "{fen}");
+        for (i, nodes) in expected.iter().enumerate() {
+            let depth = i + 1;
+            assert_eq!(board.perft(depth), *nodes, "perft({depth}) of {fen}");
+        }
+    }
+}
+
+#[test]
+fn castling_rights() {
+    init();
+    let board =
+        Board::from_fen("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9")
+            .unwrap();
+    assert_eq!(
+        board.castling_rook_file(crate::Colour::White, Side::King),
+        Some(7)
+    );
+    assert_eq!(
+        board.castling_rook_file(crate::Colour::Black, Side::Queen),
+        Some(5)
+    );
+
+    // KQkq refers to the outermost rooks
+    let x_fen = Board::from_fen("rk2r3/8/8/8/8/8/8/RK2R3 w KQkq - 0 1").unwrap();
+    assert!(x_fen.is_chess960());
+    assert_eq!(
+        x_fen.castling_rook_file(crate::Colour::White, Side::King),
+        Some(4)
+    );
+    assert_eq!(
+        x_fen.castling_rook_file(crate::Colour::Black, Side::Queen),
+        Some(0)
+    );
+
+    // Standard positions are not Chess960, no matter how the castling rights are written
+    let shredder =
+        Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap();
+    assert!(!shredder.is_chess960());
+
+    for fen in ["KQkqK", "HAhaH", "B", "KH"] {
+        let fen = format!("rk2r3/8/8/8/8/8/8/RK2R3 w {fen} - 0 1");
+        assert!(Board::from_fen(&fen).is_err(), "{fen}");
+    }
+}
+
+#[test]
+fn fen_round_trip() {
+    init();
+    for (fen, _) in POSITIONS {
+        let board = Board::from_fen(fen).unwrap();
+        assert_eq!(board.into_shredder_fen(), fen);
+        assert_eq!(Board::from_fen(&board.into_fen()).unwrap(), board);
+    }
+
+    // X-FEN uses file letters only when there is another rook further out
+    let fen = "1r2k1rr/8/8/8/8/8/8/RR2K2R w KBgq - 0 1";
+    let board = Board::from_fen(fen).unwrap();
+    assert_eq!(board.into_fen(), fen);
+    assert_eq!(
+        board.into_shredder_fen(),
+        "1r2k1rr/8/8/8/8/8/8/RR2K2R w HBgb - 0 1"
+    );
+
+    let start = Board::from(START_POSITION);
+    assert_eq!(start.into_fen(), START_POSITION.trim());
+    assert_eq!(
+        start.into_shredder_fen(),
+        "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w HAha - 0 1"
+    );
+}
+
+#[test]
+fn castling_moves() {
+    init();
+    // The king is already on its destination square, so only the rook moves
+    let board = Board::from_fen("1r4kr/8/8/8/8/8/8/1R4KR w HBhb - 0 1").unwrap();
+    let m = board.parse_uci_move("g1h1").unwrap();
+    assert_eq!(m.to_uci(), UciMove::new(g1, h1, None));
+    assert_eq!(board.get_san(&m.board).to_string(), "O-O");
+    assert_eq!(m.board.into_fen(), "1r4kr/8/8/8/8/8/8/1R3RK1 b kq - 1 1");
+
+    // The king and rook swap squares
+    let m = board.parse_uci_move("g1b1").unwrap();
+    assert_eq!(board.get_san(&m.board).to_string(), "O-O-O");
+    assert_eq!(m.board.into_fen(), "1r4kr/8/8/8/8/8/8/2KR3R b kq - 1 1");
+    let mut san_board = board;
+    san_board.make_san_move(board.get_san(&m.board)).unwrap();
+    assert_eq!(san_board, m.board);
+
+    // Castling is not allowed through attacked squares, even if the king does not move there
+    let attacked = Board::from_fen("1r2r1k1/8/8/8/8/8/8/1R4KR w B - 0 1").unwrap();
+    assert!(attacked.parse_uci_move("g1b1").is_err());
+
+    // Standard castling keeps the king's move in UCI
+    let standard = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+    assert!(!standard.is_chess960());
+    let m = standard.parse_uci_move("e1g1").unwrap();
+    assert_eq!(standard.get_san(&m.board).to_string(), "O-O");
+    assert!(standard.parse_uci_move("e1h1").is_err());
+}
@@ -0,0 +1,128 @@
+use crate::{init, Board, Epd, EpdError, FenError, FenField};
+
+const START: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq -";
+
+const WAC_1: &str =
+    r#"2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id "WAC.001";"#;
+
+#[test]
+fn parse_operations() {
+    init();
+    let epd: Epd = WAC_1.parse().unwrap();
+    assert_eq!(epd.id(), Some("WAC.001"));
+    assert_eq!(epd.operation("bm"), Some(&["Qg6".to_string()][..]));
+    assert_eq!(epd.comment(), None);
+    // Clocks default to 0 and 1 without hmvc and fmvn
+    assert_eq!(
+        epd.into_fen(),
+        "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - 0 1"
+    );
+
+    let epd: Epd = r#"8/8/8/8/8/8/8/K6k w - - c0 "quoted; with spaces" ;hmvc 12;fmvn 40"#
+        .parse()
+        .unwrap();
+    assert_eq!(epd.comment(), Some("quoted; with spaces"));
+    assert_eq!(epd.into_fen(), "8/8/8/8/8/8/8/K6k w - - 12 40");
+}
+
+#[test]
+fn resolve_moves() {
+    init();
+    let epd: Epd = WAC_1.parse().unwrap();
+    let best = epd.best_moves().unwrap();
+    assert_eq!(best.len(), 1);
+    assert_eq!(best[0].to_string(), "Qg6");
+    assert!(epd.avoid_moves().unwrap().is_empty());
+
+    let epd: Epd = format!("{} bm e4 d4; am f3;", START).parse().unwrap();
+    let best: Vec<String> = epd
+        .best_moves()
+        .unwrap()
+        .iter()
+        .map(|m| m.to_string())
+        .collect();
+    assert_eq!(best, ["e4", "d4"]);
+    assert_eq!(epd.avoid_moves().unwrap()[0].to_string(), "f3");
+
+    let epd: Epd = format!("{} bm e5;", START).parse().unwrap();
+    assert_eq!(
+        epd.best_moves(),
+        Err(EpdError::IllegalMove("e5".to_string()))
+    );
+}
+
+#[test]
+fn round_trip() {
+    init();
+    let epd: Epd = WAC_1.parse().unwrap();
+    assert_eq!(epd.to_string(), WAC_1);
+
+    let board = Board::from_fen("r3k2r/8/8/8/4Pp2/8/8/R3K2R b KQkq e3 5 31").unwrap();
+    let mut epd = Epd::new(board);
+    epd.set_operation("bm", vec!["fxe3".to_string()]);
+    epd.set_operation("c0", vec!["en passent".to_string()]);
+    let written = epd.to_string();
+    assert_eq!(
+        written,
+        r#"r3k2r/8/8/8/4Pp2/8/8/R3K2R b KQkq e3 hmvc 5; fmvn 31; bm fxe3; c0 "en passent";"#
+    );
+    let read: Epd = written.parse().unwrap();
+    assert_eq!(read, epd);
+    assert_eq!(read.into_fen(), board.into_fen());
+}
+
+#[test]
+fn clocks_written_from_board() {
+    init();
+    let board =
+        Board::from_fen("2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - 7 25").unwrap();
+
+    // Record without clocks
+    let mut epd: Epd = WAC_1.parse().unwrap();
+    epd.board = board;
+    let written = epd.to_string();
+    assert_eq!(
+        written,
+        r#"2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - hmvc 7; fmvn 25; bm Qg6; id "WAC.001";"#
+    );
+    assert_eq!(written.parse::<Epd>().unwrap().into_fen(), epd.into_fen());
+
+    // Stale clocks stored as operations
+    let mut epd: Epd =
+        "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - hmvc 0; fmvn 1; bm Qg6;"
+            .parse()
+            .unwrap();
+    epd.board = board;
+    let written = epd.to_string();
+    assert!(
+        written.ends_with(" - - hmvc 7; fmvn 25; bm Qg6;"),
+        "{written}"
+    );
+    assert_eq!(written.parse::<Epd>().unwrap().into_fen(), epd.into_fen());
+}
+
+#[test]
+fn invalid_epd() {
+    init();
+    assert_eq!(
+        "8/8/8/8/8/8/8/K6k w -".parse::<Epd>(),
+        Err(EpdError::InvalidFen(FenError::MissingField(
+            FenField::EnPassent
+        )))
+    );
+    assert_eq!(
+        "8/8/8/8/8/8/8/K6k w - - hmvc x;".parse::<Epd>(),
+        Err(EpdError::InvalidFen(FenError::InvalidClock(
+            FenField::Halfmove,
+            "x".to_string()
+        )))
+    );
+    assert!(matches!(
+        r#"8/8/8/8/8/8/8/K6k w - - c0 "unterminated;"#.parse::<Epd>(),
+        Err(EpdError::Syntax(_))
+    ));
+    assert!(matches!(
+        "8/8/8/8/8/8/8/K6k w - - 1bm e4;".parse::<Epd>(),
+        Err(EpdError::Syntax(_))
+    ));
+}
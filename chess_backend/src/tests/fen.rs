@@ -1,6 +1,7 @@
 use crate::{
-    Board, CASTLE_KINGSIDE_POSITION, CASTLE_QUEENSIDE_POSITION, CHECK_POSITION, CMK_POSITION,
-    EMPTY_BOARD, KILLER_POSITION, PROMOTION_POSITION, START_POSITION, TRICKY_POSITION,
+    Board, FenError, FenField, CASTLE_KINGSIDE_POSITION, CASTLE_QUEENSIDE_POSITION, CHECK_POSITION,
+    CMK_POSITION, EMPTY_BOARD, KILLER_POSITION, PROMOTION_POSITION, START_POSITION,
+    TRICKY_POSITION,
 };
 
 #[test]
@@ -21,7 +22,99 @@ fn fen_conversion() {
         let first_iter_fen = board.into_fen();
         assert_eq!(first_iter_fen.trim(), pos.trim());
 
-        let second_iter_fen = Board::from(&first_iter_fen).into_fen();
+        // The empty board has no kings, so only the built-in constant skips validation
+        if pos.trim() == EMPTY_BOARD.trim() {
+            assert!(Board::from_fen(&first_iter_fen).is_err());
+            continue;
+        }
+
+        let second_iter_fen = Board::from_fen(&first_iter_fen).unwrap().into_fen();
         assert_eq!(first_iter_fen, second_iter_fen);
     }
 }
+
+#[test]
+fn parse_traits() {
+    let fen = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1";
+    let board = Board::from_fen(fen).unwrap();
+
+    assert_eq!(fen.parse::<Board>().unwrap(), board);
+    assert_eq!(Board::try_from(fen).unwrap(), board);
+    assert_eq!(Board::try_from(fen.to_string()).unwrap(), board);
+    assert!(Board::try_from("not a fen").is_err());
+}
+
+#[test]
+fn fen_errors() {
+    for (fen, expected) in [
+        ("", FenError::MissingField(FenField::Placement)),
+        (
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR",
+            FenError::MissingField(FenField::SideToMove),
+        ),
+        (
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0",
+            FenError::MissingField(FenField::Fullmove),
+        ),
+        (
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 x",
+            FenError::TooManyFields,
+        ),
+        (
+            "rnbqkbnr/pppppppp/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            FenError::WrongRankCount(7),
+        ),
+        (
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBN w KQkq - 0 1",
+            FenError::WrongRankLength(1),
+        ),
+        (
+            "rnbqkbnrr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            FenError::WrongRankLength(8),
+        ),
+        (
+            "rnbqkbnr/pppppppp/9/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            FenError::WrongRankLength(6),
+        ),
+        (
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKXNR w KQkq - 0 1",
+            FenError::InvalidPiece('X'),
+        ),
+        (
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1",
+            FenError::InvalidSideToMove("x".to_string()),
+        ),
+        (
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkA - 0 1",
+            FenError::InvalidCastling("KQkA".to_string()),
+        ),
+        (
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KKq - 0 1",
+            FenError::InvalidCastling("KKq".to_string()),
+        ),
+        (
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq e9 0 1",
+            FenError::InvalidEnPassent("e9".to_string()),
+        ),
+        (
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e6 0 1",
+            FenError::InvalidEnPassent("e6".to_string()),
+        ),
+        (
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - -1 1",
+            FenError::InvalidClock(FenField::Halfmove, "-1".to_string()),
+        ),
+        (
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 one",
+            FenError::InvalidClock(FenField::Fullmove, "one".to_string()),
+        ),
+    ] {
+        assert_eq!(Board::from_fen(fen).unwrap_err(), expected, "{fen}");
+    }
+
+    assert_eq!(
+        FenError::InvalidCastling("KKq".to_string()).field(),
+        Some(FenField::Castling)
+    );
+    assert_eq!(FenError::TooManyFields.field(), None);
+}
@@ -0,0 +1,94 @@
+use crate::{
+    init, Board, Colour, FinishedState, Game, GameState, ReasonDraw, ReasonWin, SanMove,
+    START_POSITION,
+};
+
+fn play(game: &mut Game, moves: &[&str]) {
+    for m in moves {
+        let san = SanMove::from_string(*m, game.board()).unwrap();
+        game.play_san(san).unwrap();
+    }
+}
+
+fn draw(reason: ReasonDraw) -> GameState {
+    GameState::Finished(FinishedState::Draw(reason))
+}
+
+#[test]
+fn halfmove_clock() {
+    init();
+    let mut game = Game::from(Board::from(START_POSITION));
+
+    play(&mut game, &["Nf3", "Nc6"]);
+    assert_eq!(game.board().halfmove(), 2);
+    // Pawn move
+    play(&mut game, &["e4"]);
+    assert_eq!(game.board().halfmove(), 0);
+    play(&mut game, &["Nf6", "Bc4"]);
+    assert_eq!(game.board().halfmove(), 2);
+    // Capture
+    play(&mut game, &["Nxe4"]);
+    assert_eq!(game.board().halfmove(), 0);
+    // Castling is neither
+    play(&mut game, &["O-O"]);
+    assert_eq!(game.board().halfmove(), 1);
+}
+
+#[test]
+fn repetition() {
+    init();
+    let mut game = Game::default();
+    let shuffle = ["Nf3", "Nf6", "Ng1", "Ng8"];
+
+    play(&mut game, &shuffle);
+    assert_eq!(game.repetitions(), 2);
+    assert_eq!(game.get_game_state(), GameState::Ongoing);
+
+    play(&mut game, &shuffle);
+    assert_eq!(game.repetitions(), 3);
+    assert_eq!(game.get_game_state(), draw(ReasonDraw::ThreefoldRepetition));
+    // A single board knows nothing of the repetition
+    assert_eq!(game.board().get_game_state(), GameState::Ongoing);
+
+    play(&mut game, &shuffle);
+    play(&mut game, &shuffle);
+    assert_eq!(game.repetitions(), 5);
+    assert_eq!(game.get_game_state(), draw(ReasonDraw::FivefoldRepetition));
+
+    // A pawn move means no earlier position can be repeated
+    play(&mut game, &["e4"]);
+    assert_eq!(game.repetitions(), 1);
+    assert_eq!(game.get_game_state(), GameState::Ongoing);
+
+    // Black can not take en passent after e4, so the position is the same once the knights are
+    // back even though the killer square is gone
+    play(&mut game, &["Nf6", "Nf3", "Ng8", "Ng1"]);
+    assert_eq!(game.repetitions(), 2);
+    play(&mut game, &["Nf6", "Nf3", "Ng8", "Ng1"]);
+    assert_eq!(game.get_game_state(), draw(ReasonDraw::ThreefoldRepetition));
+}
+
+#[test]
+fn halfmove_limit() {
+    init();
+    let fen = |halfmove: usize| format!("4k3/8/8/8/8/8/8/R3K3 w - - {halfmove} 80");
+
+    let board = Board::from_fen(&fen(99)).unwrap();
+    assert_eq!(board.get_game_state(), GameState::Ongoing);
+
+    let board = Board::from_fen(&fen(100)).unwrap();
+    assert_eq!(board.get_game_state(), draw(ReasonDraw::HalfmoveLimit));
+
+    let board = Board::from_fen(&fen(150)).unwrap();
+    assert_eq!(
+        board.get_game_state(),
+        draw(ReasonDraw::SeventyFiveMoveRule)
+    );
+
+    // Checkmate on the last move takes precedence
+    let board = Board::from_fen("R3k3/8/4K3/8/8/8/8/8 b - - 120 80").unwrap();
+    assert_eq!(
+        board.get_game_state(),
+        GameState::Finished(FinishedState::Win(Colour::White, ReasonWin::Checkmate))
+    );
+}
@@ -0,0 +1,106 @@
+use std::collections::HashSet;
+
+use crate::{
+    init, Board, ChessMove, CASTLE_KINGSIDE_POSITION, KILLER_POSITION, PROMOTION_POSITION,
+    START_POSITION, TRICKY_POSITION,
+};
+
+use super::perft::POSITIONS;
+
+// White can capture en passent, and promote with and without capturing (only the capturing
+// promotions give check)
+const SPECIAL_MOVES_POSITION: &str = "1n2k3/P7/8/3pP3/8/8/8/4K3 w - d6 0 1";
+
+fn boards(moves: &[ChessMove]) -> HashSet<Board> {
+    moves.iter().map(|m| m.board).collect()
+}
+
+fn check_generators(board: Board, depth: usize) {
+    let legal = board.generate_legal_moves();
+    let captures = board.generate_captures();
+    let quiets = board.generate_quiets();
+
+    // Every legal move is either a capture or a quiet move, but never both
+    assert_eq!(
+        captures.len() + quiets.len(),
+        legal.len(),
+        "{}",
+        board.into_fen()
+    );
+    let captures = boards(&captures);
+    let quiets = boards(&quiets);
+    assert!(captures.is_disjoint(&quiets), "{}", board.into_fen());
+    assert_eq!(&captures | &quiets, boards(&legal), "{}", board.into_fen());
+
+    let other = board.side_to_move().other();
+    for m in &legal {
+        let capture = m.board.base.get_side(other) != board.base.get_side(other);
+        assert_eq!(captures.contains(&m.board), capture, "{}", board.into_fen());
+    }
+    assert_eq!(
+        boards(&board.generate_checks()),
+        legal
+            .iter()
+            .filter(|m| m.board.is_check())
+            .map(|m| m.board)
+            .collect(),
+        "{}",
+        board.into_fen()
+    );
+
+    if depth > 1 {
+        for m in legal {
+            check_generators(m.board, depth - 1);
+        }
+    }
+}
+
+#[test]
+fn captures_and_quiets() {
+    init();
+    for board in [
+        Board::from(START_POSITION),
+        Board::from(TRICKY_POSITION),
+        Board::from(KILLER_POSITION),
+        Board::from(PROMOTION_POSITION),
+        Board::from(CASTLE_KINGSIDE_POSITION),
+    ] {
+        check_generators(board, 2);
+    }
+    for fen in POSITIONS.into_iter().chain([SPECIAL_MOVES_POSITION]) {
+        check_generators(Board::from_fen(fen).unwrap(), 2);
+    }
+}
+
+#[test]
+fn special_moves() {
+    init();
+    let board = Board::from_fen(SPECIAL_MOVES_POSITION).unwrap();
+    let captures: Vec<String> = board
+        .generate_captures()
+        .iter()
+        .map(|m| m.to_uci().to_string())
+        .collect();
+    for m in ["e5d6", "a7b8q", "a7b8n"] {
+        assert!(captures.contains(&m.to_string()), "{m}");
+    }
+    let quiets: Vec<String> = board
+        .generate_quiets()
+        .iter()
+        .map(|m| m.to_uci().to_string())
+        .collect();
+    for m in ["e5e6", "a7a8q", "a7a8r"] {
+        assert!(quiets.contains(&m.to_string()), "{m}");
+    }
+
+    // Capturing the knight opens the back rank, promoting on a8 does not
+    let checks: Vec<String> = board
+        .generate_checks()
+        .iter()
+        .map(|m| m.to_uci().to_string())
+        .collect();
+    assert!(checks.contains(&String::from("a7b8q")));
+    assert!(checks.contains(&String::from("a7b8r")));
+    assert!(!checks.contains(&String::from("a7b8n")));
+    assert!(!checks.contains(&String::from("a7a8q")));
+}
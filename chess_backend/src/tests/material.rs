@@ -0,0 +1,65 @@
+use crate::{init, Board, Colour, FinishedState, GameState, ReasonDraw};
+
+fn board(fen: &str) -> Board {
+    Board::from_fen(fen).unwrap()
+}
+
+#[test]
+fn insufficient_material() {
+    init();
+    for fen in [
+        // K vs K
+        "4k3/8/8/8/8/8/8/4K3 w - - 0 1",
+        // K + minor vs K
+        "4k3/8/8/8/8/8/8/4KB2 w - - 0 1",
+        "4k3/8/8/8/8/8/8/4KN2 b - - 0 1",
+        // Bishops on the same colour only
+        "4kb2/8/8/8/8/8/8/2B1K3 w - - 0 1",
+        "4k3/8/8/8/8/B7/8/2B1K3 w - - 0 1",
+    ] {
+        let board = board(fen);
+        assert!(board.is_insufficient_material(), "{fen}");
+        assert_eq!(
+            board.get_game_state(),
+            GameState::Finished(FinishedState::Draw(ReasonDraw::InsufficientMaterial)),
+            "{fen}"
+        );
+    }
+
+    for fen in [
+        // K + B + N vs K
+        "4k3/8/8/8/8/8/8/4KBN1 w - - 0 1",
+        // Bishop pair
+        "4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1",
+        // Opposite coloured bishops, a mate in the corner is possible
+        "4kb2/8/8/8/8/8/8/3BK3 w - - 0 1",
+        // Minor piece each, helpmates are possible
+        "4kn2/8/8/8/8/8/8/4KN2 w - - 0 1",
+        "4kn2/8/8/8/8/8/8/4KB2 w - - 0 1",
+        "4k3/8/8/8/8/8/8/4KNN1 w - - 0 1",
+        "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1",
+        "4k3/8/8/8/8/8/8/R3K3 w - - 0 1",
+    ] {
+        let board = board(fen);
+        assert!(!board.is_insufficient_material(), "{fen}");
+        assert_eq!(board.get_game_state(), GameState::Ongoing, "{fen}");
+    }
+}
+
+#[test]
+fn can_checkmate() {
+    init();
+    for (fen, white, black) in [
+        ("4k3/8/8/8/8/8/8/4KBN1 w - - 0 1", true, false),
+        ("4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1", true, false),
+        ("4k3/8/8/8/8/8/8/4KNN1 w - - 0 1", true, false),
+        ("4k3/8/8/8/8/B7/8/2B1K3 w - - 0 1", false, false),
+        // Not a dead position, but the knight can never mate on its own
+        ("4k3/4p3/8/8/8/8/8/4KN2 w - - 0 1", false, true),
+        ("r3k3/8/8/8/8/8/8/4KB2 w - - 0 1", false, true),
+    ] {
+        let board = board(fen);
+        assert_eq!(board.can_checkmate(Colour::White), white, "{fen}");
+        assert_eq!(board.can_checkmate(Colour::Black), black, "{fen}");
+    }
+}
@@ -3,3 +3,54 @@ mod squares;
 
 #[cfg(test)]
 mod fen;
+
+#[cfg(test)]
+mod perft;
+
+#[cfg(test)]
+mod zobrist;
+
+#[cfg(test)]
+mod game;
+
+#[cfg(test)]
+mod material;
+
+#[cfg(test)]
+mod uci;
+
+#[cfg(test)]
+mod pgn;
+
+#[cfg(test)]
+mod chess960;
+
+#[cfg(test)]
+mod moves;
+
+#[cfg(test)]
+mod generation;
+
+#[cfg(test)]
+mod see;
+
+#[cfg(test)]
+mod attacks;
+
+#[cfg(test)]
+mod validate;
+
+#[cfg(test)]
+mod bitboard;
+
+#[cfg(test)]
+mod epd;
+
+#[cfg(all(test, feature = "serde"))]
+mod serialization;
+
+#[cfg(test)]
+mod packed;
+
+#[cfg(test)]
+mod san;
@@ -0,0 +1,111 @@
+use std::collections::HashSet;
+
+use crate::{
+    init, utils::squares::*, Board, Colour, Move, Piece, KILLER_POSITION, PROMOTION_POSITION,
+    START_POSITION, TRICKY_POSITION,
+};
+
+use super::perft::POSITIONS;
+
+// Castling kingside leaves the king on its square
+const CHESS960_POSITION: &str = "1r4kr/8/8/8/8/8/8/1R4KR w HBhb - 0 1";
+
+fn perft_make_unmake(board: &mut Board, depth: usize) -> u64 {
+    let moves = board.generate_moves();
+    if depth == 1 {
+        return moves.len() as u64;
+    }
+    let mut res = 0;
+    for m in moves {
+        let undo = board.make_move(m);
+        res += perft_make_unmake(board, depth - 1);
+        board.unmake_move(m, undo);
+    }
+    res
+}
+
+// Every move should lead to the same board as the corresponding `ChessMove`, and unmaking it
+// should restore the original board exactly
+fn check_moves(board: Board, depth: usize) {
+    let chess_moves = board.generate_legal_moves();
+    let moves = board.generate_moves();
+    assert_eq!(moves.len(), chess_moves.len(), "{}", board.into_fen());
+
+    let encoded: HashSet<Move> = chess_moves.iter().map(|m| board.encode_move(m)).collect();
+    assert_eq!(
+        encoded,
+        moves.iter().copied().collect(),
+        "{}",
+        board.into_fen()
+    );
+
+    for chess_move in chess_moves {
+        let m = board.encode_move(&chess_move);
+        let mut made = board;
+        let undo = made.make_move(m);
+        assert_eq!(made, chess_move.board, "{m} in {}", board.into_fen());
+        assert_eq!(made.hash(), made.calculate_hash());
+        if depth > 1 {
+            check_moves(made, depth - 1);
+        }
+        made.unmake_move(m, undo);
+        assert_eq!(made, board, "{m} in {}", board.into_fen());
+        assert_eq!(made.hash(), board.hash());
+    }
+}
+
+#[test]
+fn same_as_chess_moves() {
+    init();
+    for board in [
+        Board::from(START_POSITION),
+        Board::from(TRICKY_POSITION),
+        Board::from(KILLER_POSITION),
+        Board::from(PROMOTION_POSITION),
+    ] {
+        check_moves(board, 3);
+    }
+    for fen in POSITIONS.into_iter().chain([CHESS960_POSITION]) {
+        check_moves(Board::from_fen(fen).unwrap(), 3);
+    }
+}
+
+#[test]
+fn perft() {
+    init();
+    let mut board = Board::from(TRICKY_POSITION);
+    assert_eq!(perft_make_unmake(&mut board, 3), 97862);
+    assert_eq!(board, Board::from(TRICKY_POSITION));
+    for fen in POSITIONS.into_iter().chain([CHESS960_POSITION]) {
+        let mut board = Board::from_fen(fen).unwrap();
+        assert_eq!(perft_make_unmake(&mut board, 3), board.perft(3), "{fen}");
+    }
+}
+
+#[test]
+fn encoding() {
+    init();
+    let board = Board::from(PROMOTION_POSITION);
+    for m in board.generate_moves() {
+        assert_eq!(board.parse_uci_move(&m.to_string()).unwrap().board, {
+            let mut made = board;
+            made.make_move(m);
+            made
+        });
+    }
+
+    let board = Board::from(KILLER_POSITION);
+    let m = board.parse_uci_move("f5e6").unwrap();
+    let m = board.encode_move(&m);
+    assert!(m.is_en_passent() && m.is_capture());
+    assert_eq!((m.origin(), m.dest()), (f5, e6));
+
+    let board = Board::from_fen("r3k2r/1P6/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+    let m = board.encode_move(&board.parse_uci_move("b7a8q").unwrap());
+    assert!(m.is_capture());
+    assert_eq!(m.promotion(), Some(Piece::Queen(Colour::White)));
+    let m = board.encode_move(&board.parse_uci_move("e1c1").unwrap());
+    assert_eq!((m.origin(), m.dest()), (e1, a1));
+    assert!(m.castling().is_some() && !m.is_capture());
+    assert_eq!(m.to_string(), "e1a1");
+}
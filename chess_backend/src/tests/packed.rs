@@ -0,0 +1,93 @@
+use crate::{
+    init, Board, Colour, PackedError, PositionError, KILLER_POSITION, PACKED_SIZE, START_POSITION,
+    TRICKY_POSITION,
+};
+
+// Small xorshift generator, so that the random games are the same on every run
+struct Rng(u64);
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+fn assert_round_trip(board: &Board) {
+    let packed = board.to_packed().unwrap();
+    assert_eq!(
+        Board::from_packed(&packed).as_ref(),
+        Ok(board),
+        "{}",
+        board.into_fen()
+    );
+}
+
+#[test]
+fn random_games() {
+    init();
+    let mut rng = Rng(0x2545f4914f6cdd1d);
+    let starts = [
+        Board::from(START_POSITION),
+        Board::from(KILLER_POSITION),
+        Board::from(TRICKY_POSITION),
+        Board::from_fen("bqnb1rkr/pp3ppp/3ppn2/2p5/5P2/P2P4/NPP1P1PP/BQ1BNRKR w HFhf - 2 9")
+            .unwrap(),
+    ];
+    for start in starts {
+        for _ in 0..25 {
+            let mut board = start;
+            for _ in 0..120 {
+                assert_round_trip(&board);
+                let moves = board.generate_legal_moves();
+                if moves.is_empty() {
+                    break;
+                }
+                board = moves[rng.next() as usize % moves.len()].board;
+            }
+        }
+    }
+}
+
+#[test]
+fn packed_fields() {
+    init();
+    let packed = Board::from(START_POSITION).to_packed().unwrap();
+    assert_eq!(packed.len(), PACKED_SIZE);
+    assert_eq!(&packed[..8], &0xffff00000000ffffu64.to_le_bytes());
+
+    // Killer square, castling rights and large clocks
+    for fen in [
+        "r3k2r/8/8/8/4Pp2/8/8/R3K2R b Kq e3 0 1",
+        "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 40",
+        "4k3/8/8/8/8/8/8/4K3 w - - 99 1000",
+    ] {
+        assert_round_trip(&Board::from_fen(fen).unwrap());
+    }
+}
+
+#[test]
+fn invalid_packed() {
+    init();
+    let mut packed = Board::from(START_POSITION).to_packed().unwrap();
+    packed[8] |= 0xf;
+    assert!(matches!(
+        Board::from_packed(&packed),
+        Err(PackedError::InvalidPiece(_, 15))
+    ));
+
+    // Both kings removed from the piece codes (turned into knights)
+    let mut packed = Board::from_fen("4k3/8/8/8/8/8/8/4K3 w - - 0 1")
+        .unwrap()
+        .to_packed()
+        .unwrap();
+    packed[8] = 1 | 7 << 4;
+    assert_eq!(
+        Board::from_packed(&packed),
+        Err(PackedError::InvalidPosition(PositionError::KingCount(
+            Colour::White,
+            0
+        )))
+    );
+}
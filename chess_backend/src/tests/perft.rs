@@ -0,0 +1,74 @@
+use crate::{init, Board, KILLER_POSITION, START_POSITION, TRICKY_POSITION};
+
+// Remaining positions from the chessprogramming wiki perft results page
+const POSITION_3: &str = "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1";
+const POSITION_4: &str = "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1";
+const POSITION_4_MIRRORED: &str =
+    "r2q1rk1/pP1p2pp/Q4n2/bbp1p3/Np6/1B3NBn/pPPP1PPP/R3K2R b KQ - 0 1";
+const POSITION_5: &str = "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8";
+const POSITION_6: &str = "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10";
+// Perft positions without a constant of their own, shared with the move generation tests
+pub(super) const POSITIONS: [&str; 3] = [POSITION_3, POSITION_4, POSITION_5];
+
+fn check_perft(fen: &str, expected: &[u64]) {
+    init();
+    let board = Board::from_fen(fen).unwrap();
+    for (i, nodes) in expected.iter().enumerate() {
+        let depth = i + 1;
+        assert_eq!(board.perft(depth), *nodes, "perft({depth}) of {fen}");
+    }
+}
+
+#[test]
+fn perft_start_position() {
+    check_perft(&START_POSITION, &[20, 400, 8902, 197281]);
+}
+
+#[test]
+fn perft_tricky_position() {
+    check_perft(&TRICKY_POSITION, &[48, 2039, 97862, 4085603]);
+}
+
+#[test]
+fn perft_killer_position() {
+    check_perft(&KILLER_POSITION, &[42, 1088, 39518, 1032012]);
+}
+
+#[test]
+fn perft_position_3() {
+    check_perft(POSITION_3, &[14, 191, 2812, 43238, 674624]);
+}
+
+#[test]
+fn perft_position_4() {
+    check_perft(POSITION_4, &[6, 264, 9467, 422333]);
+    check_perft(POSITION_4_MIRRORED, &[6, 264, 9467, 422333]);
+}
+
+#[test]
+fn perft_position_5() {
+    check_perft(POSITION_5, &[44, 1486, 62379, 2103487]);
+}
+
+#[test]
+fn perft_position_6() {
+    check_perft(POSITION_6, &[46, 2079, 89890, 3894594]);
+}
+
+#[test]
+fn divide_matches_perft() {
+    init();
+    let board = Board::from(TRICKY_POSITION);
+    let divided = board.divide(3);
+
+    assert_eq!(divided.len(), board.generate_legal_moves().len());
+    assert_eq!(divided.iter().map(|(_, n)| n).sum::<u64>(), board.perft(3));
+    for (m, nodes) in divided {
+        assert_eq!(m.board.perft(2), nodes);
+    }
+
+    assert!(Board::from(START_POSITION)
+        .divide(2)
+        .iter()
+        .all(|(_, nodes)| *nodes == 20));
+}
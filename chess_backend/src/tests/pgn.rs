@@ -0,0 +1,190 @@
+use crate::{
+    init,
+    pgn::{PgnError, PgnGame, PgnReader, PgnResult},
+    Board, START_POSITION,
+};
+
+const ANNOTATED: &str = r#"[Event "Casual \"blitz\" game"]
+[Site "?"]
+[Date "2024.01.01"]
+[Round "1"]
+[White "A"]
+[Black "B"]
+[Result "1-0"]
+[WhiteElo "2100"]
+
+{Opening comment} 1. e4 e5 2. Nf3 $1 Nc6 (2... d6 {Philidor} 3. d4 (3. Bc4 Be7) 3... Nf6)
+3. Bb5 a6!? ; rest of line comment
+4. Ba4 Nf6 5. O-O Be7 6. Re1 b5 7. Bb3 d6 8. c3 O-O 9. h3 Nb8 10. d4 Nbd7 11.
+Nbd2 Bb7 12. Bc2 Re8 13. Nf1 Bf8 14. Ng3 g6 15. a4 c5 16. d5 c4 17. Bg5 h6 18.
+Be3 Nc5 19. Qd2 h5 20. Bg5 Be7 21. Ra3 Nh7 22. Bh6 Bf8 23. Bxf8 Kxf8 1-0
+"#;
+
+fn read_one(pgn: &str) -> PgnGame {
+    PgnReader::new(pgn.as_bytes())
+        .next()
+        .expect("There should be a game")
+        .expect("The game should be valid")
+}
+
+#[test]
+fn read_annotated() {
+    init();
+    let game = read_one(ANNOTATED);
+
+    assert_eq!(game.tag("Event"), Some("Casual \"blitz\" game"));
+    assert_eq!(game.tag("WhiteElo"), Some("2100"));
+    assert_eq!(game.tag("Annotator"), None);
+    assert_eq!(game.result, PgnResult::WhiteWins);
+    assert_eq!(game.main_line.comment.as_deref(), Some("Opening comment"));
+
+    let moves = game.moves();
+    assert_eq!(moves.len(), 46);
+    assert_eq!(moves[2].san.to_string(), "Nf3");
+    assert_eq!(moves[2].nags, vec![1]);
+    assert_eq!(moves[5].nags, vec![5]);
+    assert_eq!(moves[5].comments, vec!["rest of line comment"]);
+    assert_eq!(moves[45].san.to_string(), "Kxf8");
+
+    // Variations, including a nested one
+    let alternative = &moves[3].variations[0];
+    assert_eq!(alternative.moves[0].san.to_string(), "d6");
+    assert_eq!(alternative.moves[0].comments, vec!["Philidor"]);
+    let nested = &alternative.moves[1].variations[0];
+    assert_eq!(nested.moves[0].san.to_string(), "Bc4");
+    assert_eq!(alternative.moves[2].san.to_string(), "Nf6");
+
+    // Boards follow the moves
+    let after_e4 = Board::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+    assert_eq!(moves[0].board, after_e4.unwrap());
+    assert_eq!(game.final_board(), moves[45].board);
+}
+
+#[test]
+fn read_many() {
+    init();
+    let pgn = r#"[Event "First"]
+[FEN "4k3/8/8/8/8/8/4P3/4K3 w - - 0 40"]
+[SetUp "1"]
+
+40. e4 Kd7 41. e5 *
+
+[Event "No result marker"]
+[Result "1/2-1/2"]
+
+1. e4 e5
+
+[Event "Illegal"]
+
+1. e4 e5 2. Ke3 Nc6 1-0
+
+[Event "Last"]
+
+1. d4 0-0?? 0-1
+[Event "Castling with zeros"]
+
+1. e4 e5 2. Nf3 Nc6 3. Bc4 Bc5 4. 0-0 1-0
+"#;
+    let games: Vec<Result<PgnGame, PgnError>> = PgnReader::new(pgn.as_bytes()).collect();
+    assert_eq!(games.len(), 5);
+
+    let first = games[0].as_ref().unwrap();
+    assert_eq!(first.start.fullmove(), 40);
+    assert_eq!(first.moves().len(), 3);
+    assert_eq!(first.result, PgnResult::Unknown);
+
+    let second = games[1].as_ref().unwrap();
+    assert_eq!(second.moves().len(), 2);
+    assert_eq!(second.result, PgnResult::Draw);
+
+    // Errors are reported, but the following games can still be read
+    assert!(matches!(games[2], Err(PgnError::IllegalMove(14, _))));
+    assert!(matches!(games[3], Err(PgnError::IllegalMove(18, _))));
+    let last = games[4].as_ref().unwrap();
+    assert_eq!(last.moves()[6].san.to_string(), "O-O");
+}
+
+#[test]
+fn syntax_errors() {
+    init();
+    for pgn in [
+        "[Event \"Unterminated]\n\n1. e4 *",
+        "[Event \"?\"\n\n1. e4 *",
+        "1. e4 {never closed *",
+        "1. e4 (1. d4 *",
+        "1. e4 e5) *",
+        "( 1. e4 ) *",
+        "1. e4 & *",
+    ] {
+        let res = PgnReader::new(pgn.as_bytes()).next().unwrap();
+        assert!(matches!(res, Err(PgnError::Syntax(..))), "{pgn}");
+    }
+    assert!(matches!(
+        PgnReader::new("[FEN \"8/8 w - - 0 1\"]\n\n*".as_bytes()).next(),
+        Some(Err(PgnError::InvalidFen(..)))
+    ));
+}
+
+#[test]
+fn write_export_format() {
+    init();
+    let game = read_one(ANNOTATED);
+    let pgn = game.to_pgn();
+
+    let tags: Vec<&str> = pgn.lines().take(8).collect();
+    assert_eq!(
+        tags,
+        [
+            r#"[Event "Casual \"blitz\" game"]"#,
+            r#"[Site "?"]"#,
+            r#"[Date "2024.01.01"]"#,
+            r#"[Round "1"]"#,
+            r#"[White "A"]"#,
+            r#"[Black "B"]"#,
+            r#"[Result "1-0"]"#,
+            r#"[WhiteElo "2100"]"#,
+        ]
+    );
+    assert!(pgn.lines().all(|line| line.len() <= 80));
+    assert!(pgn.contains(
+        "{Opening comment} 1. e4 e5 2. Nf3 $1 Nc6 (2... d6 {Philidor} 3. d4 (3. Bc4 Be7)\n3... Nf6) 3. Bb5 a6 $5 {rest of line comment} 4. Ba4"
+    ));
+    assert!(pgn.trim_end().ends_with("Bxf8 Kxf8 1-0"));
+
+    // Writing and reading back gives the same game
+    assert_eq!(read_one(&pgn), game);
+}
+
+#[test]
+fn write_comment_with_closing_brace() {
+    init();
+    let game = read_one("1. e4 ; see {this} }\n1... e5 (1... c5 ; or }\n) 2. Nf3 {plain} *\n");
+    assert_eq!(game.main_line.moves[0].comments, vec!["see {this} }"]);
+
+    let pgn = game.to_pgn();
+    assert!(pgn.contains("1. e4 ; see {this} }\n"));
+    assert!(pgn.contains("{plain}"));
+    assert_eq!(read_one(&pgn), game);
+}
+
+#[test]
+fn write_new_game() {
+    init();
+    let mut game = PgnGame::new(Board::from(START_POSITION));
+    for _ in 0..4 {
+        let m = game.final_board().generate_legal_moves()[0];
+        game.play(&m);
+    }
+    game.result = PgnResult::Draw;
+    let pgn = game.to_pgn();
+    assert!(pgn.starts_with("[Event \"?\"]\n[Site \"?\"]\n[Date \"????.??.??\"]"));
+    assert_eq!(read_one(&pgn).final_board(), game.final_board());
+
+    let start = Board::from_fen("4k3/8/8/8/8/8/4P3/4K3 b - - 0 40").unwrap();
+    let mut game = PgnGame::new(start);
+    game.play(&start.generate_legal_moves()[0]);
+    let pgn = game.to_pgn();
+    assert!(pgn.contains("[FEN \"4k3/8/8/8/8/8/4P3/4K3 b - - 0 40\"]"));
+    assert!(pgn.contains("\n40... K"));
+    assert_eq!(read_one(&pgn).start, start);
+}
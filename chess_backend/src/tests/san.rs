@@ -0,0 +1,183 @@
+use crate::{
+    c3, d2, e1, e2, e4, init, Board, ChessError, Colour, Game, Piece, SanMove, Side, START_POSITION,
+};
+
+// The canonical SAN of the move found for `san`
+fn resolve(board: &Board, san: &str) -> Result<String, ChessError> {
+    board
+        .parse_san(san)
+        .map(|m| board.get_san(&m.board).to_string())
+}
+
+#[test]
+fn lenient_forms() {
+    init();
+    let board = Board::from(START_POSITION);
+    for san in ["Nf3", "Nf3+", "Nf3!?", "Ng1f3", "Ng1-f3", " Nf3 "] {
+        assert_eq!(resolve(&board, san).as_deref(), Ok("Nf3"), "{san}");
+    }
+    assert_eq!(resolve(&board, "e2-e4").as_deref(), Ok("e4"));
+
+    let board = Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1").unwrap();
+    for (san, expected) in [
+        ("O-O", "O-O"),
+        ("0-0", "O-O"),
+        ("0-0-0+", "O-O-O"),
+        ("Rxa8", "Rxa8+"),
+        ("Ra1:a8", "Rxa8+"),
+    ] {
+        assert_eq!(resolve(&board, san).as_deref(), Ok(expected), "{san}");
+    }
+
+    let board = Board::from_fen("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1").unwrap();
+    for san in ["exd6", "exd6e.p.", "exd6 e.p.", "exd6ep"] {
+        assert_eq!(resolve(&board, san).as_deref(), Ok("exd6"), "{san}");
+    }
+
+    let board = Board::from_fen("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    for san in ["b8=Q+", "b8Q", "b8q", "b8=Q"] {
+        assert_eq!(resolve(&board, san).as_deref(), Ok("b8=Q+"), "{san}");
+    }
+    assert_eq!(resolve(&board, "b8=B").as_deref(), Ok("b8=B"));
+    assert_eq!(resolve(&board, "b8N").as_deref(), Ok("b8=N"));
+}
+
+#[test]
+fn ambiguous_moves() {
+    init();
+    let board = Board::from_fen("4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1").unwrap();
+    assert_eq!(
+        resolve(&board, "Nd2"),
+        Err(ChessError::AmbiguousMove("Nd2".to_string()))
+    );
+    assert_eq!(
+        resolve(&board, "N1d2"),
+        Err(ChessError::AmbiguousMove("N1d2".to_string()))
+    );
+    assert_eq!(resolve(&board, "Nbd2").as_deref(), Ok("Nbd2"));
+    assert_eq!(resolve(&board, "Nfd2").as_deref(), Ok("Nfd2"));
+
+    // A promotion needs the piece
+    let board = Board::from_fen("4k3/1P6/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(
+        resolve(&board, "b8"),
+        Err(ChessError::AmbiguousMove("b8".to_string()))
+    );
+}
+
+#[test]
+fn illegal_and_malformed() {
+    init();
+    let board = Board::from(START_POSITION);
+    for san in ["Nf4", "e5", "Ke2", "Nb1d2", "e4=Q", "Ra3"] {
+        assert_eq!(
+            resolve(&board, san),
+            Err(ChessError::IllegalMove(san.to_string())),
+            "{san}"
+        );
+    }
+    for san in ["", "Zf3", "e9", "Nf3x3", "hello", "Nf", "O-O-O-O", "1.e4"] {
+        assert_eq!(
+            resolve(&board, san),
+            Err(ChessError::MalformedMove(san.to_string())),
+            "{san}"
+        );
+    }
+}
+
+#[test]
+fn rejection_reasons() {
+    init();
+    // Pinned knight and king walking into check, the latter reported on the square it moves to
+    let board = Board::from_fen("3rk3/4r3/8/8/8/8/3rN3/4K3 w - - 0 1").unwrap();
+    assert_eq!(
+        resolve(&board, "Nc3"),
+        Err(ChessError::KingInCheck("Nc3".to_string(), e1))
+    );
+    assert_eq!(
+        resolve(&board, "Kxd2"),
+        Err(ChessError::KingInCheck("Kxd2".to_string(), d2))
+    );
+    let board = Board::from_fen("3rk3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(
+        resolve(&board, "Kd2"),
+        Err(ChessError::KingInCheck("Kd2".to_string(), d2))
+    );
+
+    let board = Board::from(START_POSITION);
+    assert_eq!(
+        resolve(&board, "O-O"),
+        Err(ChessError::CastlingBlocked(Side::King))
+    );
+    // No kingside rights
+    let board = Board::from_fen("r3k2r/8/8/8/8/8/5r2/R3K2R w Qkq - 0 1").unwrap();
+    assert_eq!(
+        resolve(&board, "O-O"),
+        Err(ChessError::NoCastlingRights(Side::King))
+    );
+    // Castling through the attacked f1
+    let board = Board::from_fen("r3k2r/8/8/8/8/8/5r2/R3K2R w KQkq - 0 1").unwrap();
+    assert_eq!(
+        resolve(&board, "O-O"),
+        Err(ChessError::CastlingBlocked(Side::King))
+    );
+    assert_eq!(resolve(&board, "O-O-O").as_deref(), Ok("O-O-O"));
+}
+
+#[test]
+fn make_san_move_rejection_reasons() {
+    init();
+    let white = Colour::White;
+    let knight_move = |origin, dest| {
+        SanMove::new(
+            Piece::Knight(white),
+            false,
+            false,
+            false,
+            origin,
+            (false, false),
+            dest,
+            None,
+            None,
+        )
+    };
+
+    let mut board = Board::from(START_POSITION);
+    assert_eq!(
+        board.make_san_move(knight_move(c3, e4)),
+        Err(ChessError::NoPieceOnSquare(c3))
+    );
+    assert_eq!(
+        board.make_san_move(SanMove::kingside_castle(white)),
+        Err(ChessError::CastlingBlocked(Side::King))
+    );
+
+    let mut board = Board::from_fen("4k3/4r3/8/8/8/8/4N3/4K3 w - - 0 1").unwrap();
+    assert_eq!(
+        board.make_san_move(knight_move(e2, c3)),
+        Err(ChessError::KingInCheck("Nc3".to_string(), e1))
+    );
+
+    let mut game = Game::from(Board::from_fen("3rk3/8/8/8/8/8/8/4K3 w - - 0 1").unwrap());
+    let king_move = SanMove::new(
+        Piece::King(white),
+        false,
+        false,
+        false,
+        e1,
+        (false, false),
+        d2,
+        None,
+        None,
+    );
+    assert_eq!(
+        game.play_san(king_move),
+        Err(ChessError::KingInCheck("Kd2".to_string(), d2))
+    );
+
+    let mut game = Game::from(Board::from_fen("r3k2r/8/8/8/8/8/8/R3K2R w Qkq - 0 1").unwrap());
+    assert_eq!(
+        game.play_san(SanMove::kingside_castle(white)),
+        Err(ChessError::NoCastlingRights(Side::King))
+    );
+}
@@ -0,0 +1,66 @@
+use crate::{init, Board};
+
+fn see(fen: &str, m: &str) -> i32 {
+    let board = Board::from_fen(fen).unwrap();
+    let m = board.encode_move(&board.parse_uci_move(m).unwrap());
+    board.see(m)
+}
+
+#[test]
+fn captures() {
+    init();
+    // Undefended pawn
+    assert_eq!(see("4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1", "e4d5"), 100);
+    // Queen takes a pawn defended by a pawn
+    assert_eq!(see("4k3/8/2p5/3p4/8/8/3Q4/4K3 w - - 0 1", "d2d5"), -800);
+    // Equal trade
+    assert_eq!(see("4k3/8/2p5/3n4/8/4N3/8/4K3 w - - 0 1", "e3d5"), 0);
+    // Classic positions from the chessprogramming wiki
+    assert_eq!(
+        see("1k1r4/1pp4p/p7/4p3/8/P5P1/1PP4P/2K1R3 w - - 0 1", "e1e5"),
+        100
+    );
+    assert_eq!(
+        see(
+            "1k1r3q/1ppn3p/p4b2/4p3/8/P2N2P1/1PP1R1BP/2K1Q3 w - - 0 1",
+            "d3e5"
+        ),
+        -220
+    );
+}
+
+#[test]
+fn x_rays() {
+    init();
+    // The rook behind the first one recaptures once the first rook has left the file
+    assert_eq!(see("3rk3/8/8/3r4/8/8/3R4/3RK3 w - - 0 1", "d2d5"), 500);
+    assert_eq!(see("3rk3/8/8/3r4/8/8/3R4/4K3 w - - 0 1", "d2d5"), 0);
+    // A queen behind a bishop
+    assert_eq!(
+        see("4k3/8/5p2/4p3/3B4/2Q5/8/4K3 w - - 0 1", "d4e5"),
+        100 - 330 + 100
+    );
+}
+
+#[test]
+fn special_moves() {
+    init();
+    assert_eq!(see("4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1", "e5d6"), 100);
+    assert_eq!(see("4k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a7a8q"), 800);
+    // Moving a piece to a square attacked by a pawn loses it
+    assert_eq!(see("4k3/8/8/8/8/2p5/8/3NK3 w - - 0 1", "d1b2"), -320);
+    assert_eq!(see("4k3/8/8/8/8/2p5/8/3NK3 w - - 0 1", "d1f2"), 0);
+    // The king can not recapture on a defended square
+    assert_eq!(see("3rk3/8/8/8/8/8/3r4/4K3 b - - 0 1", "d2d1"), 0);
+    assert_eq!(see("4k3/8/8/8/8/8/8/r2RK3 b - - 0 1", "a1d1"), 0);
+}
+
+#[test]
+fn thresholds() {
+    init();
+    let board = Board::from_fen("4k3/8/2p5/3p4/8/8/3Q4/4K3 w - - 0 1").unwrap();
+    let m = board.encode_move(&board.parse_uci_move("d2d5").unwrap());
+    assert!(board.see_ge(m, -800));
+    assert!(!board.see_ge(m, -799));
+    assert!(!board.see_ge(m, 0));
+}
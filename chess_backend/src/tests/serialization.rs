@@ -0,0 +1,61 @@
+use crate::{
+    c2, e4, init, Board, ChessMove, Colour, FinishedState, GameState, MoveType, Piece, ReasonDraw,
+    ReasonWin, SanMove, Side, Square, UciMove, KILLER_POSITION, START_POSITION,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::fmt::Debug;
+
+fn round_trip<T: Serialize + DeserializeOwned + PartialEq + Debug>(value: T, json: &str) {
+    assert_eq!(serde_json::to_string(&value).unwrap(), json);
+    assert_eq!(serde_json::from_str::<T>(json).unwrap(), value);
+}
+
+#[test]
+fn board_as_fen() {
+    init();
+    for fen in [
+        START_POSITION.as_str().trim(),
+        KILLER_POSITION.as_str().trim(),
+        "r3k2r/8/8/8/4Pp2/8/8/R3K2R b KQkq e3 5 31",
+    ] {
+        round_trip(Board::from_fen(fen).unwrap(), &format!("\"{fen}\""));
+    }
+    assert!(serde_json::from_str::<Board>("\"8/8/8/8/8/8/8/8 w - - 0 1\"").is_err());
+}
+
+#[test]
+fn moves_as_strings() {
+    init();
+    let board = Board::from(START_POSITION);
+    let m: ChessMove = board.parse_uci_move("g1f3").unwrap();
+    assert_eq!(serde_json::to_string(&m).unwrap(), "\"g1f3\"");
+    let san: SanMove = board.get_san(&m.board);
+    assert_eq!(serde_json::to_string(&san).unwrap(), "\"Nf3\"");
+
+    // Moves are read back as UCI and resolved against the board
+    let uci: UciMove = serde_json::from_str("\"g1f3\"").unwrap();
+    assert_eq!(uci.to_chess_move(&board).unwrap().board, m.board);
+    round_trip(UciMove::new(c2, e4, None), "\"c2e4\"");
+    round_trip("e7e8q".parse::<UciMove>().unwrap(), "\"e7e8q\"");
+    assert!(serde_json::from_str::<UciMove>("\"e2\"").is_err());
+
+    round_trip(e4, "\"e4\"");
+    assert!(serde_json::from_str::<Square>("\"i9\"").is_err());
+}
+
+#[test]
+fn enums_by_name() {
+    round_trip(Colour::White, "\"white\"");
+    round_trip(Piece::Knight(Colour::Black), r#"{"knight":"black"}"#);
+    round_trip(MoveType::Castling(Side::Queen), r#"{"castling":"queen"}"#);
+    round_trip(MoveType::Standard, "\"standard\"");
+    round_trip(GameState::Ongoing, "\"ongoing\"");
+    round_trip(
+        GameState::Finished(FinishedState::Win(Colour::Black, ReasonWin::Checkmate)),
+        r#"{"finished":{"win":["black","checkmate"]}}"#,
+    );
+    round_trip(
+        FinishedState::Draw(ReasonDraw::ThreefoldRepetition),
+        r#"{"draw":"threefold_repetition"}"#,
+    );
+}
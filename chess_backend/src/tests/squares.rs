@@ -1,25 +1,71 @@
 use crate::board::Board;
 use crate::utils::extract_squares;
 use crate::utils::squares::*;
-use crate::START_POSITION;
+use crate::{Colour, START_POSITION};
+
+fn indices(squares: &[Square]) -> Vec<i32> {
+    squares.iter().map(Square::as_int).collect()
+}
 
 #[test]
 fn test_squares_parse() {
-    assert_eq!(from_str(&to_str(a1).unwrap()), a1);
+    assert_eq!(a1.to_string().parse::<Square>(), Ok(a1));
+    assert_eq!("e4".parse::<Square>(), Ok(e4));
+    assert_eq!(h8.to_string(), "h8");
+    for s in ["", "-", "e", "e9", "i1", "e44", "E4"] {
+        assert_eq!(s.parse::<Square>(), Err(ParseSquareError(s.to_string())));
+    }
+
+    assert_eq!(Square::from_index(0), Some(a1));
+    assert_eq!(Square::from_index(63), Some(h8));
+    assert_eq!(Square::from_index(-1), None);
+    assert_eq!(Square::from_index(64), None);
+    assert_eq!(Square::try_from(28), Ok(e4));
+    assert_eq!(i32::from(e4), 28);
+}
+
+#[test]
+fn test_files_and_ranks() {
+    assert_eq!(Square::new(File::E, Rank::Fourth), e4);
+    assert_eq!(e4.file(), File::E);
+    assert_eq!(e4.rank(), Rank::Fourth);
+    assert_eq!(File::from_char('h'), Some(File::H));
+    assert_eq!(File::from_char('i'), None);
+    assert_eq!(Rank::from_char('8'), Some(Rank::Eighth));
+    assert_eq!(Rank::from_char('0'), None);
+    assert_eq!(File::C.as_char(), 'c');
+    assert_eq!(Rank::Third.as_char(), '3');
+    assert_eq!(Rank::Second.relative(Colour::Black), Rank::Seventh);
+    assert_eq!(a1.bitboard(), 1);
+    assert_eq!(h8.bitboard(), 1 << 63);
+}
+
+#[test]
+fn test_square_helpers() {
+    assert_eq!(e4.offset(1, 2), Some(f6));
+    assert_eq!(e4.offset(-4, -3), Some(a1));
+    assert_eq!(h4.offset(1, 0), None);
+    assert_eq!(e8.offset(0, 1), None);
+    assert_eq!(e2.forward(Colour::White), Some(e3));
+    assert_eq!(e2.forward(Colour::Black), Some(e1));
+    assert_eq!(e1.backward(Colour::White), None);
+    assert_eq!(c2.mirror(), c7);
+    assert_eq!(g1.relative(Colour::Black), g8);
+    assert_eq!(g1.relative(Colour::White), g1);
 }
 
 #[test]
 fn test_extraction() {
-    assert_eq!(vec![a1], extract_squares(1_u64 << a1));
-    assert_eq!(vec![b5], extract_squares(1_u64 << b5));
-    assert_eq!(vec![g6], extract_squares(1_u64 << g6));
-    assert_eq!(vec![h7], extract_squares(1_u64 << h7));
-    assert_eq!(vec![d8], extract_squares(1_u64 << d8));
+    assert_eq!(indices(&[a1]), extract_squares(a1.bitboard()));
+    assert_eq!(indices(&[b5]), extract_squares(b5.bitboard()));
+    assert_eq!(indices(&[g6]), extract_squares(g6.bitboard()));
+    assert_eq!(indices(&[h7]), extract_squares(h7.bitboard()));
+    assert_eq!(indices(&[d8]), extract_squares(d8.bitboard()));
 
     assert_eq!(
-        vec![a1, b5, g6, h7, d8],
+        indices(&[a1, b5, g6, h7, d8]),
         extract_squares(
-            (1_u64 << a1) + (1_u64 << g6) + (1_u64 << d8) + (1_u64 << b5) + (1_u64 << h7)
+            a1.bitboard() + g6.bitboard() + d8.bitboard() + b5.bitboard() + h7.bitboard()
         )
     );
 }
@@ -27,23 +73,35 @@ fn test_extraction() {
 #[test]
 fn test_fen() {
     let board = Board::from(START_POSITION);
-    assert_eq!(vec![a1, h1], extract_squares(board.base.white.rooks));
-    assert_eq!(vec![b1, g1], extract_squares(board.base.white.knights));
-    assert_eq!(vec![c1, f1], extract_squares(board.base.white.bishops));
-    assert_eq!(vec![d1], extract_squares(board.base.white.queens));
-    assert_eq!(vec![e1], extract_squares(board.base.white.king));
+    assert_eq!(indices(&[a1, h1]), extract_squares(board.base.white.rooks));
     assert_eq!(
-        vec![a2, b2, c2, d2, e2, f2, g2, h2],
+        indices(&[b1, g1]),
+        extract_squares(board.base.white.knights)
+    );
+    assert_eq!(
+        indices(&[c1, f1]),
+        extract_squares(board.base.white.bishops)
+    );
+    assert_eq!(indices(&[d1]), extract_squares(board.base.white.queens));
+    assert_eq!(indices(&[e1]), extract_squares(board.base.white.king));
+    assert_eq!(
+        indices(&[a2, b2, c2, d2, e2, f2, g2, h2]),
         extract_squares(board.base.white.pawns)
     );
 
-    assert_eq!(vec![a8, h8], extract_squares(board.base.black.rooks));
-    assert_eq!(vec![b8, g8], extract_squares(board.base.black.knights));
-    assert_eq!(vec![c8, f8], extract_squares(board.base.black.bishops));
-    assert_eq!(vec![d8], extract_squares(board.base.black.queens));
-    assert_eq!(vec![e8], extract_squares(board.base.black.king));
+    assert_eq!(indices(&[a8, h8]), extract_squares(board.base.black.rooks));
+    assert_eq!(
+        indices(&[b8, g8]),
+        extract_squares(board.base.black.knights)
+    );
+    assert_eq!(
+        indices(&[c8, f8]),
+        extract_squares(board.base.black.bishops)
+    );
+    assert_eq!(indices(&[d8]), extract_squares(board.base.black.queens));
+    assert_eq!(indices(&[e8]), extract_squares(board.base.black.king));
     assert_eq!(
-        vec![a7, b7, c7, d7, e7, f7, g7, h7],
+        indices(&[a7, b7, c7, d7, e7, f7, g7, h7]),
         extract_squares(board.base.black.pawns)
     );
 }
@@ -0,0 +1,109 @@
+use crate::{
+    init, utils::squares::*, Board, ChessError, Colour, Piece, SanMove, Side, UciMove,
+    KILLER_POSITION, PROMOTION_POSITION, START_POSITION, TRICKY_POSITION,
+};
+
+#[test]
+fn parse_and_format() {
+    assert_eq!(
+        "e2e4".parse::<UciMove>().unwrap(),
+        UciMove::new(e2, e4, None)
+    );
+    assert_eq!(
+        "e7e8q".parse::<UciMove>().unwrap(),
+        UciMove::new(e7, e8, Some(Piece::Queen(Colour::White)))
+    );
+    assert_eq!(
+        "b2a1n".parse::<UciMove>().unwrap(),
+        UciMove::new(b2, a1, Some(Piece::Knight(Colour::Black)))
+    );
+
+    for s in ["e2e4", "e7e8q", "b2a1n", "e1g1", "h8h1"] {
+        assert_eq!(s.parse::<UciMove>().unwrap().to_string(), s);
+    }
+    for s in ["", "e2", "e2e", "e2e9", "i2e4", "e7e8k", "e7e8qq", "e2-e4"] {
+        assert!(s.parse::<UciMove>().is_err(), "{s}");
+    }
+}
+
+#[test]
+fn round_trip() {
+    init();
+    for pos in [
+        START_POSITION,
+        TRICKY_POSITION,
+        KILLER_POSITION,
+        PROMOTION_POSITION,
+    ] {
+        let board = Board::from(pos);
+        for m in board.generate_legal_moves() {
+            let uci = m.to_uci();
+            let found = board.parse_uci_move(&uci.to_string()).unwrap();
+            assert_eq!(found.board, m.board, "{uci}");
+
+            // Same move through SAN
+            let san = uci.to_san(&board).unwrap();
+            assert_eq!(UciMove::from(san), uci);
+        }
+    }
+}
+
+#[test]
+fn special_moves() {
+    init();
+    let board = Board::from(TRICKY_POSITION);
+    // Castling is written as the king's move
+    let castle = board.parse_uci_move("e1g1").unwrap();
+    assert_eq!(
+        board.get_san(&castle.board),
+        SanMove::kingside_castle(Colour::White)
+    );
+    let castle = board.parse_uci_move("e1c1").unwrap();
+    assert_eq!(
+        board.get_san(&castle.board),
+        SanMove::queenside_castle(Colour::White)
+    );
+
+    // En passent
+    let board = Board::from(KILLER_POSITION);
+    let ep = board.parse_uci_move("f5e6").unwrap();
+    assert_eq!(ep.to_uci(), UciMove::new(f5, e6, None));
+    assert_eq!(board.get_san(&ep.board).to_string(), "fxe6");
+
+    // Promotions, including underpromotion with capture
+    let board = Board::from(KILLER_POSITION);
+    let promotion = board.parse_uci_move("g7h8n").unwrap();
+    assert_eq!(board.get_san(&promotion.board).to_string(), "gxh8=N");
+    assert!(board.parse_uci_move("g7h8").is_err());
+    assert!(board.parse_uci_move("g7g8q").is_ok());
+    assert!(board.parse_uci_move("b4b5q").is_err());
+
+    // Syntactically valid, but illegal
+    assert!(Board::from(START_POSITION).parse_uci_move("e2e5").is_err());
+}
+
+#[test]
+fn rejection_reasons() {
+    init();
+    let board = Board::from(START_POSITION);
+    for (uci, error) in [
+        ("e3e4", ChessError::NoPieceOnSquare(e3)),
+        ("e7e5", ChessError::NoPieceOnSquare(e7)),
+        ("e2e5", ChessError::IllegalMove("e2e5".to_string())),
+        ("e1g1", ChessError::CastlingBlocked(Side::King)),
+        ("e2", ChessError::MalformedMove("e2".to_string())),
+        ("e2e4k", ChessError::MalformedMove("e2e4k".to_string())),
+    ] {
+        assert_eq!(
+            board.parse_uci_move(uci).map(|m| m.to_uci()),
+            Err(error),
+            "{uci}"
+        );
+    }
+
+    let board = Board::from_fen("4k3/4r3/8/8/8/8/4N3/4K3 w - - 0 1").unwrap();
+    assert_eq!(
+        board.parse_uci_move("e2c3").map(|m| m.to_uci()),
+        Err(ChessError::KingInCheck("e2c3".to_string(), e1))
+    );
+}
@@ -0,0 +1,137 @@
+use crate::{
+    init, Board, Colour, FenError, PositionError, Side, CMK_POSITION, KILLER_POSITION,
+    START_POSITION, TRICKY_POSITION,
+};
+
+fn validate(fen: &str) -> Result<(), PositionError> {
+    match Board::from_fen(fen) {
+        Ok(board) => board.validate(),
+        Err(FenError::InvalidPosition(e)) => Err(e),
+        Err(e) => panic!("{fen} should be well-formed, got {e}"),
+    }
+}
+
+#[test]
+fn valid_positions() {
+    init();
+    for pos in [
+        START_POSITION,
+        TRICKY_POSITION,
+        KILLER_POSITION,
+        CMK_POSITION,
+    ] {
+        assert_eq!(Board::from(pos).validate(), Ok(()));
+    }
+    // Chess960 castling rights and an en passent square without a pawn to capture
+    assert_eq!(validate("1r2k1r1/8/8/8/8/8/8/1R2K1R1 w GBgb - 0 1"), Ok(()));
+    assert_eq!(validate("4k3/8/8/8/4P3/8/8/4K3 b - e3 0 1"), Ok(()));
+}
+
+#[test]
+fn king_count() {
+    init();
+    assert_eq!(
+        validate("8/8/8/8/8/8/8/8 w - - 0 1"),
+        Err(PositionError::KingCount(Colour::White, 0))
+    );
+    assert_eq!(
+        validate("4k3/8/8/8/8/8/8/3KK3 w - - 0 1"),
+        Err(PositionError::KingCount(Colour::White, 2))
+    );
+    assert_eq!(
+        validate("8/8/8/8/8/8/8/4K3 w - - 0 1"),
+        Err(PositionError::KingCount(Colour::Black, 0))
+    );
+}
+
+#[test]
+fn pawns_on_back_rank() {
+    init();
+    assert_eq!(
+        validate("4k3/8/8/8/8/8/8/P3K3 w - - 0 1"),
+        Err(PositionError::PawnOnBackRank(crate::a1))
+    );
+    assert_eq!(
+        validate("3pk3/8/8/8/8/8/8/4K3 w - - 0 1"),
+        Err(PositionError::PawnOnBackRank(crate::d8))
+    );
+}
+
+#[test]
+fn opponent_in_check() {
+    init();
+    assert_eq!(validate("4k3/8/8/8/8/8/8/4K2R w - - 0 1"), Ok(()));
+    assert_eq!(validate("4k3/8/8/8/8/8/8/4K2R b - - 0 1"), Ok(()));
+    assert_eq!(validate("4k3/8/8/8/8/8/8/R3K3 w - - 0 1"), Ok(()));
+    assert_eq!(
+        validate("4k3/8/8/8/8/8/8/4R1K1 w - - 0 1"),
+        Err(PositionError::OpponentInCheck)
+    );
+}
+
+#[test]
+fn castling() {
+    init();
+    assert_eq!(
+        validate("r3k2r/8/8/8/8/8/8/R3K3 w KQkq - 0 1"),
+        Err(PositionError::InvalidCastling(Colour::White, Side::King))
+    );
+    assert_eq!(
+        validate("r3k2r/8/8/8/8/8/4K3/R6R w Qkq - 0 1"),
+        Err(PositionError::InvalidCastling(Colour::White, Side::Queen))
+    );
+    assert_eq!(
+        validate("4k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1"),
+        Err(PositionError::InvalidCastling(Colour::Black, Side::Queen))
+    );
+}
+
+#[test]
+fn en_passent() {
+    init();
+    // No pawn in front of the en passent square
+    assert_eq!(
+        validate("4k3/8/8/8/8/8/8/4K3 w - e6 0 1"),
+        Err(PositionError::InvalidEnPassent(crate::e6))
+    );
+    // The square the pawn came from is occupied
+    assert_eq!(
+        validate("4k3/4n3/8/4p3/8/8/8/4K3 w - e6 0 1"),
+        Err(PositionError::InvalidEnPassent(crate::e6))
+    );
+    // The pawn in front of the square belongs to the side to move
+    assert_eq!(
+        validate("4k3/8/8/8/4p3/8/8/4K3 b - e3 0 1"),
+        Err(PositionError::InvalidEnPassent(crate::e3))
+    );
+}
+
+#[test]
+fn constructed_boards() {
+    init();
+    let board = Board::from(START_POSITION);
+    let mut base = board.base;
+    base.white.king |= crate::e4.bitboard();
+    assert_eq!(
+        Board::new(base, None, board.castling_rights(), Colour::White, 0, 1),
+        Err(PositionError::KingCount(Colour::White, 2))
+    );
+
+    let mut base = board.base;
+    base.black.knights |= base.white.queens;
+    assert_eq!(
+        Board::new(base, None, board.castling_rights(), Colour::White, 0, 1),
+        Err(PositionError::OverlappingPieces(crate::d1))
+    );
+}
+
+#[test]
+fn fen_errors() {
+    init();
+    let err = Board::from_fen("4k3/8/8/8/8/8/8/4R1K1 w - - 0 1").unwrap_err();
+    assert_eq!(
+        err,
+        FenError::InvalidPosition(PositionError::OpponentInCheck)
+    );
+    assert_eq!(err.field(), None);
+}
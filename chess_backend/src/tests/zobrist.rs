@@ -0,0 +1,97 @@
+use crate::{
+    init, utils::squares::Square, Board, Colour, CASTLE_KINGSIDE_POSITION,
+    CASTLE_QUEENSIDE_POSITION, KILLER_POSITION, PROMOTION_POSITION, START_POSITION,
+    TRICKY_POSITION,
+};
+
+fn check_tree(board: Board, depth: usize) {
+    assert_eq!(board.hash(), board.calculate_hash(), "{}", board.into_fen());
+    if depth > 0 {
+        for m in board.generate_legal_moves() {
+            check_tree(m.board, depth - 1);
+        }
+    }
+}
+
+// Plays the (first) legal move from `origin` to `dest`
+fn play(board: Board, origin: Square, dest: Square) -> Board {
+    board
+        .generate_legal_moves()
+        .into_iter()
+        .find(|m| m.base.starting_square == Some(origin) && m.base.destination_square == Some(dest))
+        .expect("Move should be legal")
+        .board
+}
+
+#[test]
+fn incremental_hash() {
+    init();
+    for pos in [
+        START_POSITION,
+        CASTLE_KINGSIDE_POSITION,
+        CASTLE_QUEENSIDE_POSITION,
+        PROMOTION_POSITION,
+        TRICKY_POSITION,
+        KILLER_POSITION,
+    ] {
+        check_tree(Board::from(pos), 3);
+    }
+}
+
+#[test]
+fn transpositions() {
+    use crate::utils::squares::*;
+    init();
+    let board = Board::from(START_POSITION);
+
+    let first = play(play(play(play(board, g1, f3), g8, f6), b1, c3), b8, c6);
+    let second = play(play(play(play(board, b1, c3), b8, c6), g1, f3), g8, f6);
+    assert_eq!(first.hash(), second.hash());
+    assert_eq!(
+        first.hash(),
+        Board::from_fen(&first.into_fen()).unwrap().hash()
+    );
+
+    // Knights going back and forth lead to the same position, only the clocks differ
+    let back = play(play(play(play(board, g1, f3), g8, f6), f3, g1), f6, g8);
+    assert_eq!(back.hash(), board.hash());
+    assert_ne!(back, board);
+}
+
+#[test]
+fn hash_components() {
+    init();
+    let board = Board::from(START_POSITION);
+
+    // Side to move
+    let black_to_move =
+        Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR b KQkq - 0 1").unwrap();
+    assert_eq!(black_to_move.side_to_move(), Colour::Black);
+    assert_ne!(board.hash(), black_to_move.hash());
+
+    // Castling rights
+    let no_castling =
+        Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w - - 0 1").unwrap();
+    assert_ne!(board.hash(), no_castling.hash());
+
+    // Killer square, which only counts when a pawn can take en passent
+    let hashes = |fen: &str| {
+        let without_killer = fen.replace(" e3 ", " - ");
+        (
+            Board::from_fen(fen).unwrap().hash(),
+            Board::from_fen(&without_killer).unwrap().hash(),
+        )
+    };
+    let (with, without) = hashes("rnbqkbnr/ppp1pppp/8/8/3pP3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+    assert_ne!(with, without);
+    let (with, without) = hashes("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+    assert_eq!(with, without);
+    // Taking en passent would leave the king in check from the rook
+    let (with, without) = hashes("8/8/8/8/k2pP2R/8/8/4K3 b - e3 0 1");
+    assert_eq!(with, without);
+
+    // Clocks are not hashed
+    let clocks =
+        Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 12 40").unwrap();
+    assert_eq!(board.hash(), clocks.hash());
+}
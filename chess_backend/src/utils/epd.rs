@@ -0,0 +1,279 @@
+// Extended Position Description EPD
+use std::{error::Error, fmt::Display, str::FromStr};
+
+use super::fen::{FenError, FenField};
+use crate::{Board, SanMove};
+
+// EPD only has the first four FEN fields, the clocks are given by the hmvc and fmvn opcodes
+const FIELDS: [FenField; 4] = [
+    FenField::Placement,
+    FenField::SideToMove,
+    FenField::Castling,
+    FenField::EnPassent,
+];
+
+/// A single operation of an EPD record, e.g. `bm Nf3 e4;`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EpdOperation {
+    pub opcode: String,
+    /// Operands with the quotes of string operands removed
+    pub operands: Vec<String>,
+}
+impl EpdOperation {
+    pub fn new(opcode: impl Into<String>, operands: Vec<String>) -> Self {
+        Self {
+            opcode: opcode.into(),
+            operands,
+        }
+    }
+
+    // Comments and identifiers are always written as strings, other operands only if they could
+    // not be read back otherwise
+    fn quote_operand(&self, operand: &str) -> bool {
+        let opcode = self.opcode.as_bytes();
+        self.opcode == "id"
+            || opcode.len() == 2 && opcode[0] == b'c' && opcode[1].is_ascii_digit()
+            || operand.is_empty()
+            || operand.contains(|c: char| c.is_whitespace() || c == ';')
+    }
+}
+impl Display for EpdOperation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.opcode)?;
+        for operand in &self.operands {
+            if self.quote_operand(operand) {
+                write!(f, " \"{operand}\"")?;
+            } else {
+                write!(f, " {operand}")?;
+            }
+        }
+        write!(f, ";")
+    }
+}
+
+/// A position along with its EPD operations, as used by test suites
+#[derive(Debug, Clone, PartialEq)]
+pub struct Epd {
+    /// The position, with the clocks taken from the hmvc and fmvn operations (0 and 1 if missing).
+    /// Its clocks are the ones written, whatever the operations hold.
+    pub board: Board,
+    /// Operations in the order they were read
+    pub operations: Vec<EpdOperation>,
+}
+impl Epd {
+    /// Creates a record for the position with the clocks stored as hmvc and fmvn operations, so
+    /// that `into_fen` gives back the FEN of the board
+    pub fn new(board: Board) -> Self {
+        Self {
+            board,
+            operations: vec![
+                EpdOperation::new("hmvc", vec![board.halfmove().to_string()]),
+                EpdOperation::new("fmvn", vec![board.fullmove().to_string()]),
+            ],
+        }
+    }
+
+    /// Operands of the first operation with the given opcode
+    pub fn operation(&self, opcode: &str) -> Option<&[String]> {
+        self.operations
+            .iter()
+            .find(|o| o.opcode == opcode)
+            .map(|o| o.operands.as_slice())
+    }
+    /// Replaces the operands of the operation with the given opcode, or adds it at the end
+    pub fn set_operation(&mut self, opcode: &str, operands: Vec<String>) {
+        if let Some(o) = self.operations.iter_mut().find(|o| o.opcode == opcode) {
+            o.operands = operands;
+        } else {
+            self.operations.push(EpdOperation::new(opcode, operands));
+        }
+    }
+    pub fn remove_operation(&mut self, opcode: &str) {
+        self.operations.retain(|o| o.opcode != opcode);
+    }
+
+    /// Position identifier (`id`)
+    pub fn id(&self) -> Option<&str> {
+        self.operation("id")?.first().map(String::as_str)
+    }
+    /// Primary comment (`c0`)
+    pub fn comment(&self) -> Option<&str> {
+        self.operation("c0")?.first().map(String::as_str)
+    }
+
+    /// The moves listed by the `bm` operation, empty if there is none
+    pub fn best_moves(&self) -> Result<Vec<SanMove>, EpdError> {
+        self.resolve_moves("bm")
+    }
+    /// The moves listed by the `am` operation, empty if there is none
+    pub fn avoid_moves(&self) -> Result<Vec<SanMove>, EpdError> {
+        self.resolve_moves("am")
+    }
+
+    fn resolve_moves(&self, opcode: &str) -> Result<Vec<SanMove>, EpdError> {
+        self.operation(opcode)
+            .unwrap_or_default()
+            .iter()
+            .map(|san| {
+                self.board
+                    .parse_san(san)
+                    .map(|m| self.board.get_san(&m.board))
+                    .map_err(|_| EpdError::IllegalMove(san.clone()))
+            })
+            .collect()
+    }
+
+    /// FEN of the position, including the clocks
+    pub fn into_fen(&self) -> String {
+        self.board.into_fen()
+    }
+}
+
+fn parse_operations(s: &str) -> Result<Vec<EpdOperation>, EpdError> {
+    let mut res = Vec::new();
+    let mut chars = s.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.peek().is_none() {
+            return Ok(res);
+        }
+
+        let mut opcode = String::new();
+        while let Some(c) = chars.next_if(|&c| !c.is_whitespace() && c != ';') {
+            opcode.push(c);
+        }
+        let valid = opcode.starts_with(|c: char| c.is_ascii_alphabetic())
+            && opcode
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid {
+            return Err(EpdError::Syntax(format!("invalid opcode '{opcode}'")));
+        }
+
+        let mut operands = Vec::new();
+        loop {
+            while chars.next_if(|c| c.is_whitespace()).is_some() {}
+            match chars.next() {
+                // The semicolon of the last operation is commonly left out
+                None | Some(';') => break,
+                Some('"') => {
+                    let mut operand = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('"') => break,
+                            Some(c) => operand.push(c),
+                            None => {
+                                return Err(EpdError::Syntax(format!(
+                                    "unterminated string in operation '{opcode}'"
+                                )))
+                            }
+                        }
+                    }
+                    operands.push(operand);
+                }
+                Some(c) => {
+                    let mut operand = c.to_string();
+                    while let Some(c) = chars.next_if(|&c| !c.is_whitespace() && c != ';') {
+                        operand.push(c);
+                    }
+                    operands.push(operand);
+                }
+            }
+        }
+        res.push(EpdOperation { opcode, operands });
+    }
+}
+
+fn clock(operations: &[EpdOperation], opcode: &str, field: FenField) -> Result<String, EpdError> {
+    let operation = operations.iter().find(|o| o.opcode == opcode);
+    match operation.and_then(|o| o.operands.first()) {
+        None if field == FenField::Halfmove => Ok("0".to_string()),
+        None => Ok("1".to_string()),
+        // Checked here as well, since anything after it would be taken as another FEN field
+        Some(n) if n.parse::<u32>().is_ok() => Ok(n.clone()),
+        Some(n) => Err(EpdError::InvalidFen(FenError::InvalidClock(
+            field,
+            n.clone(),
+        ))),
+    }
+}
+
+impl FromStr for Epd {
+    type Err = EpdError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut rest = s.trim();
+        let mut fields = Vec::new();
+        for field in FIELDS {
+            if rest.is_empty() {
+                return Err(EpdError::InvalidFen(FenError::MissingField(field)));
+            }
+            let (value, r) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+            fields.push(value);
+            rest = r.trim_start();
+        }
+
+        let operations = parse_operations(rest)?;
+        let halfmove = clock(&operations, "hmvc", FenField::Halfmove)?;
+        let fullmove = clock(&operations, "fmvn", FenField::Fullmove)?;
+        let board = Board::from_fen(&format!("{} {halfmove} {fullmove}", fields.join(" ")))?;
+
+        Ok(Self { board, operations })
+    }
+}
+
+impl Display for Epd {
+    /// The first four FEN fields followed by the operations, with hmvc and fmvn written from the
+    /// clocks of the board
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let fen = self.board.into_fen();
+        let fields: Vec<&str> = fen.split(' ').take(4).collect();
+        write!(f, "{}", fields.join(" "))?;
+
+        let clocks = [
+            ("hmvc", self.board.halfmove(), 0),
+            ("fmvn", self.board.fullmove(), 1),
+        ];
+        // Clocks without an operation are left out when they are the defaults
+        for (opcode, value, default) in clocks {
+            if value != default && self.operation(opcode).is_none() {
+                write!(f, " {opcode} {value};")?;
+            }
+        }
+        for operation in &self.operations {
+            match clocks
+                .iter()
+                .find(|(opcode, ..)| *opcode == operation.opcode)
+            {
+                Some((opcode, value, _)) => write!(f, " {opcode} {value};")?,
+                None => write!(f, " {operation}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reason for an EPD record being rejected, or its moves not being resolved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EpdError {
+    /// The position fields (or the clocks given by hmvc and fmvn) are not valid FEN
+    InvalidFen(FenError),
+    /// Malformed operation
+    Syntax(String),
+    /// A move of a `bm` or `am` operation that is not legal (or not valid SAN) in the position
+    IllegalMove(String),
+}
+impl Display for EpdError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidFen(e) => write!(f, "Invalid EPD: {e}"),
+            Self::Syntax(message) => write!(f, "Invalid EPD: {message}"),
+            Self::IllegalMove(san) => write!(f, "Invalid EPD: illegal move '{san}'"),
+        }
+    }
+}
+impl Error for EpdError {}
+impl From<FenError> for EpdError {
+    fn from(value: FenError) -> Self {
+        Self::InvalidFen(value)
+    }
+}
@@ -1,106 +1,371 @@
 use super::{Colour, Pieces};
-use crate::{board::Board, castling_rights, createBase, piece_map_bitboards, utils::squares};
-use core::panic;
-use std::convert::From;
-
-pub const EMPTY_BOARD: &str = "8/8/8/8/8/8/8/8 w - - 0 1";
-pub const START_POSITION: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 ";
-pub const CASTLE_KINGSIDE_POSITION: &str =
-    "rnbqk2r/pppppppp/8/8/8/8/PPPPPPPP/RNBQK2R w KQkq - 0 1 ";
-pub const CASTLE_QUEENSIDE_POSITION: &str = "r3kbnr/pppppppp/8/8/8/8/PPPPPPPP/R3KBNR w KQkq - 0 1 ";
-
-pub const PROMOTION_POSITION: &str = "4k3/1P6/2P5/8/8/8/8/4K3 w - - 0 1";
-
-pub const CHECK_POSITION: &str = "4k3/1P6/2P5/8/8/8/5p2/4K3 w - - 0 1";
-
-pub const TRICKY_POSITION: &str =
-    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1 ";
-pub const KILLER_POSITION: &str =
-    "rnbqkb1r/pp1p1pPp/8/2p1pP2/1P1P4/3P3P/P1P1P3/RNBQKBNR w KQkq e6 0 1";
-pub const CMK_POSITION: &str =
-    "r2q1rk1/ppp2ppp/2n1bn2/2b1p3/3pP3/3P1NPP/PPP1NPB1/R1BQ1RK1 b - - 0 9 ";
-
-/// Create a chess board instance from fen
-impl<T> From<T> for Board
-where
-    T: Into<String>,
-{
-    fn from(value: T) -> Self {
-        let val = value.into();
-        let s = val.split_at((&val).find(" ").unwrap());
-        let ranks = s.0.split("/");
-        let mut args = s.1.split(" ");
-
-        let mut white = Pieces::default();
-        let mut black = Pieces::default();
-        let mut i: i32 = 63;
-        for rank in ranks {
-            for c in rank.chars().rev() {
-                if let Ok(n) = c.to_string().parse::<i32>() {
-                    i -= n;
+use crate::{
+    board::{Board, Side},
+    castling_rights, createBase, piece_map_bitboards,
+    utils::squares::{Rank, Square},
+    PositionError,
+};
+use std::{error::Error, fmt::Display, ops::Deref, str::FromStr};
+
+/// FEN string that is known to be valid. Only the built-in positions below are of this type,
+/// which is why they (unlike arbitrary strings) can be converted into a `Board` infallibly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fen(&'static str);
+impl Fen {
+    pub fn as_str(&self) -> &'static str {
+        self.0
+    }
+}
+impl Deref for Fen {
+    type Target = str;
+    fn deref(&self) -> &Self::Target {
+        self.0
+    }
+}
+impl Display for Fen {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+pub const EMPTY_BOARD: Fen = Fen("8/8/8/8/8/8/8/8 w - - 0 1");
+pub const START_POSITION: Fen = Fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1 ");
+pub const CASTLE_KINGSIDE_POSITION: Fen =
+    Fen("rnbqk2r/pppppppp/8/8/8/8/PPPPPPPP/RNBQK2R w KQkq - 0 1 ");
+pub const CASTLE_QUEENSIDE_POSITION: Fen =
+    Fen("r3kbnr/pppppppp/8/8/8/8/PPPPPPPP/R3KBNR w KQkq - 0 1 ");
+
+pub const PROMOTION_POSITION: Fen = Fen("4k3/1P6/2P5/8/8/8/8/4K3 w - - 0 1");
+
+pub const CHECK_POSITION: Fen = Fen("4k3/1P6/2P5/8/8/8/5p2/4K3 w - - 0 1");
+
+pub const TRICKY_POSITION: Fen =
+    Fen("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1 ");
+pub const KILLER_POSITION: Fen =
+    Fen("rnbqkb1r/pp1p1pPp/8/2p1pP2/1P1P4/3P3P/P1P1P3/RNBQKBNR w KQkq e6 0 1");
+pub const CMK_POSITION: Fen =
+    Fen("r2q1rk1/ppp2ppp/2n1bn2/2b1p3/3pP3/3P1NPP/PPP1NPB1/R1BQ1RK1 b - - 0 9 ");
+
+/// The six space separated fields of a FEN string
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FenField {
+    Placement,
+    SideToMove,
+    Castling,
+    EnPassent,
+    Halfmove,
+    Fullmove,
+}
+impl Display for FenField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Placement => write!(f, "piece placement"),
+            Self::SideToMove => write!(f, "side to move"),
+            Self::Castling => write!(f, "castling rights"),
+            Self::EnPassent => write!(f, "en passent square"),
+            Self::Halfmove => write!(f, "halfmove clock"),
+            Self::Fullmove => write!(f, "fullmove number"),
+        }
+    }
+}
+const FIELDS: [FenField; 6] = [
+    FenField::Placement,
+    FenField::SideToMove,
+    FenField::Castling,
+    FenField::EnPassent,
+    FenField::Halfmove,
+    FenField::Fullmove,
+];
+
+/// Reason for a FEN string being rejected by `Board::from_fen`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FenError {
+    /// The string ended before the given field
+    MissingField(FenField),
+    /// There was more input after the fullmove number
+    TooManyFields,
+    /// The piece placement did not contain exactly eight ranks (found count is attached)
+    WrongRankCount(usize),
+    /// A rank (1-8) did not describe exactly eight squares
+    WrongRankLength(usize),
+    InvalidPiece(char),
+    InvalidSideToMove(String),
+    InvalidCastling(String),
+    InvalidEnPassent(String),
+    /// One of the clocks was not a non-negative number
+    InvalidClock(FenField, String),
+    /// The FEN string is well-formed, but the position it describes is not valid
+    InvalidPosition(PositionError),
+}
+impl FenError {
+    /// The field that could not be parsed
+    pub fn field(&self) -> Option<FenField> {
+        match self {
+            Self::MissingField(field) | Self::InvalidClock(field, _) => Some(*field),
+            Self::TooManyFields | Self::InvalidPosition(_) => None,
+            Self::WrongRankCount(_) | Self::WrongRankLength(_) | Self::InvalidPiece(_) => {
+                Some(FenField::Placement)
+            }
+            Self::InvalidSideToMove(_) => Some(FenField::SideToMove),
+            Self::InvalidCastling(_) => Some(FenField::Castling),
+            Self::InvalidEnPassent(_) => Some(FenField::EnPassent),
+        }
+    }
+}
+impl Display for FenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid FEN: ")?;
+        match self {
+            Self::MissingField(field) => write!(f, "missing {field}"),
+            Self::TooManyFields => write!(f, "unexpected input after the fullmove number"),
+            Self::WrongRankCount(n) => write!(f, "expected 8 ranks, found {n}"),
+            Self::WrongRankLength(rank) => write!(f, "rank {rank} does not have 8 squares"),
+            Self::InvalidPiece(c) => write!(f, "invalid piece symbol '{c}'"),
+            Self::InvalidSideToMove(s) => write!(f, "side to move must be 'w' or 'b', got '{s}'"),
+            Self::InvalidCastling(s) => write!(f, "invalid castling rights '{s}'"),
+            Self::InvalidEnPassent(s) => write!(f, "invalid en passent square '{s}'"),
+            Self::InvalidClock(field, s) => write!(f, "{field} must be a number, got '{s}'"),
+            Self::InvalidPosition(e) => write!(f, "{e}"),
+        }
+    }
+}
+impl Error for FenError {}
+impl From<PositionError> for FenError {
+    fn from(value: PositionError) -> Self {
+        Self::InvalidPosition(value)
+    }
+}
+
+fn parse_placement(placement: &str) -> Result<(Pieces, Pieces), FenError> {
+    let mut white = Pieces::default();
+    let mut black = Pieces::default();
+
+    let ranks: Vec<&str> = placement.split('/').collect();
+    if ranks.len() != 8 {
+        return Err(FenError::WrongRankCount(ranks.len()));
+    }
+    // Ranks are listed from the eighth down to the first
+    for (rank, rank_str) in (0..8).rev().zip(ranks) {
+        let mut file = 0;
+        for c in rank_str.chars() {
+            if file >= 8 {
+                return Err(FenError::WrongRankLength(rank as usize + 1));
+            }
+            if let Some(n) = c.to_digit(10) {
+                if n == 0 || file + n as i32 > 8 {
+                    return Err(FenError::WrongRankLength(rank as usize + 1));
+                }
+                file += n as i32;
+                continue;
+            }
+            let square = rank * 8 + file;
+            match c {
+                'p' => black.pawns.push(square),
+                'P' => white.pawns.push(square),
+                'k' => black.king.push(square),
+                'K' => white.king.push(square),
+                'q' => black.queens.push(square),
+                'Q' => white.queens.push(square),
+                'b' => black.bishops.push(square),
+                'B' => white.bishops.push(square),
+                'n' => black.knights.push(square),
+                'N' => white.knights.push(square),
+                'r' => black.rooks.push(square),
+                'R' => white.rooks.push(square),
+                _ => return Err(FenError::InvalidPiece(c)),
+            };
+            file += 1;
+        }
+        if file != 8 {
+            return Err(FenError::WrongRankLength(rank as usize + 1));
+        }
+    }
+
+    Ok((white, black))
+}
+
+// Files of the rooks on the back rank of `colour`, along with the file of its king if the king
+// is on the back rank
+fn back_rank_files(pieces: &Pieces, colour: Colour) -> (Option<i32>, Vec<i32>) {
+    let back_rank = match colour {
+        Colour::White => 0,
+        Colour::Black => 7,
+    };
+    let king = pieces
+        .king
+        .iter()
+        .find(|&&k| k / 8 == back_rank)
+        .map(|k| k % 8);
+    let rooks = pieces
+        .rooks
+        .iter()
+        .filter(|&&r| r / 8 == back_rank)
+        .map(|r| r % 8)
+        .collect();
+    (king, rooks)
+}
+
+// Accepts standard `KQkq` castling rights as well as the file letters of Shredder-FEN and X-FEN.
+// `K` and `Q` refer to the outermost rook on that side of the king. Also returns whether the
+// castling rights can only be those of a Chess960 position.
+fn parse_castling(
+    castling: &str,
+    white: &Pieces,
+    black: &Pieces,
+) -> Result<(castling_rights, bool), FenError> {
+    let mut rights = castling_rights::none();
+    if castling == "-" {
+        return Ok((rights, false));
+    }
+    let error = || FenError::InvalidCastling(castling.to_string());
+
+    let mut chess960 = false;
+    for c in castling.chars() {
+        let (colour, pieces) = if c.is_ascii_uppercase() {
+            (Colour::White, white)
+        } else {
+            (Colour::Black, black)
+        };
+        let (king, rooks) = back_rank_files(pieces, colour);
+        let (side, file) = match c.to_ascii_lowercase() {
+            'k' => {
+                let file = king
+                    .and_then(|k| rooks.iter().filter(|&&r| r > k).max().copied())
+                    .unwrap_or(7);
+                (Side::King, file)
+            }
+            'q' => {
+                let file = king
+                    .and_then(|k| rooks.iter().filter(|&&r| r < k).min().copied())
+                    .unwrap_or(0);
+                (Side::Queen, file)
+            }
+            f @ 'a'..='h' => {
+                let file = f as i32 - 'a' as i32;
+                let king = king.ok_or_else(error)?;
+                if file > king {
+                    (Side::King, file)
+                } else if file < king {
+                    (Side::Queen, file)
                 } else {
-                    match c {
-                        'p' => black.pawns.push(i),
-                        'P' => white.pawns.push(i),
-                        'k' => black.king.push(i),
-                        'K' => white.king.push(i),
-                        'q' => black.queens.push(i),
-                        'Q' => white.queens.push(i),
-                        'b' => black.bishops.push(i),
-                        'B' => white.bishops.push(i),
-                        'n' => black.knights.push(i),
-                        'N' => white.knights.push(i),
-                        'r' => black.rooks.push(i),
-                        'R' => white.rooks.push(i),
-                        _ => panic!("Invalid symbol '{c}'"),
-                    };
-                    i -= 1;
+                    return Err(error());
                 }
             }
+            _ => return Err(error()),
+        };
+        if rights.file(colour, side) >= 0 {
+            return Err(error());
         }
+        rights.set_file(colour, side, file);
+
+        let standard_file = match side {
+            Side::King => 7,
+            Side::Queen => 0,
+        };
+        if !matches!(c, 'K' | 'Q' | 'k' | 'q')
+            || king.is_some_and(|k| k != 4)
+            || file != standard_file
+        {
+            chess960 = true;
+        }
+    }
+    Ok((rights, chess960))
+}
+
+fn parse_killer_square(square: &str, side_to_move: Colour) -> Result<Option<Square>, FenError> {
+    if square == "-" {
+        return Ok(None);
+    }
+    // The killer square is always behind a pawn that has just made a double push, so it has to
+    // be on the third rank with black to move or on the sixth rank with white to move
+    let expected_rank = match side_to_move {
+        Colour::White => Rank::Sixth,
+        Colour::Black => Rank::Third,
+    };
+    match square.parse::<Square>() {
+        Ok(s) if s.rank() == expected_rank => Ok(Some(s)),
+        _ => Err(FenError::InvalidEnPassent(square.to_string())),
+    }
+}
+
+fn parse_clock(clock: &str, field: FenField) -> Result<i32, FenError> {
+    match clock.parse::<i32>() {
+        Ok(n) if n >= 0 => Ok(n),
+        _ => Err(FenError::InvalidClock(field, clock.to_string())),
+    }
+}
+
+impl Board {
+    /// Create a chess board instance from FEN. The fields are checked for correct syntax and
+    /// the resulting position is checked with `Board::validate`.
+    pub fn from_fen(fen: &str) -> Result<Self, FenError> {
+        let board = Self::parse_fen(fen)?;
+        board.validate()?;
+        Ok(board)
+    }
+
+    // Reads the fields of a FEN string without checking the position they describe
+    fn parse_fen(fen: &str) -> Result<Self, FenError> {
+        let mut fields = fen.split_whitespace();
+        let mut next_field = |i: usize| fields.next().ok_or(FenError::MissingField(FIELDS[i]));
 
-        args.next();
-        let side_to_move = match args.next().unwrap() {
+        let (mut white, mut black) = parse_placement(next_field(0)?)?;
+        let side_to_move = match next_field(1)? {
             "w" => Colour::White,
             "b" => Colour::Black,
-            _ => panic!("Invalid side to move"),
+            s => return Err(FenError::InvalidSideToMove(s.to_string())),
         };
+        let (castling_rights, chess960) = parse_castling(next_field(2)?, &white, &black)?;
+        let killer_square = parse_killer_square(next_field(3)?, side_to_move)?;
+        let halfmove = parse_clock(next_field(4)?, FenField::Halfmove)?;
+        let fullmove = parse_clock(next_field(5)?, FenField::Fullmove)?;
 
-        let castling_rights = castling_rights::from(args.next().unwrap());
-        let killer_square = squares::from_str(args.next().unwrap());
-        let halfmove = args.next().unwrap().parse::<i32>().unwrap();
-        let fullmove = args.next().unwrap().parse::<i32>().unwrap();
+        if fields.next().is_some() {
+            return Err(FenError::TooManyFields);
+        }
 
         unsafe {
             let base = createBase(
                 piece_map_bitboards::from(&mut white),
                 piece_map_bitboards::from(&mut black),
             );
-            Self::new(
+            let mut board = Self::new_unchecked(
                 base,
                 killer_square,
                 castling_rights,
                 side_to_move,
                 halfmove,
                 fullmove,
-            )
+            );
+            board.set_chess960(chess960);
+            Ok(board)
         }
     }
 }
 
-impl From<&str> for castling_rights {
-    fn from(value: &str) -> Self {
-        let white_king = value.contains("K");
-        let white_queen = value.contains("Q");
-        let black_king = value.contains("k");
-        let black_queen = value.contains("q");
-
-        Self {
-            white_king,
-            white_queen,
-            black_king,
-            black_queen,
-        }
+/// Built-in positions are known to be well-formed, so the conversion cannot fail. They are not
+/// validated, so that `EMPTY_BOARD` can be used to start setting up a position.
+impl From<Fen> for Board {
+    fn from(value: Fen) -> Self {
+        Self::parse_fen(value.0).expect("Built-in FEN constants should be well-formed")
+    }
+}
+impl FromStr for Board {
+    type Err = FenError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_fen(s)
+    }
+}
+impl TryFrom<&str> for Board {
+    type Error = FenError;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Self::from_fen(value)
+    }
+}
+impl TryFrom<String> for Board {
+    type Error = FenError;
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::from_fen(&value)
     }
 }
 
@@ -112,7 +377,42 @@ macro_rules! convert_into_character {
     };
 }
 impl Board {
+    /// FEN of the position. Castling rights are written as `KQkq`, unless this is a Chess960
+    /// position in which case X-FEN is used: the file letter of the rook is only written when
+    /// `K` or `Q` would be ambiguous.
     pub fn into_fen(&self) -> String {
+        self.fen_with_castling(|board, colour, side, file| {
+            let (_, rooks) = back_rank_files(
+                &Pieces::from(match colour {
+                    Colour::White => board.base.white,
+                    Colour::Black => board.base.black,
+                }),
+                colour,
+            );
+            let outermost = match side {
+                Side::King => rooks.iter().all(|&r| r <= file),
+                Side::Queen => rooks.iter().all(|&r| r >= file),
+            };
+            if !board.is_chess960() || outermost {
+                match side {
+                    Side::King => 'K',
+                    Side::Queen => 'Q',
+                }
+            } else {
+                (b'A' + file as u8) as char
+            }
+        })
+    }
+
+    /// FEN of the position with castling rights written as the file letters of the rooks
+    /// (Shredder-FEN), e.g. `HAha` for the starting position
+    pub fn into_shredder_fen(&self) -> String {
+        self.fen_with_castling(|_, _, _, file| (b'A' + file as u8) as char)
+    }
+
+    // `castling` gives the (uppercase) letter of a castling right of the given colour and side
+    // with the rook on the given file
+    fn fen_with_castling(&self, castling: impl Fn(&Self, Colour, Side, i32) -> char) -> String {
         let mut board = ['-'; 64];
         let white_pieces = Pieces::from(self.base.white);
         let black_pieces = Pieces::from(self.base.black);
@@ -158,27 +458,28 @@ impl Board {
             res += " b ";
         }
 
-        let castling_rights = self.castling_rights_as_arr();
-        if castling_rights.iter().all(|r| !r) {
+        let castling_rights = self.castling_rights();
+        if castling_rights.as_arr().iter().all(|&f| f < 0) {
             res += "-";
         } else {
-            if castling_rights[0] {
-                res += "K";
-            }
-            if castling_rights[1] {
-                res += "Q";
-            }
-            if castling_rights[2] {
-                res += "k";
-            }
-            if castling_rights[3] {
-                res += "q";
+            for colour in [Colour::White, Colour::Black] {
+                for side in [Side::King, Side::Queen] {
+                    let file = castling_rights.file(colour, side);
+                    if file < 0 {
+                        continue;
+                    }
+                    let c = castling(self, colour, side, file);
+                    res.push(match colour {
+                        Colour::White => c,
+                        Colour::Black => c.to_ascii_lowercase(),
+                    });
+                }
             }
         }
 
-        if let Some(killer_square) = squares::to_str(self.killer_square()) {
+        if let Some(killer_square) = self.killer_square() {
             res += " ";
-            res += &killer_square;
+            res += &killer_square.to_string();
         } else {
             res += " -";
         }
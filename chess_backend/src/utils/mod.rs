@@ -1,25 +1,66 @@
+pub mod epd;
 pub mod fen;
 pub mod san;
 pub mod squares;
+pub mod uci;
+pub mod zobrist;
 
 use core::panic;
 use std::{error::Error, fmt::Display};
 
-use crate::{piece_map_bitboards, squares_to_bitboard};
+use crate::{board::Side, piece_map_bitboards, BitBoard};
+use squares::Square;
 
-#[derive(Debug)]
+/// Reason for a move or position given as input being rejected
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ChessError {
-    InputError,
+    /// The input is not a move in the expected notation
+    MalformedMove(String),
+    /// More than one legal move matches, e.g. a knight move without the file or rank of the
+    /// knight, or a promotion without the piece
+    AmbiguousMove(String),
+    /// There is no piece of the side to move on the square the move starts from
+    NoPieceOnSquare(Square),
+    /// None of the pieces of the side to move can make the move
+    IllegalMove(String),
+    /// The move would leave the king (on the attached square) in check
+    KingInCheck(String, Square),
+    /// The side to move has lost the right to castle on that side
+    NoCastlingRights(Side),
+    /// There are pieces between the king and the rook, or the king is in check or would pass
+    /// through or land on an attacked square
+    CastlingBlocked(Side),
 }
 impl Display for ChessError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "ChessError")
+        match self {
+            Self::MalformedMove(m) => write!(f, "Invalid move: '{m}' is not a move"),
+            Self::AmbiguousMove(m) => {
+                write!(f, "Invalid move: '{m}' matches more than one legal move")
+            }
+            Self::NoPieceOnSquare(s) => write!(f, "Invalid move: no piece to move on {s}"),
+            Self::IllegalMove(m) => write!(f, "Invalid move: '{m}' is not a legal move"),
+            Self::KingInCheck(m, s) => {
+                write!(f, "Invalid move: '{m}' leaves the king on {s} in check")
+            }
+            Self::NoCastlingRights(side) => {
+                write!(f, "Invalid move: no right to castle {side:?}side")
+            }
+            Self::CastlingBlocked(side) => {
+                write!(
+                    f,
+                    "Invalid move: castling {side:?}side is blocked or through check"
+                )
+            }
+        }
     }
 }
 impl Error for ChessError {}
 
 /// Enum representation of the colour of pieces.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum Colour {
     White,
     Black,
@@ -39,7 +80,9 @@ impl Colour {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum Piece {
     Pawn(Colour),
     King(Colour),
@@ -49,6 +92,16 @@ pub enum Piece {
     Rook(Colour),
 }
 impl Piece {
+    pub fn colour(&self) -> Colour {
+        match *self {
+            Self::Pawn(c)
+            | Self::King(c)
+            | Self::Queen(c)
+            | Self::Bishop(c)
+            | Self::Knight(c)
+            | Self::Rook(c) => c,
+        }
+    }
     pub fn symbol(&self) -> Option<&str> {
         match self {
             Self::Pawn(Colour::Black) => Some("♙"),
@@ -86,6 +139,17 @@ impl Piece {
             _ => panic!("Invalid piece string"),
         }
     }
+    /// The same kind of piece in the given colour
+    pub fn with_colour(&self, colour: Colour) -> Piece {
+        match self {
+            Self::Pawn(_) => Self::Pawn(colour),
+            Self::King(_) => Self::King(colour),
+            Self::Queen(_) => Self::Queen(colour),
+            Self::Bishop(_) => Self::Bishop(colour),
+            Self::Knight(_) => Self::Knight(colour),
+            Self::Rook(_) => Self::Rook(colour),
+        }
+    }
 }
 
 pub struct Pieces {
@@ -110,49 +174,44 @@ impl Default for Pieces {
 }
 impl From<piece_map_bitboards> for Pieces {
     fn from(value: piece_map_bitboards) -> Self {
-        let king = extract_squares(value.king);
-        let queens = extract_squares(value.queens);
-        let bishops = extract_squares(value.bishops);
-        let knights = extract_squares(value.knights);
-        let rooks = extract_squares(value.rooks);
-        let pawns = extract_squares(value.pawns);
         Self {
-            king,
-            queens,
-            bishops,
-            knights,
-            rooks,
-            pawns,
+            king: extract_squares(value.king),
+            queens: extract_squares(value.queens),
+            bishops: extract_squares(value.bishops),
+            knights: extract_squares(value.knights),
+            rooks: extract_squares(value.rooks),
+            pawns: extract_squares(value.pawns),
         }
     }
 }
 
 pub fn extract_squares(bitboard: u64) -> Vec<i32> {
-    let mut res = Vec::new();
-    let mut i = 1;
-    for iter in 0..64 {
-        if bitboard & i != 0 {
-            res.push(iter);
-        }
-        i <<= 1;
-    }
+    BitBoard(bitboard).iter().map(|s| s.as_int()).collect()
+}
 
-    res
+// Squares that are not on the board are ignored
+fn squares_to_bitboard(squares: &[i32]) -> u64 {
+    squares
+        .iter()
+        .filter_map(|&s| Square::from_index(s))
+        .collect::<BitBoard>()
+        .0
 }
 
-unsafe fn convert_piece_map_to_bitboards(colour: &mut Pieces) -> piece_map_bitboards {
-    piece_map_bitboards {
-        pawns: squares_to_bitboard(colour.pawns.as_mut_ptr(), colour.pawns.len() as i32),
-        king: squares_to_bitboard(colour.king.as_mut_ptr(), colour.king.len() as i32),
-        queens: squares_to_bitboard(colour.queens.as_mut_ptr(), colour.queens.len() as i32),
-        bishops: squares_to_bitboard(colour.bishops.as_mut_ptr(), colour.bishops.len() as i32),
-        knights: squares_to_bitboard(colour.knights.as_mut_ptr(), colour.knights.len() as i32),
-        rooks: squares_to_bitboard(colour.rooks.as_mut_ptr(), colour.rooks.len() as i32),
+impl From<&Pieces> for piece_map_bitboards {
+    fn from(value: &Pieces) -> Self {
+        Self {
+            pawns: squares_to_bitboard(&value.pawns),
+            king: squares_to_bitboard(&value.king),
+            queens: squares_to_bitboard(&value.queens),
+            bishops: squares_to_bitboard(&value.bishops),
+            knights: squares_to_bitboard(&value.knights),
+            rooks: squares_to_bitboard(&value.rooks),
+        }
     }
 }
-
 impl From<&mut Pieces> for piece_map_bitboards {
     fn from(value: &mut Pieces) -> Self {
-        unsafe { convert_piece_map_to_bitboards(value) }
+        Self::from(&*value)
     }
 }
@@ -2,11 +2,14 @@ use std::fmt::Display;
 
 // Standard Algebraic Notation SAN
 use crate::{
-    board::ReasonWin, generateAttackTargets, piece_map_bitboards, Board, ChessMove, Colour,
-    FinishedState, GameState, Piece,
+    board::{ChessMoveBase, ReasonWin, Side},
+    piece_map_bitboards, Board, ChessMove, Colour, FinishedState, GameState, MoveType, Piece,
 };
 
-use super::{squares, ChessError};
+use super::{
+    squares::{e1, e8, File, Rank, Square},
+    ChessError,
+};
 
 // Special moves
 const CASTLE_KING: &str = "O-O";
@@ -18,9 +21,9 @@ pub struct SanMove {
     capture: bool,
     check: bool,
     checkmate: bool,
-    origin: i32,
+    origin: Square,
     disambiguation: (bool, bool),
-    dest: i32,
+    dest: Square,
     promotion: Option<Piece>,
     castling: Option<(bool, bool)>,
 }
@@ -30,9 +33,9 @@ impl SanMove {
         capture: bool,
         check: bool,
         checkmate: bool,
-        origin: i32,
+        origin: Square,
         disambiguation: (bool, bool),
-        dest: i32,
+        dest: Square,
         promotion: Option<Piece>,
         castling: Option<(bool, bool)>,
     ) -> Self {
@@ -50,11 +53,8 @@ impl SanMove {
     }
 
     pub fn kingside_castle(colour: Colour) -> Self {
-        let (origin, dest) = if colour == Colour::White {
-            (4, 6)
-        } else {
-            (60, 62)
-        };
+        let origin = if colour == Colour::White { e1 } else { e8 };
+        let dest = Square::new(File::G, origin.rank());
         Self {
             piece: Piece::King(colour),
             capture: false,
@@ -69,11 +69,8 @@ impl SanMove {
     }
 
     pub fn queenside_castle(colour: Colour) -> Self {
-        let (origin, dest) = if colour == Colour::White {
-            (4, 2)
-        } else {
-            (60, 58)
-        };
+        let origin = if colour == Colour::White { e1 } else { e8 };
+        let dest = Square::new(File::C, origin.rank());
         Self {
             piece: Piece::King(colour),
             capture: false,
@@ -86,15 +83,20 @@ impl SanMove {
             castling: Some((false, true)),
         }
     }
+    pub fn origin(&self) -> Square {
+        self.origin
+    }
+    pub fn dest(&self) -> Square {
+        self.dest
+    }
+    pub fn promotion(&self) -> Option<Piece> {
+        self.promotion
+    }
+
+    /// Parses SAN with `Board::parse_san` and gives the move in its canonical form
     pub fn from_string(value: impl Into<String>, origin_board: Board) -> Result<Self, ChessError> {
-        let value: String = value.into();
-        for m in origin_board.generate_legal_moves() {
-            let san = origin_board.get_san(&m.board);
-            if <SanMove as Into<String>>::into(san) == value {
-                return Ok(san);
-            }
-        }
-        Err(ChessError::InputError)
+        let m = origin_board.parse_san(&value.into())?;
+        Ok(origin_board.get_san(&m.board))
     }
 }
 impl Into<String> for SanMove {
@@ -111,19 +113,18 @@ impl Into<String> for SanMove {
         let mut res = String::new();
         res += &self.piece.letter();
 
-        let origin_square = squares::to_str(self.origin).expect("Invalid origin number");
         if self.disambiguation.0 {
-            res += &origin_square[0..1];
+            res.push(self.origin.file().as_char());
         }
         if self.disambiguation.1 {
-            res += &origin_square[1..2];
+            res.push(self.origin.rank().as_char());
         }
 
         if self.capture {
             res += "x";
         }
 
-        res += &squares::to_str(self.dest).expect("Invalid destination number");
+        res += &self.dest.to_string();
 
         if let Some(res_piece) = self.promotion {
             res += "=";
@@ -170,8 +171,8 @@ macro_rules! find_descrepency {
             if discrepency && $dest_square != None {
                 let possible_moves = $possible_boards
                     .iter()
-                    .filter(|m| $pieces.contains(&m.base.piece) && m.base.destination_square.unwrap_or_default() == $dest_square.unwrap() as i32)
-                    .map(|m| m.base.starting_square.unwrap())
+                    .filter(|m| $pieces.contains(&m.base.piece) && m.base.destination_square.map(|s| s.as_int()) == Some($dest_square.unwrap() as i32))
+                    .map(|m| m.base.starting_square.unwrap().as_int())
                     .collect::<Vec<i32>>();
                 $disambiguation = if possible_moves.len() > 1 {
                     let origin = $origin_square.unwrap() as i32;
@@ -228,46 +229,48 @@ impl Board {
     pub fn get_san(&self, res_board: &Self) -> SanMove {
         let possible_boards = self.generate_legal_moves();
         let colour = self.side_to_move();
-        let (origin_pieces, dest_pieces, other_base, res_other_base, other_king) =
-            if colour == Colour::White {
-                (
-                    self.base.white,
-                    res_board.base.white,
-                    self.base.black,
-                    res_board.base.black,
-                    res_board.base.black.king,
-                )
-            } else {
-                (
-                    self.base.black,
-                    res_board.base.black,
-                    self.base.white,
-                    res_board.base.white,
-                    res_board.base.white.king,
-                )
-            };
-
-        let res_attack = unsafe {
-            generateAttackTargets(
-                res_board.base.get_side(colour),
-                colour.as_int(),
-                res_board.base.black_occupied + res_board.base.white_occupied,
+        let (origin_pieces, dest_pieces, other_base, res_other_base) = if colour == Colour::White {
+            (
+                self.base.white,
+                res_board.base.white,
+                self.base.black,
+                res_board.base.black,
+            )
+        } else {
+            (
+                self.base.black,
+                res_board.base.black,
+                self.base.white,
+                res_board.base.white,
             )
         };
 
+        // Check for castling. In Chess960 the king and rook can end up on each other's squares
+        // so this can not be told from the changed bitboards alone.
+        if let Some(m) = possible_boards
+            .iter()
+            .find(|m| m.board == *res_board && matches!(m.base.move_type, MoveType::Castling(_)))
+        {
+            let origin = m
+                .base
+                .starting_square
+                .expect("Castling should have a king square");
+            return match m.base.move_type {
+                MoveType::Castling(Side::King) => SanMove {
+                    origin,
+                    dest: Square::new(File::G, origin.rank()),
+                    ..SanMove::kingside_castle(colour)
+                },
+                _ => SanMove {
+                    origin,
+                    dest: Square::new(File::C, origin.rank()),
+                    ..SanMove::queenside_castle(colour)
+                },
+            };
+        }
+
         let (pieces, origin, dest, mut disambiguation) =
             Self::get_move_specs(origin_pieces, dest_pieces, colour, &possible_boards);
-
-        // Check for castling
-        if pieces.contains(&Piece::King(colour)) {
-            if origin == 4 || origin == 60 {
-                if dest == 6 || dest == 62 {
-                    return SanMove::kingside_castle(colour);
-                } else if dest == 2 || dest == 58 {
-                    return SanMove::queenside_castle(colour);
-                }
-            }
-        }
         // if we have not returned a value, castling must be None
         let castling = None;
 
@@ -299,7 +302,7 @@ impl Board {
         {
             (false, true)
         } else {
-            (other_king & res_attack != 0, false)
+            (res_board.is_check(), false)
         };
 
         SanMove {
@@ -307,9 +310,9 @@ impl Board {
             capture,
             check,
             checkmate,
-            origin: origin as i32,
+            origin: Square::from_index_unchecked(origin as i32),
             disambiguation,
-            dest: dest as i32,
+            dest: Square::from_index_unchecked(dest as i32),
             promotion,
             castling,
         }
@@ -325,6 +328,186 @@ impl Board {
             }
         }
 
-        Err(ChessError::InputError)
+        Err(match m.castling {
+            Some((kingside, _)) => {
+                self.castling_rejection(if kingside { Side::King } else { Side::Queen })
+            }
+            None => match self.piece_on(m.origin) {
+                Some(piece) if piece == m.piece && piece.colour() == self.side_to_move() => self
+                    .rejection(&m.to_string(), |base| {
+                        let promotion = match base.move_type {
+                            MoveType::Promotion(p) => Some(p),
+                            _ => None,
+                        };
+                        base.starting_square == Some(m.origin)
+                            && base.destination_square == Some(m.dest)
+                            && promotion == m.promotion
+                    }),
+                _ => ChessError::NoPieceOnSquare(m.origin),
+            },
+        })
+    }
+}
+
+// A SAN move before it is resolved against a position. Pieces are given by their letter, which is
+// empty for pawns.
+#[derive(Debug, PartialEq)]
+enum ParsedSan<'a> {
+    Castling(Side),
+    Move {
+        piece: &'a str,
+        origin_file: Option<File>,
+        origin_rank: Option<Rank>,
+        dest: Square,
+        promotion: Option<&'a str>,
+    },
+}
+
+// Removes annotations and check indicators (`!?`, `+`, `#`) as well as en passent markers
+fn strip_suffixes(mut san: &str) -> &str {
+    loop {
+        let stripped = san.trim_end_matches(['!', '?', '+', '#']).trim_end();
+        let stripped = ["e.p.", "ep"]
+            .iter()
+            .find_map(|marker| stripped.strip_suffix(marker))
+            .unwrap_or(stripped)
+            .trim_end();
+        if stripped == san {
+            return san;
+        }
+        san = stripped;
+    }
+}
+
+fn parse_promotion(san: &str) -> (&str, Option<&str>) {
+    let Some(last) = san.chars().last() else {
+        return (san, None);
+    };
+    let piece = match last.to_ascii_uppercase() {
+        'Q' => "Q",
+        'R' => "R",
+        'B' => "B",
+        'N' => "N",
+        _ => return (san, None),
+    };
+    // A lowercase `b` at the end could only be a file, not a promotion
+    let rest = &san[..san.len() - 1];
+    if last == 'b' && !rest.ends_with('=') {
+        return (san, None);
+    }
+    let rest = rest.strip_suffix(['=', '/']).unwrap_or(rest);
+    (rest, Some(piece))
+}
+
+// Accepts castling with zeros, promotions with or without `=` (`e8=Q`, `e8Q`, `e8q`), captures
+// with `x` or `:`, long algebraic notation (`Ng1-f3`) and over-disambiguated moves (`Ng1f3`)
+fn parse(san: &str) -> Option<ParsedSan<'_>> {
+    let san = strip_suffixes(san.trim());
+    match san.replace('0', "O").as_str() {
+        "O-O" | "OO" => return Some(ParsedSan::Castling(Side::King)),
+        "O-O-O" | "OOO" => return Some(ParsedSan::Castling(Side::Queen)),
+        _ => {}
+    }
+    if !san.is_ascii() {
+        return None;
+    }
+
+    let (piece, rest) = match san.chars().next()? {
+        'K' | 'Q' | 'R' | 'B' | 'N' => san.split_at(1),
+        _ => ("", san),
+    };
+    let (rest, promotion) = match piece {
+        "" => parse_promotion(rest),
+        _ => (rest, None),
+    };
+    let squares: String = rest
+        .chars()
+        .filter(|c| !matches!(c, 'x' | ':' | '-'))
+        .collect();
+    if squares.len() < 2 || squares.len() > 4 {
+        return None;
+    }
+    let (origin, dest) = squares.split_at(squares.len() - 2);
+    let dest = dest.parse().ok()?;
+
+    let mut origin_file = None;
+    let mut origin_rank = None;
+    for c in origin.chars() {
+        match c {
+            'a'..='h' if origin_file.is_none() && origin_rank.is_none() => {
+                origin_file = File::from_char(c)
+            }
+            '1'..='8' if origin_rank.is_none() => origin_rank = Rank::from_char(c),
+            _ => return None,
+        }
+    }
+
+    Some(ParsedSan::Move {
+        piece,
+        origin_file,
+        origin_rank,
+        dest,
+        promotion,
+    })
+}
+
+impl ParsedSan<'_> {
+    fn matches(&self, base: &ChessMoveBase) -> bool {
+        match (self, base.move_type) {
+            (Self::Castling(side), MoveType::Castling(castling_side)) => *side == castling_side,
+            (Self::Castling(_), _) | (Self::Move { .. }, MoveType::Castling(_)) => false,
+            (
+                Self::Move {
+                    piece,
+                    origin_file,
+                    origin_rank,
+                    dest,
+                    promotion,
+                },
+                move_type,
+            ) => {
+                let (Some(origin), Some(destination)) =
+                    (base.starting_square, base.destination_square)
+                else {
+                    return false;
+                };
+                let promotion_matches = match (promotion, move_type) {
+                    (Some(letter), MoveType::Promotion(p)) => p.letter() == *letter,
+                    (Some(_), _) => false,
+                    // Left to be reported as ambiguous, as all four promotions match
+                    (None, _) => true,
+                };
+                base.piece.letter() == *piece
+                    && destination == *dest
+                    && origin_file.is_none_or(|f| origin.file() == f)
+                    && origin_rank.is_none_or(|r| origin.rank() == r)
+                    && promotion_matches
+            }
+        }
+    }
+}
+
+impl Board {
+    /// Finds the legal move for a move in SAN. Besides strict SAN, the common variations found in
+    /// the wild are accepted: missing or superfluous check indicators, annotations (`!?`),
+    /// castling with zeros, `e.p.` suffixes, promotions without `=`, long algebraic notation and
+    /// over-disambiguated moves.
+    ///
+    /// Moves that can not be played are rejected as malformed, ambiguous or illegal, with illegal
+    /// moves further distinguished by the reason (see `ChessError`).
+    pub fn parse_san(&self, san: &str) -> Result<ChessMove, ChessError> {
+        let parsed = parse(san).ok_or_else(|| ChessError::MalformedMove(san.to_string()))?;
+        let mut candidates = self
+            .generate_legal_moves()
+            .into_iter()
+            .filter(|m| parsed.matches(&m.base));
+        match (candidates.next(), candidates.next()) {
+            (Some(m), None) => Ok(m),
+            (Some(_), Some(_)) => Err(ChessError::AmbiguousMove(san.to_string())),
+            (None, _) => Err(match parsed {
+                ParsedSan::Castling(side) => self.castling_rejection(side),
+                ParsedSan::Move { .. } => self.rejection(san, |m| parsed.matches(m)),
+            }),
+        }
     }
 }
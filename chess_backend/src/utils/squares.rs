@@ -1,110 +1,263 @@
-#![allow(dead_code)]
+// Typed squares, files and ranks. Squares are numbered from a1 = 0 to h8 = 63 (rank by rank), which
+// is also the bit of the square in a bitboard.
+use super::Colour;
+use std::{error::Error, fmt::Display, str::FromStr};
 
-use core::panic;
-
-// integer representations of squares to be used by lookup functions
-pub const a1: i32 = 0;
-pub const b1: i32 = 1;
-pub const c1: i32 = 2;
-pub const d1: i32 = 3;
-pub const e1: i32 = 4;
-pub const f1: i32 = 5;
-pub const g1: i32 = 6;
-pub const h1: i32 = 7;
-pub const a2: i32 = 8;
-pub const b2: i32 = 9;
-pub const c2: i32 = 10;
-pub const d2: i32 = 11;
-pub const e2: i32 = 12;
-pub const f2: i32 = 13;
-pub const g2: i32 = 14;
-pub const h2: i32 = 15;
-pub const a3: i32 = 16;
-pub const b3: i32 = 17;
-pub const c3: i32 = 18;
-pub const d3: i32 = 19;
-pub const e3: i32 = 20;
-pub const f3: i32 = 21;
-pub const g3: i32 = 22;
-pub const h3: i32 = 23;
-pub const a4: i32 = 24;
-pub const b4: i32 = 25;
-pub const c4: i32 = 26;
-pub const d4: i32 = 27;
-pub const e4: i32 = 28;
-pub const f4: i32 = 29;
-pub const g4: i32 = 30;
-pub const h4: i32 = 31;
-pub const a5: i32 = 32;
-pub const b5: i32 = 33;
-pub const c5: i32 = 34;
-pub const d5: i32 = 35;
-pub const e5: i32 = 36;
-pub const f5: i32 = 37;
-pub const g5: i32 = 38;
-pub const h5: i32 = 39;
-pub const a6: i32 = 40;
-pub const b6: i32 = 41;
-pub const c6: i32 = 42;
-pub const d6: i32 = 43;
-pub const e6: i32 = 44;
-pub const f6: i32 = 45;
-pub const g6: i32 = 46;
-pub const h6: i32 = 47;
-pub const a7: i32 = 48;
-pub const b7: i32 = 49;
-pub const c7: i32 = 50;
-pub const d7: i32 = 51;
-pub const e7: i32 = 52;
-pub const f7: i32 = 53;
-pub const g7: i32 = 54;
-pub const h7: i32 = 55;
-pub const a8: i32 = 56;
-pub const b8: i32 = 57;
-pub const c8: i32 = 58;
-pub const d8: i32 = 59;
-pub const e8: i32 = 60;
-pub const f8: i32 = 61;
-pub const g8: i32 = 62;
-pub const h8: i32 = 63;
-
-pub fn from_str(s: &str) -> i32 {
-    let mut c = s.chars();
-    let mut res = 0;
-    match c.next().unwrap() {
-        'a' => res += 0,
-        'b' => res += 1,
-        'c' => res += 2,
-        'd' => res += 3,
-        'e' => res += 4,
-        'f' => res += 5,
-        'g' => res += 6,
-        'h' => res += 7,
-        '-' => return -1,
-        _ => {}
-    }
-    res += (c.next().unwrap().to_string().parse::<i32>().unwrap() - 1) * 8;
-    res
+/// One of the eight files, from the a-file to the h-file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum File {
+    A,
+    B,
+    C,
+    D,
+    E,
+    F,
+    G,
+    H,
+}
+impl File {
+    pub const ALL: [Self; 8] = [
+        Self::A,
+        Self::B,
+        Self::C,
+        Self::D,
+        Self::E,
+        Self::F,
+        Self::G,
+        Self::H,
+    ];
+    /// The file with the given index (0 for the a-file)
+    pub fn from_index(index: i32) -> Option<Self> {
+        usize::try_from(index)
+            .ok()
+            .and_then(|i| Self::ALL.get(i))
+            .copied()
+    }
+    pub const fn index(&self) -> i32 {
+        *self as i32
+    }
+    pub fn from_char(c: char) -> Option<Self> {
+        Self::from_index(c as i32 - 'a' as i32)
+    }
+    pub fn as_char(&self) -> char {
+        (b'a' + *self as u8) as char
+    }
 }
 
-pub fn to_str(n: i32) -> Option<String> {
-    if n >= 0 && n < 64 {
-        let mut res = String::from(match n % 8 {
-            0 => 'a',
-            1 => 'b',
-            2 => 'c',
-            3 => 'd',
-            4 => 'e',
-            5 => 'f',
-            6 => 'g',
-            7 => 'h',
-            _ => panic!("Invalid square number"),
-        });
+/// One of the eight ranks, from the first (White's back rank) to the eighth
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Rank {
+    First,
+    Second,
+    Third,
+    Fourth,
+    Fifth,
+    Sixth,
+    Seventh,
+    Eighth,
+}
+impl Rank {
+    pub const ALL: [Self; 8] = [
+        Self::First,
+        Self::Second,
+        Self::Third,
+        Self::Fourth,
+        Self::Fifth,
+        Self::Sixth,
+        Self::Seventh,
+        Self::Eighth,
+    ];
+    /// The rank with the given index (0 for the first rank)
+    pub fn from_index(index: i32) -> Option<Self> {
+        usize::try_from(index)
+            .ok()
+            .and_then(|i| Self::ALL.get(i))
+            .copied()
+    }
+    pub const fn index(&self) -> i32 {
+        *self as i32
+    }
+    pub fn from_char(c: char) -> Option<Self> {
+        Self::from_index(c as i32 - '1' as i32)
+    }
+    pub fn as_char(&self) -> char {
+        (b'1' + *self as u8) as char
+    }
+    /// The same rank seen from the other side of the board (the first rank becomes the eighth)
+    pub fn mirror(&self) -> Self {
+        Self::ALL[7 - *self as usize]
+    }
+    /// The rank as seen by the given colour, so that `Rank::First` is the back rank of both sides
+    pub fn relative(&self, colour: Colour) -> Self {
+        match colour {
+            Colour::White => *self,
+            Colour::Black => self.mirror(),
+        }
+    }
+}
 
-        res += &format!("{}", n / 8 + 1);
+/// A square of the board
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Square(u8);
+impl Square {
+    pub const fn new(file: File, rank: Rank) -> Self {
+        Self(rank as u8 * 8 + file as u8)
+    }
+    /// The square with the given number (0 for a1, 63 for h8)
+    pub fn from_index(index: i32) -> Option<Self> {
+        (0..64).contains(&index).then_some(Self(index as u8))
+    }
+    // For square numbers that are known to be on the board, like those coming from a bitboard
+    pub(crate) fn from_index_unchecked(index: i32) -> Self {
+        debug_assert!((0..64).contains(&index));
+        Self(index as u8)
+    }
+    /// Number of the square, as used by bitboards and the lookup functions
+    pub const fn as_int(&self) -> i32 {
+        self.0 as i32
+    }
+    /// Bitboard with only this square set
+    pub const fn bitboard(&self) -> u64 {
+        1 << self.0
+    }
+    pub fn file(&self) -> File {
+        File::ALL[self.0 as usize % 8]
+    }
+    pub fn rank(&self) -> Rank {
+        Rank::ALL[self.0 as usize / 8]
+    }
+    /// The square the given number of files (to the right) and ranks (up) away, if it is still
+    /// on the board
+    pub fn offset(&self, files: i32, ranks: i32) -> Option<Self> {
+        let file = File::from_index(self.file().index() + files)?;
+        let rank = Rank::from_index(self.rank().index() + ranks)?;
+        Some(Self::new(file, rank))
+    }
+    /// The square one rank ahead from the point of view of the given colour
+    pub fn forward(&self, colour: Colour) -> Option<Self> {
+        match colour {
+            Colour::White => self.offset(0, 1),
+            Colour::Black => self.offset(0, -1),
+        }
+    }
+    /// The square one rank behind from the point of view of the given colour
+    pub fn backward(&self, colour: Colour) -> Option<Self> {
+        self.forward(colour.other())
+    }
+    /// The square on the same file, mirrored across the middle of the board (a1 becomes a8)
+    pub fn mirror(&self) -> Self {
+        Self(self.0 ^ 56)
+    }
+    /// The square as seen by the given colour, e.g. e1 for Black is e8
+    pub fn relative(&self, colour: Colour) -> Self {
+        match colour {
+            Colour::White => *self,
+            Colour::Black => self.mirror(),
+        }
+    }
+}
+impl Display for Square {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.file().as_char(), self.rank().as_char())
+    }
+}
+impl FromStr for Square {
+    type Err = ParseSquareError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next(), chars.next()) {
+            (Some(file), Some(rank), None) => File::from_char(file)
+                .zip(Rank::from_char(rank))
+                .map(|(file, rank)| Self::new(file, rank))
+                .ok_or_else(|| ParseSquareError(s.to_string())),
+            _ => Err(ParseSquareError(s.to_string())),
+        }
+    }
+}
+impl TryFrom<i32> for Square {
+    type Error = ParseSquareError;
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        Self::from_index(value).ok_or_else(|| ParseSquareError(value.to_string()))
+    }
+}
+impl From<Square> for i32 {
+    fn from(value: Square) -> Self {
+        value.as_int()
+    }
+}
 
-        Some(res)
-    } else {
-        None
+/// A string (or number) that does not name a square
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseSquareError(pub String);
+impl Display for ParseSquareError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid square '{}'", self.0)
     }
 }
+impl Error for ParseSquareError {}
+
+// Squares by name
+pub const a1: Square = Square::new(File::A, Rank::First);
+pub const b1: Square = Square::new(File::B, Rank::First);
+pub const c1: Square = Square::new(File::C, Rank::First);
+pub const d1: Square = Square::new(File::D, Rank::First);
+pub const e1: Square = Square::new(File::E, Rank::First);
+pub const f1: Square = Square::new(File::F, Rank::First);
+pub const g1: Square = Square::new(File::G, Rank::First);
+pub const h1: Square = Square::new(File::H, Rank::First);
+pub const a2: Square = Square::new(File::A, Rank::Second);
+pub const b2: Square = Square::new(File::B, Rank::Second);
+pub const c2: Square = Square::new(File::C, Rank::Second);
+pub const d2: Square = Square::new(File::D, Rank::Second);
+pub const e2: Square = Square::new(File::E, Rank::Second);
+pub const f2: Square = Square::new(File::F, Rank::Second);
+pub const g2: Square = Square::new(File::G, Rank::Second);
+pub const h2: Square = Square::new(File::H, Rank::Second);
+pub const a3: Square = Square::new(File::A, Rank::Third);
+pub const b3: Square = Square::new(File::B, Rank::Third);
+pub const c3: Square = Square::new(File::C, Rank::Third);
+pub const d3: Square = Square::new(File::D, Rank::Third);
+pub const e3: Square = Square::new(File::E, Rank::Third);
+pub const f3: Square = Square::new(File::F, Rank::Third);
+pub const g3: Square = Square::new(File::G, Rank::Third);
+pub const h3: Square = Square::new(File::H, Rank::Third);
+pub const a4: Square = Square::new(File::A, Rank::Fourth);
+pub const b4: Square = Square::new(File::B, Rank::Fourth);
+pub const c4: Square = Square::new(File::C, Rank::Fourth);
+pub const d4: Square = Square::new(File::D, Rank::Fourth);
+pub const e4: Square = Square::new(File::E, Rank::Fourth);
+pub const f4: Square = Square::new(File::F, Rank::Fourth);
+pub const g4: Square = Square::new(File::G, Rank::Fourth);
+pub const h4: Square = Square::new(File::H, Rank::Fourth);
+pub const a5: Square = Square::new(File::A, Rank::Fifth);
+pub const b5: Square = Square::new(File::B, Rank::Fifth);
+pub const c5: Square = Square::new(File::C, Rank::Fifth);
+pub const d5: Square = Square::new(File::D, Rank::Fifth);
+pub const e5: Square = Square::new(File::E, Rank::Fifth);
+pub const f5: Square = Square::new(File::F, Rank::Fifth);
+pub const g5: Square = Square::new(File::G, Rank::Fifth);
+pub const h5: Square = Square::new(File::H, Rank::Fifth);
+pub const a6: Square = Square::new(File::A, Rank::Sixth);
+pub const b6: Square = Square::new(File::B, Rank::Sixth);
+pub const c6: Square = Square::new(File::C, Rank::Sixth);
+pub const d6: Square = Square::new(File::D, Rank::Sixth);
+pub const e6: Square = Square::new(File::E, Rank::Sixth);
+pub const f6: Square = Square::new(File::F, Rank::Sixth);
+pub const g6: Square = Square::new(File::G, Rank::Sixth);
+pub const h6: Square = Square::new(File::H, Rank::Sixth);
+pub const a7: Square = Square::new(File::A, Rank::Seventh);
+pub const b7: Square = Square::new(File::B, Rank::Seventh);
+pub const c7: Square = Square::new(File::C, Rank::Seventh);
+pub const d7: Square = Square::new(File::D, Rank::Seventh);
+pub const e7: Square = Square::new(File::E, Rank::Seventh);
+pub const f7: Square = Square::new(File::F, Rank::Seventh);
+pub const g7: Square = Square::new(File::G, Rank::Seventh);
+pub const h7: Square = Square::new(File::H, Rank::Seventh);
+pub const a8: Square = Square::new(File::A, Rank::Eighth);
+pub const b8: Square = Square::new(File::B, Rank::Eighth);
+pub const c8: Square = Square::new(File::C, Rank::Eighth);
+pub const d8: Square = Square::new(File::D, Rank::Eighth);
+pub const e8: Square = Square::new(File::E, Rank::Eighth);
+pub const f8: Square = Square::new(File::F, Rank::Eighth);
+pub const g8: Square = Square::new(File::G, Rank::Eighth);
+pub const h8: Square = Square::new(File::H, Rank::Eighth);
@@ -0,0 +1,166 @@
+use std::{fmt::Display, str::FromStr};
+
+// Long algebraic notation as used by the UCI protocol
+use crate::{Board, ChessMove, Colour, MoveType, Piece, Side};
+
+use super::{
+    san::SanMove,
+    squares::{File, Rank, Square},
+    ChessError,
+};
+
+/// A move in the long algebraic notation used by UCI, e.g. `e2e4` or `e7e8q`.
+/// Castling is written as the king's move (`e1g1`), or as the king moving onto its own rook
+/// (`e1h1`) in Chess960.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct UciMove {
+    pub origin: Square,
+    pub dest: Square,
+    pub promotion: Option<Piece>,
+}
+impl UciMove {
+    pub fn new(origin: Square, dest: Square, promotion: Option<Piece>) -> Self {
+        Self {
+            origin,
+            dest,
+            promotion,
+        }
+    }
+
+    /// Finds the legal move on `board` that this move refers to
+    pub fn to_chess_move(&self, board: &Board) -> Result<ChessMove, ChessError> {
+        if let Some(m) = board
+            .generate_legal_moves()
+            .into_iter()
+            .find(|m| m.to_uci() == *self)
+        {
+            return Ok(m);
+        }
+
+        let colour = board.side_to_move();
+        match board.piece_on(self.origin) {
+            Some(piece) if piece.colour() == colour => {
+                // Castling is written as a king move of two files or onto its own rook
+                let castling = piece == Piece::King(colour)
+                    && self.origin.rank() == self.dest.rank()
+                    && ((self.dest.file().index() - self.origin.file().index()).abs() == 2
+                        || board.piece_on(self.dest) == Some(Piece::Rook(colour)));
+                if castling {
+                    return Err(board.castling_rejection(if self.dest > self.origin {
+                        Side::King
+                    } else {
+                        Side::Queen
+                    }));
+                }
+                Err(board.rejection(&self.to_string(), |m| {
+                    let promotion = match m.move_type {
+                        MoveType::Promotion(p) => Some(p),
+                        _ => None,
+                    };
+                    m.starting_square == Some(self.origin)
+                        && m.destination_square == Some(self.dest)
+                        && promotion == self.promotion
+                }))
+            }
+            _ => Err(ChessError::NoPieceOnSquare(self.origin)),
+        }
+    }
+
+    pub fn to_san(&self, board: &Board) -> Result<SanMove, ChessError> {
+        let m = self.to_chess_move(board)?;
+        Ok(board.get_san(&m.board))
+    }
+}
+
+impl FromStr for UciMove {
+    type Err = ChessError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let malformed = || ChessError::MalformedMove(s.to_string());
+        if !s.is_ascii() || !(4..=5).contains(&s.len()) {
+            return Err(malformed());
+        }
+        let origin: Square = s[0..2].parse().map_err(|_| malformed())?;
+        let dest: Square = s[2..4].parse().map_err(|_| malformed())?;
+
+        // The colour of the promoted piece is given by the rank it is promoted on
+        let colour = if dest.rank() == Rank::Eighth {
+            Colour::White
+        } else {
+            Colour::Black
+        };
+        let promotion = match &s[4..] {
+            "" => None,
+            "q" => Some(Piece::Queen(colour)),
+            "r" => Some(Piece::Rook(colour)),
+            "b" => Some(Piece::Bishop(colour)),
+            "n" => Some(Piece::Knight(colour)),
+            _ => return Err(malformed()),
+        };
+
+        Ok(Self::new(origin, dest, promotion))
+    }
+}
+impl Display for UciMove {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.origin, self.dest)?;
+        if let Some(piece) = self.promotion {
+            write!(f, "{}", piece.letter().to_lowercase())?;
+        }
+        Ok(())
+    }
+}
+impl From<ChessMove> for UciMove {
+    fn from(value: ChessMove) -> Self {
+        value.to_uci()
+    }
+}
+impl From<SanMove> for UciMove {
+    fn from(value: SanMove) -> Self {
+        Self::new(value.origin(), value.dest(), value.promotion())
+    }
+}
+
+impl ChessMove {
+    pub fn to_uci(&self) -> UciMove {
+        let base = self.base;
+        match base.move_type {
+            MoveType::Castling(side) => {
+                let origin = base
+                    .starting_square
+                    .expect("Castling should have a king square");
+                let rook = base
+                    .destination_square
+                    .expect("Castling should have a rook square");
+                // Chess960 castling is written as the king capturing its own rook, so that it
+                // can not be confused with a normal king move
+                let dest = if self.board.is_chess960() {
+                    rook
+                } else {
+                    let file = match side {
+                        Side::King => File::G,
+                        Side::Queen => File::C,
+                    };
+                    Square::new(file, origin.rank())
+                };
+                UciMove::new(origin, dest, None)
+            }
+            _ => UciMove::new(
+                base.starting_square
+                    .expect("Move should have a starting square"),
+                base.destination_square
+                    .expect("Move should have a destination square"),
+                match base.move_type {
+                    MoveType::Promotion(piece) => Some(piece),
+                    _ => None,
+                },
+            ),
+        }
+    }
+}
+
+impl Board {
+    /// Parses a move in UCI notation and finds the matching legal move
+    pub fn parse_uci_move(&self, m: &str) -> Result<ChessMove, ChessError> {
+        m.trim().parse::<UciMove>()?.to_chess_move(self)
+    }
+}
@@ -0,0 +1,132 @@
+// Zobrist keys used for hashing board positions. The keys are generated at compile time from a
+// fixed seed so that hashes are stable between runs (and between builds).
+use super::{squares::Square, Colour, Piece};
+use crate::piece_map_bitboards;
+
+pub struct ZobristKeys {
+    // Indexed by [colour][piece][square], see `piece_index` for the piece order
+    pub pieces: [[[u64; 64]; 6]; 2],
+    pub side: u64,
+    // Same order as `Board::castling_rights_as_arr`
+    pub castling: [u64; 4],
+    // Indexed by the file of the killer square
+    pub en_passent: [u64; 8],
+}
+
+// SplitMix64, see https://prng.di.unimi.it/splitmix64.c
+const fn next_key(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E3779B97F4A7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
+
+impl ZobristKeys {
+    const fn generate(seed: u64) -> Self {
+        let mut state = seed;
+        let mut pieces = [[[0; 64]; 6]; 2];
+        let mut colour = 0;
+        while colour < 2 {
+            let mut piece = 0;
+            while piece < 6 {
+                let mut square = 0;
+                while square < 64 {
+                    pieces[colour][piece][square] = next_key(&mut state);
+                    square += 1;
+                }
+                piece += 1;
+            }
+            colour += 1;
+        }
+
+        let side = next_key(&mut state);
+
+        let mut castling = [0; 4];
+        let mut i = 0;
+        while i < 4 {
+            castling[i] = next_key(&mut state);
+            i += 1;
+        }
+
+        let mut en_passent = [0; 8];
+        let mut i = 0;
+        while i < 8 {
+            en_passent[i] = next_key(&mut state);
+            i += 1;
+        }
+
+        Self {
+            pieces,
+            side,
+            castling,
+            en_passent,
+        }
+    }
+}
+
+pub static ZOBRIST_KEYS: ZobristKeys = ZobristKeys::generate(0x2545F4914F6CDD1D);
+
+// Piece bitboards in the order used by `ZobristKeys::pieces`
+fn piece_index(pieces: piece_map_bitboards) -> [u64; 6] {
+    [
+        pieces.pawns,
+        pieces.king,
+        pieces.queens,
+        pieces.bishops,
+        pieces.knights,
+        pieces.rooks,
+    ]
+}
+
+// Combined key of the given piece on every square of the bitboard
+fn bitboard_key(colour: Colour, piece: usize, mut bitboard: u64) -> u64 {
+    let mut res = 0;
+    while bitboard != 0 {
+        let square = bitboard.trailing_zeros() as usize;
+        res ^= ZOBRIST_KEYS.pieces[colour.as_int() as usize][piece][square];
+        bitboard &= bitboard - 1;
+    }
+    res
+}
+
+/// Combined key of every piece in the piece map
+pub fn pieces_key(colour: Colour, pieces: piece_map_bitboards) -> u64 {
+    piece_index(pieces)
+        .iter()
+        .enumerate()
+        .fold(0, |res, (piece, bitboard)| {
+            res ^ bitboard_key(colour, piece, *bitboard)
+        })
+}
+
+/// Key that turns the hash of `before` into the hash of `after`. Only the squares that differ
+/// between the two are visited, which is usually two to four squares for a single move.
+pub fn pieces_diff_key(
+    colour: Colour,
+    before: piece_map_bitboards,
+    after: piece_map_bitboards,
+) -> u64 {
+    let before = piece_index(before);
+    let after = piece_index(after);
+    (0..6).fold(0, |res, piece| {
+        res ^ bitboard_key(colour, piece, before[piece] ^ after[piece])
+    })
+}
+
+/// Key of a single piece on the given square
+pub fn piece_square_key(piece: Piece, square: i32) -> u64 {
+    let index = match piece {
+        Piece::Pawn(_) => 0,
+        Piece::King(_) => 1,
+        Piece::Queen(_) => 2,
+        Piece::Bishop(_) => 3,
+        Piece::Knight(_) => 4,
+        Piece::Rook(_) => 5,
+    };
+    ZOBRIST_KEYS.pieces[piece.colour().as_int() as usize][index][square as usize]
+}
+
+pub fn en_passent_key(killer_square: Square) -> u64 {
+    ZOBRIST_KEYS.en_passent[killer_square.file().index() as usize]
+}
@@ -0,0 +1,142 @@
+// Sanity checks for positions that do not come from playing legal moves
+use crate::{
+    board::Side,
+    utils::squares::{Rank, Square},
+    BitBoard, Board, Colour, Piece,
+};
+use std::{error::Error, fmt::Display};
+
+/// Reason for a position being rejected by `Board::validate`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PositionError {
+    /// More than one piece stands on the square
+    OverlappingPieces(Square),
+    /// The side does not have exactly one king (found count is attached)
+    KingCount(Colour, u32),
+    /// A pawn stands on the first or eighth rank
+    PawnOnBackRank(Square),
+    /// The king of the side that is not to move is in check
+    OpponentInCheck,
+    /// The castling right is not backed by the king and the rook on their back rank
+    InvalidCastling(Colour, Side),
+    /// The en passent square is not behind a pawn that has just made a double push
+    InvalidEnPassent(Square),
+}
+impl Display for PositionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid position: ")?;
+        match self {
+            Self::OverlappingPieces(s) => write!(f, "more than one piece on {s}"),
+            Self::KingCount(colour, n) => write!(f, "{colour:?} has {n} kings"),
+            Self::PawnOnBackRank(s) => write!(f, "pawn on {s}"),
+            Self::OpponentInCheck => write!(f, "the side not to move is in check"),
+            Self::InvalidCastling(colour, side) => {
+                write!(f, "{colour:?} can not castle {side:?}side")
+            }
+            Self::InvalidEnPassent(s) => write!(f, "invalid en passent square {s}"),
+        }
+    }
+}
+impl Error for PositionError {}
+
+impl Board {
+    /// Checks that the pieces do not overlap, each side has exactly one king, there are no pawns
+    /// on the back ranks, the side not to move is not in check and the castling rights and en
+    /// passent square agree with the pieces on the board. Returns the first problem found.
+    pub fn validate(&self) -> Result<(), PositionError> {
+        self.validate_pieces()?;
+        self.validate_castling()?;
+        self.validate_en_passent()?;
+
+        let other = self.side_to_move.other();
+        let king = self
+            .pieces(Piece::King(other))
+            .lsb()
+            .expect("Position should have a king");
+        if self.is_square_attacked(king, self.side_to_move) {
+            return Err(PositionError::OpponentInCheck);
+        }
+        Ok(())
+    }
+
+    fn validate_pieces(&self) -> Result<(), PositionError> {
+        let back_ranks = BitBoard::rank(Rank::First) | BitBoard::rank(Rank::Eighth);
+        let mut occupied = BitBoard::EMPTY;
+        for colour in [Colour::White, Colour::Black] {
+            for piece in [
+                Piece::Pawn(colour),
+                Piece::King(colour),
+                Piece::Queen(colour),
+                Piece::Bishop(colour),
+                Piece::Knight(colour),
+                Piece::Rook(colour),
+            ] {
+                let bitboard = self.pieces(piece);
+                if let Some(square) = (occupied & bitboard).lsb() {
+                    return Err(PositionError::OverlappingPieces(square));
+                }
+                occupied |= bitboard;
+            }
+
+            let kings = self.pieces(Piece::King(colour)).popcount();
+            if kings != 1 {
+                return Err(PositionError::KingCount(colour, kings));
+            }
+            if let Some(square) = (self.pieces(Piece::Pawn(colour)) & back_ranks).lsb() {
+                return Err(PositionError::PawnOnBackRank(square));
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_castling(&self) -> Result<(), PositionError> {
+        for colour in [Colour::White, Colour::Black] {
+            let side = self.base.get_side(colour);
+            let back_rank = match colour {
+                Colour::White => 0,
+                Colour::Black => 7,
+            };
+            let king = side.king.trailing_zeros() as i32;
+            for castling_side in [Side::King, Side::Queen] {
+                let Some(file) = self.castling_rook_file(colour, castling_side) else {
+                    continue;
+                };
+                // The rook has to be on the correct side of the king
+                let rook_side = match castling_side {
+                    Side::King => file > king % 8,
+                    Side::Queen => file < king % 8,
+                };
+                if king / 8 != back_rank
+                    || !rook_side
+                    || side.rooks & 1 << (back_rank * 8 + file) == 0
+                {
+                    return Err(PositionError::InvalidCastling(colour, castling_side));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_en_passent(&self) -> Result<(), PositionError> {
+        let Some(square) = self.killer_square else {
+            return Ok(());
+        };
+        // The pawn that has just moved two squares forward, passing over the killer square
+        let colour = self.side_to_move;
+        let pawns = self.base.get_side(colour.other()).pawns;
+        let occupancy = self.base.white_occupied | self.base.black_occupied;
+        match (
+            square.rank().relative(colour),
+            square.backward(colour),
+            square.forward(colour),
+        ) {
+            (Rank::Sixth, Some(pawn), Some(origin))
+                if pawns & pawn.bitboard() != 0
+                    && occupancy & (square.bitboard() | origin.bitboard()) == 0 =>
+            {
+                Ok(())
+            }
+            _ => Err(PositionError::InvalidEnPassent(square)),
+        }
+    }
+}
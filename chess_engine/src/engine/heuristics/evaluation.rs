@@ -5,6 +5,11 @@ use crate::engine::tree::Branch;
 use crate::engine::utils::eval::Eval;
 use crate::engine::utils::phase::GamePhase;
 
+// The piece lists of `Pieces` only hold squares that are on the board
+fn square_at(index: i32) -> Square {
+    Square::from_index(index).expect("Piece should be on the board")
+}
+
 struct EvalData {
     pub game_phase: i32,
     pub mg_mobility: [i32; 2],
@@ -90,6 +95,13 @@ impl Branch {
         res += SAFETY_TABLE[eval_data.attack_weight[0] as usize]
             - SAFETY_TABLE[eval_data.attack_weight[1] as usize];
 
+        // The side that is ahead can not win without mating material
+        if (res > 0 && !self.board.can_checkmate(Colour::White))
+            || (res < 0 && !self.board.can_checkmate(Colour::Black))
+        {
+            res /= NO_MATING_MATERIAL_DIV;
+        }
+
         Eval::Numeric(res as f32)
     }
 
@@ -117,35 +129,21 @@ impl Branch {
         black_pieces: &Pieces,
     ) -> i32 {
         let mut res = 0;
-        let (pieces, other_pieces) = if colour == Colour::White {
-            (white_pieces, black_pieces)
+        let pieces = if colour == Colour::White {
+            white_pieces
         } else {
-            (black_pieces, white_pieces)
+            black_pieces
         };
-        let occupancy = res_board.base.black_occupied + res_board.base.white_occupied;
-
+        let other = colour.other();
         for square in &pieces.pawns {
-            let attacked =
-                unsafe { wrap_extract_squares(pawnTargets(*square, colour.as_int(), occupancy)) };
-            for square in &other_pieces.knights {
-                if attacked.contains(square) {
-                    res += KNIGHT_ATTACK;
-                }
-            }
-            for square in &other_pieces.bishops {
-                if attacked.contains(square) {
-                    res += BISHIP_ATTACK;
-                }
-            }
-            for square in &other_pieces.rooks {
-                if attacked.contains(square) {
-                    res += ROOK_ATTACK;
-                }
-            }
-            for square in &other_pieces.queens {
-                if attacked.contains(square) {
-                    res += QUEEN_ATTACK;
-                }
+            let attacked = res_board.attacks_from(square_at(*square));
+            for (piece, bonus) in [
+                (Piece::Knight(other), KNIGHT_ATTACK),
+                (Piece::Bishop(other), BISHIP_ATTACK),
+                (Piece::Rook(other), ROOK_ATTACK),
+                (Piece::Queen(other), QUEEN_ATTACK),
+            ] {
+                res += (attacked & res_board.pieces(piece)).popcount() as i32 * bonus;
             }
         }
         res
@@ -221,35 +219,35 @@ impl Branch {
         let mut res = 0;
         if pieces.king[0] % 8 > 4 {
             // kingside
-            if pieces.pawns.contains(&f2) {
+            if pieces.pawns.contains(&f2.as_int()) {
                 res += SHIELD_1;
-            } else if pieces.pawns.contains(&f3) {
+            } else if pieces.pawns.contains(&f3.as_int()) {
                 res += SHIELD_2;
             }
-            if pieces.pawns.contains(&g2) {
+            if pieces.pawns.contains(&g2.as_int()) {
                 res += SHIELD_1;
-            } else if pieces.pawns.contains(&g3) {
+            } else if pieces.pawns.contains(&g3.as_int()) {
                 res += SHIELD_2;
             }
-            if pieces.pawns.contains(&h2) {
+            if pieces.pawns.contains(&h2.as_int()) {
                 res += SHIELD_1;
-            } else if pieces.pawns.contains(&h3) {
+            } else if pieces.pawns.contains(&h3.as_int()) {
                 res += SHIELD_2;
             }
         } else if pieces.king[0] % 8 < 3 {
-            if pieces.pawns.contains(&a2) {
+            if pieces.pawns.contains(&a2.as_int()) {
                 res += SHIELD_1;
-            } else if pieces.pawns.contains(&a3) {
+            } else if pieces.pawns.contains(&a3.as_int()) {
                 res += SHIELD_2;
             }
-            if pieces.pawns.contains(&b2) {
+            if pieces.pawns.contains(&b2.as_int()) {
                 res += SHIELD_1;
-            } else if pieces.pawns.contains(&b3) {
+            } else if pieces.pawns.contains(&b3.as_int()) {
                 res += SHIELD_2;
             }
-            if pieces.pawns.contains(&c2) {
+            if pieces.pawns.contains(&c2.as_int()) {
                 res += SHIELD_1;
-            } else if pieces.pawns.contains(&c3) {
+            } else if pieces.pawns.contains(&c3.as_int()) {
                 res += SHIELD_2;
             }
         }
@@ -261,35 +259,35 @@ impl Branch {
         let mut res = 0;
         if pieces.king[0] % 8 > 4 {
             // kingside
-            if pieces.pawns.contains(&f7) {
+            if pieces.pawns.contains(&f7.as_int()) {
                 res += SHIELD_1;
-            } else if pieces.pawns.contains(&f6) {
+            } else if pieces.pawns.contains(&f6.as_int()) {
                 res += SHIELD_2;
             }
-            if pieces.pawns.contains(&g7) {
+            if pieces.pawns.contains(&g7.as_int()) {
                 res += SHIELD_1;
-            } else if pieces.pawns.contains(&g6) {
+            } else if pieces.pawns.contains(&g6.as_int()) {
                 res += SHIELD_2;
             }
-            if pieces.pawns.contains(&h7) {
+            if pieces.pawns.contains(&h7.as_int()) {
                 res += SHIELD_1;
-            } else if pieces.pawns.contains(&h6) {
+            } else if pieces.pawns.contains(&h6.as_int()) {
                 res += SHIELD_2;
             }
         } else if pieces.king[0] % 8 < 3 {
-            if pieces.pawns.contains(&a7) {
+            if pieces.pawns.contains(&a7.as_int()) {
                 res += SHIELD_1;
-            } else if pieces.pawns.contains(&a6) {
+            } else if pieces.pawns.contains(&a6.as_int()) {
                 res += SHIELD_2;
             }
-            if pieces.pawns.contains(&b7) {
+            if pieces.pawns.contains(&b7.as_int()) {
                 res += SHIELD_1;
-            } else if pieces.pawns.contains(&b6) {
+            } else if pieces.pawns.contains(&b6.as_int()) {
                 res += SHIELD_2;
             }
-            if pieces.pawns.contains(&c7) {
+            if pieces.pawns.contains(&c7.as_int()) {
                 res += SHIELD_1;
-            } else if pieces.pawns.contains(&c6) {
+            } else if pieces.pawns.contains(&c6.as_int()) {
                 res += SHIELD_2;
             }
         }
@@ -298,24 +296,28 @@ impl Branch {
     }
 
     fn eval_blocked_pieces(white_pieces: &Pieces, black_pieces: &Pieces, eval_data: &mut EvalData) {
-        if (white_pieces.king[0] == f1 || white_pieces.king[0] == g1)
-            && (white_pieces.rooks.contains(&h1) || white_pieces.rooks.contains(&g1))
+        if (white_pieces.king[0] == f1.as_int() || white_pieces.king[0] == g1.as_int())
+            && (white_pieces.rooks.contains(&h1.as_int())
+                || white_pieces.rooks.contains(&g1.as_int()))
         {
             eval_data.blockages[0] -= P_KING_BLOCKS_ROOK;
         }
-        if (white_pieces.king[0] == c1 || white_pieces.king[0] == b1)
-            && (white_pieces.rooks.contains(&a1) || white_pieces.rooks.contains(&b1))
+        if (white_pieces.king[0] == c1.as_int() || white_pieces.king[0] == b1.as_int())
+            && (white_pieces.rooks.contains(&a1.as_int())
+                || white_pieces.rooks.contains(&b1.as_int()))
         {
             eval_data.blockages[0] -= P_KING_BLOCKS_ROOK;
         }
 
-        if (black_pieces.king[0] == f8 || black_pieces.king[0] == g8)
-            && (black_pieces.rooks.contains(&h8) || black_pieces.rooks.contains(&g8))
+        if (black_pieces.king[0] == f8.as_int() || black_pieces.king[0] == g8.as_int())
+            && (black_pieces.rooks.contains(&h8.as_int())
+                || black_pieces.rooks.contains(&g8.as_int()))
         {
             eval_data.blockages[1] -= P_KING_BLOCKS_ROOK;
         }
-        if (black_pieces.king[0] == c8 || black_pieces.king[0] == b8)
-            && (black_pieces.rooks.contains(&a8) || black_pieces.rooks.contains(&b8))
+        if (black_pieces.king[0] == c8.as_int() || black_pieces.king[0] == b8.as_int())
+            && (black_pieces.rooks.contains(&a8.as_int())
+                || black_pieces.rooks.contains(&b8.as_int()))
         {
             eval_data.blockages[0] -= P_KING_BLOCKS_ROOK;
         }
@@ -478,7 +480,6 @@ impl Branch {
     }
 
     fn eval_pieces(&self, white_pieces: &Pieces, black_pieces: &Pieces, eval_data: &mut EvalData) {
-        let occupancy = self.board.base.white_occupied + self.board.base.black_occupied;
         for square in &white_pieces.knights {
             self.eval_knight(
                 *square,
@@ -507,7 +508,6 @@ impl Branch {
                 white_pieces,
                 black_pieces,
                 black_pieces.king[0],
-                occupancy,
                 eval_data,
             );
         }
@@ -518,7 +518,6 @@ impl Branch {
                 white_pieces,
                 black_pieces,
                 white_pieces.king[0],
-                occupancy,
                 eval_data,
             );
         }
@@ -530,7 +529,6 @@ impl Branch {
                 white_pieces,
                 black_pieces,
                 black_pieces.king[0],
-                occupancy,
                 eval_data,
             );
         }
@@ -541,7 +539,6 @@ impl Branch {
                 black_pieces,
                 white_pieces,
                 white_pieces.king[0],
-                occupancy,
                 eval_data,
             );
         }
@@ -553,7 +550,6 @@ impl Branch {
                 white_pieces,
                 black_pieces,
                 black_pieces.king[0],
-                occupancy,
                 eval_data,
             );
         }
@@ -564,7 +560,6 @@ impl Branch {
                 white_pieces,
                 black_pieces,
                 white_pieces.king[0],
-                occupancy,
                 eval_data,
             );
         }
@@ -583,31 +578,39 @@ impl Branch {
         let mut mobility = 0;
         eval_data.game_phase += 1;
         if side == Colour::White {
-            match square {
+            match square_at(square) {
                 a8 => {
-                    if black_pieces.pawns.contains(&a7) || black_pieces.pawns.contains(&c7) {
+                    if black_pieces.pawns.contains(&a7.as_int())
+                        || black_pieces.pawns.contains(&c7.as_int())
+                    {
                         eval_data.blockages[0] -= P_KNIGHT_TRAPPED_A8;
                     }
                 }
                 h8 => {
-                    if black_pieces.pawns.contains(&h7) || black_pieces.pawns.contains(&f7) {
+                    if black_pieces.pawns.contains(&h7.as_int())
+                        || black_pieces.pawns.contains(&f7.as_int())
+                    {
                         eval_data.blockages[0] -= P_KNIGHT_TRAPPED_A8;
                     }
                 }
                 a7 => {
-                    if black_pieces.pawns.contains(&a6) && black_pieces.pawns.contains(&b7) {
+                    if black_pieces.pawns.contains(&a6.as_int())
+                        && black_pieces.pawns.contains(&b7.as_int())
+                    {
                         eval_data.blockages[0] -= P_KNIGHT_TRAPPED_A7;
                     }
                 }
                 h7 => {
-                    if black_pieces.pawns.contains(&h6) && black_pieces.pawns.contains(&g7) {
+                    if black_pieces.pawns.contains(&h6.as_int())
+                        && black_pieces.pawns.contains(&g7.as_int())
+                    {
                         eval_data.blockages[0] -= P_KNIGHT_TRAPPED_A7;
                     }
                 }
                 c3 => {
-                    if white_pieces.pawns.contains(&c2)
-                        && white_pieces.pawns.contains(&d4)
-                        && !white_pieces.pawns.contains(&e4)
+                    if white_pieces.pawns.contains(&c2.as_int())
+                        && white_pieces.pawns.contains(&d4.as_int())
+                        && !white_pieces.pawns.contains(&e4.as_int())
                     {
                         eval_data.blockages[0] -= P_C3_KNIGHT;
                     }
@@ -617,31 +620,39 @@ impl Branch {
 
             eval_data.material_adjustement[0] += KNIGHT_ADJ[white_pieces.knights.len()];
         } else {
-            match square {
+            match square_at(square) {
                 a1 => {
-                    if white_pieces.pawns.contains(&a2) || white_pieces.pawns.contains(&c2) {
+                    if white_pieces.pawns.contains(&a2.as_int())
+                        || white_pieces.pawns.contains(&c2.as_int())
+                    {
                         eval_data.blockages[1] -= P_KNIGHT_TRAPPED_A8;
                     }
                 }
                 h1 => {
-                    if white_pieces.pawns.contains(&h2) || white_pieces.pawns.contains(&f2) {
+                    if white_pieces.pawns.contains(&h2.as_int())
+                        || white_pieces.pawns.contains(&f2.as_int())
+                    {
                         eval_data.blockages[1] -= P_KNIGHT_TRAPPED_A8;
                     }
                 }
                 a2 => {
-                    if white_pieces.pawns.contains(&a3) && white_pieces.pawns.contains(&b2) {
+                    if white_pieces.pawns.contains(&a3.as_int())
+                        && white_pieces.pawns.contains(&b2.as_int())
+                    {
                         eval_data.blockages[1] -= P_KNIGHT_TRAPPED_A7;
                     }
                 }
                 h2 => {
-                    if white_pieces.pawns.contains(&h3) && black_pieces.pawns.contains(&g2) {
+                    if white_pieces.pawns.contains(&h3.as_int())
+                        && black_pieces.pawns.contains(&g2.as_int())
+                    {
                         eval_data.blockages[1] -= P_KNIGHT_TRAPPED_A7;
                     }
                 }
                 c6 => {
-                    if black_pieces.pawns.contains(&c7)
-                        && black_pieces.pawns.contains(&d5)
-                        && !black_pieces.pawns.contains(&e5)
+                    if black_pieces.pawns.contains(&c7.as_int())
+                        && black_pieces.pawns.contains(&d5.as_int())
+                        && !black_pieces.pawns.contains(&e5.as_int())
                     {
                         eval_data.blockages[1] -= P_C3_KNIGHT;
                     }
@@ -651,12 +662,10 @@ impl Branch {
             eval_data.material_adjustement[1] += KNIGHT_ADJ[black_pieces.knights.len()];
         }
 
-        unsafe {
-            for s in wrap_extract_squares(knightTargets(square)) {
-                mobility += 1;
-                if SQ_NEAR_KING[to_index(side)][opponent_king_pos as usize][s as usize] == 1 {
-                    attacks += 1;
-                }
+        for s in self.board.attacks_from(square_at(square)) {
+            mobility += 1;
+            if SQ_NEAR_KING[to_index(side)][opponent_king_pos as usize][s.as_int() as usize] == 1 {
+                attacks += 1;
             }
         }
 
@@ -676,7 +685,6 @@ impl Branch {
         white_pieces: &Pieces,
         black_pieces: &Pieces,
         opponent_king_pos: i32,
-        occupancy: u64,
         eval_data: &mut EvalData,
     ) {
         let mut mobility = 0;
@@ -684,88 +692,88 @@ impl Branch {
         eval_data.game_phase += 1;
 
         if side == Colour::White {
-            match square {
+            match square_at(square) {
                 a7 => {
-                    if black_pieces.pawns.contains(&b6) {
+                    if black_pieces.pawns.contains(&b6.as_int()) {
                         eval_data.blockages[0] -= P_BISHOP_TRAPPED_A7;
                     }
                 }
                 h7 => {
-                    if black_pieces.pawns.contains(&g6) {
+                    if black_pieces.pawns.contains(&g6.as_int()) {
                         eval_data.blockages[0] -= P_BISHOP_TRAPPED_A7;
                     }
                 }
                 b8 => {
-                    if black_pieces.pawns.contains(&c7) {
+                    if black_pieces.pawns.contains(&c7.as_int()) {
                         eval_data.blockages[0] -= P_BISHOP_TRAPPED_A7;
                     }
                 }
                 g8 => {
-                    if black_pieces.pawns.contains(&f7) {
+                    if black_pieces.pawns.contains(&f7.as_int()) {
                         eval_data.blockages[0] -= P_BISHOP_TRAPPED_A7;
                     }
                 }
                 a6 => {
-                    if black_pieces.pawns.contains(&b5) {
+                    if black_pieces.pawns.contains(&b5.as_int()) {
                         eval_data.blockages[0] -= P_BISHOP_TRAPPED_A6;
                     }
                 }
                 h6 => {
-                    if black_pieces.pawns.contains(&g5) {
+                    if black_pieces.pawns.contains(&g5.as_int()) {
                         eval_data.blockages[0] -= P_BISHOP_TRAPPED_A7;
                     }
                 }
                 f1 => {
-                    if white_pieces.king[0] == g1 {
+                    if white_pieces.king[0] == g1.as_int() {
                         eval_data.positional_themes[0] += RETURNING_BISHOP;
                     }
                 }
                 c1 => {
-                    if white_pieces.king[0] == b1 {
+                    if white_pieces.king[0] == b1.as_int() {
                         eval_data.positional_themes[0] += RETURNING_BISHOP;
                     }
                 }
                 _ => {}
             }
         } else {
-            match square {
+            match square_at(square) {
                 a2 => {
-                    if white_pieces.pawns.contains(&b3) {
+                    if white_pieces.pawns.contains(&b3.as_int()) {
                         eval_data.blockages[1] -= P_BISHOP_TRAPPED_A7;
                     }
                 }
                 h2 => {
-                    if white_pieces.pawns.contains(&g3) {
+                    if white_pieces.pawns.contains(&g3.as_int()) {
                         eval_data.blockages[1] -= P_BISHOP_TRAPPED_A7;
                     }
                 }
                 b1 => {
-                    if white_pieces.pawns.contains(&c2) {
+                    if white_pieces.pawns.contains(&c2.as_int()) {
                         eval_data.blockages[1] -= P_BISHOP_TRAPPED_A7;
                     }
                 }
                 g1 => {
-                    if white_pieces.pawns.contains(&f2) {
+                    if white_pieces.pawns.contains(&f2.as_int()) {
                         eval_data.blockages[1] -= P_BISHOP_TRAPPED_A7;
                     }
                 }
                 a3 => {
-                    if white_pieces.pawns.contains(&b4) {
+                    if white_pieces.pawns.contains(&b4.as_int()) {
                         eval_data.blockages[1] -= P_BISHOP_TRAPPED_A6;
                     }
                 }
                 h3 => {
-                    if black_pieces.pawns.contains(&g4) {
+                    if black_pieces.pawns.contains(&g4.as_int()) {
                         eval_data.blockages[1] -= P_BISHOP_TRAPPED_A7;
                     }
                 }
                 f8 => {
-                    if white_pieces.king[0] == g8 {
+                    if white_pieces.king[0] == g8.as_int() {
                         eval_data.positional_themes[1] += RETURNING_BISHOP;
                     }
                 }
                 c8 => {
-                    if white_pieces.king[0] == b8 {
+                    if white_pieces.king[0] == b8.as_int() {
                         eval_data.positional_themes[1] += RETURNING_BISHOP;
                     }
                 }
@@ -773,12 +781,10 @@ impl Branch {
             }
         }
 
-        unsafe {
-            for s in wrap_extract_squares(bishopTargets(square, occupancy)) {
-                mobility += 1;
-                if SQ_NEAR_KING[to_index(side)][opponent_king_pos as usize][s as usize] == 1 {
-                    attacks += 1;
-                }
+        for s in self.board.attacks_from(square_at(square)) {
+            mobility += 1;
+            if SQ_NEAR_KING[to_index(side)][opponent_king_pos as usize][s.as_int() as usize] == 1 {
+                attacks += 1;
             }
         }
 
@@ -798,7 +804,6 @@ impl Branch {
         side_pieces: &Pieces,
         other_pieces: &Pieces,
         opponent_king_pos: i32,
-        occupancy: u64,
         eval_data: &mut EvalData,
     ) {
         let mut mobility = 0;
@@ -837,12 +842,10 @@ impl Branch {
             }
         }
 
-        unsafe {
-            for s in wrap_extract_squares(rookTargets(square, occupancy)) {
-                mobility += 1;
-                if SQ_NEAR_KING[to_index(side)][opponent_king_pos as usize][s as usize] == 1 {
-                    attacks += 1;
-                }
+        for s in self.board.attacks_from(square_at(square)) {
+            mobility += 1;
+            if SQ_NEAR_KING[to_index(side)][opponent_king_pos as usize][s.as_int() as usize] == 1 {
+                attacks += 1;
             }
         }
 
@@ -862,47 +865,44 @@ impl Branch {
         white_pieces: &Pieces,
         black_pieces: &Pieces,
         opponent_king_pos: i32,
-        occupancy: u64,
         eval_data: &mut EvalData,
     ) {
         let mut mobility = 0;
         let mut attacks = 0;
         eval_data.game_phase += 4;
 
-        if side == Colour::White && square > h2 {
-            if white_pieces.knights.contains(&b1) {
+        if side == Colour::White && square > h2.as_int() {
+            if white_pieces.knights.contains(&b1.as_int()) {
                 eval_data.positional_themes[0] -= P_QUEEN_DEVELOPED_EARLY;
             }
-            if white_pieces.knights.contains(&g1) {
+            if white_pieces.knights.contains(&g1.as_int()) {
                 eval_data.positional_themes[0] -= P_QUEEN_DEVELOPED_EARLY;
             }
-            if white_pieces.bishops.contains(&c1) {
+            if white_pieces.bishops.contains(&c1.as_int()) {
                 eval_data.positional_themes[0] -= P_QUEEN_DEVELOPED_EARLY;
             }
-            if white_pieces.bishops.contains(&f1) {
+            if white_pieces.bishops.contains(&f1.as_int()) {
                 eval_data.positional_themes[0] -= P_QUEEN_DEVELOPED_EARLY;
             }
-        } else if side == Colour::Black && square < a7 {
-            if black_pieces.knights.contains(&b8) {
+        } else if side == Colour::Black && square < a7.as_int() {
+            if black_pieces.knights.contains(&b8.as_int()) {
                 eval_data.positional_themes[1] -= P_QUEEN_DEVELOPED_EARLY;
             }
-            if white_pieces.knights.contains(&g8) {
+            if white_pieces.knights.contains(&g8.as_int()) {
                 eval_data.positional_themes[1] -= P_QUEEN_DEVELOPED_EARLY;
             }
-            if white_pieces.bishops.contains(&c8) {
+            if white_pieces.bishops.contains(&c8.as_int()) {
                 eval_data.positional_themes[1] -= P_QUEEN_DEVELOPED_EARLY;
             }
-            if white_pieces.bishops.contains(&f8) {
+            if white_pieces.bishops.contains(&f8.as_int()) {
                 eval_data.positional_themes[1] -= P_QUEEN_DEVELOPED_EARLY;
             }
         }
 
-        unsafe {
-            for s in wrap_extract_squares(queenTargets(square, occupancy)) {
-                mobility += 1;
-                if SQ_NEAR_KING[to_index(side)][opponent_king_pos as usize][s as usize] == 1 {
-                    attacks += 1;
-                }
+        for s in self.board.attacks_from(square_at(square)) {
+            mobility += 1;
+            if SQ_NEAR_KING[to_index(side)][opponent_king_pos as usize][s.as_int() as usize] == 1 {
+                attacks += 1;
             }
         }
 
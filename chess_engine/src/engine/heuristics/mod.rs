@@ -3,14 +3,37 @@ use log::debug;
 
 mod evaluation;
 pub mod modifiers;
-use super::{tree::Branch, utils::eval::Eval};
+use super::{
+    transposition::{Bound, TranspositionTable},
+    tree::Branch,
+    utils::eval::Eval,
+};
 use modifiers::{BISHOP_VAL, KING_VAL, KNIGHT_VAL, PAWN_VAL, QUEEN_VAL, ROOK_VAL};
 mod priority;
+mod quiescence;
 
 impl Branch {
-    pub fn eval_position(&mut self, mobility: usize, depth: usize) -> Eval {
+    pub fn eval_position(
+        &mut self,
+        mobility: usize,
+        depth: usize,
+        table: Option<&TranspositionTable>,
+    ) -> Eval {
         match self.board.get_unchecked_game_state(mobility) {
-            GameState::Ongoing => self.eval_heuristic(),
+            GameState::Ongoing => {
+                let Some(table) = table else {
+                    return self.eval_quiescence(depth);
+                };
+                // Transpositions, and positions searched for earlier moves, have been evaluated
+                // before (possibly deeper than the heuristic would)
+                let key = self.board.hash();
+                if let Some(entry) = table.probe(key, depth).filter(|e| e.bound == Bound::Exact) {
+                    return entry.eval;
+                }
+                let eval = self.eval_quiescence(depth);
+                table.store(key, 0, Bound::Exact, eval, None, depth);
+                eval
+            }
             GameState::Finished(state) => {
                 self.game_over = true;
                 match state {
@@ -8,11 +8,12 @@ pub const ROOK_ATTACK: i32 = 50;
 pub const QUEEN_ATTACK: i32 = 60;
 pub const ACTIVE_ATTACK_MOD: i32 = 3;
 pub const PASSIVE_ATTACK_MOD: i32 = 1;
+pub const NO_MATING_MATERIAL_DIV: i32 = 8;
 
-const fn CONVERT_TO_USIZE(input: [i32; 64]) -> [usize; 64] {
+const fn CONVERT_TO_USIZE(input: [Square; 64]) -> [usize; 64] {
     let mut res = [0; 64];
     const_for!(i in 0..64 => {
-        res[i] = input[i] as usize;
+        res[i] = input[i].as_int() as usize;
     });
 
     res
@@ -20,7 +21,7 @@ const fn CONVERT_TO_USIZE(input: [i32; 64]) -> [usize; 64] {
 
 // constants based on the CPW stdafx library
 
-const INDEX_WHITE_INT: [i32; 64] = [
+const INDEX_WHITE_INT: [Square; 64] = [
     a8, b8, c8, d8, e8, f8, g8, h8, //
     a7, b7, c7, d7, e7, f7, g7, h7, //
     a6, b6, c6, d6, e6, f6, g6, h6, //
@@ -33,7 +34,7 @@ const INDEX_WHITE_INT: [i32; 64] = [
 
 const INDEX_WHITE: [usize; 64] = CONVERT_TO_USIZE(INDEX_WHITE_INT);
 
-const INDEX_BLACK_INT: [i32; 64] = [
+const INDEX_BLACK_INT: [Square; 64] = [
     a1, b1, c1, d1, e1, f1, g1, h1, //
     a2, b2, c2, d2, e2, f2, g2, h2, //
     a3, b3, c3, d3, e3, f3, g3, h3, //
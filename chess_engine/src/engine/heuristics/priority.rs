@@ -1,7 +1,6 @@
 use chess_backend::*;
 use log::debug;
 
-use super::modifiers::{BISHOP_VAL, KNIGHT_VAL, PAWN_VAL, QUEEN_VAL, ROOK_VAL};
 use crate::engine::{tree::Branch, utils::eval::Eval};
 
 // priority modifiers
@@ -14,6 +13,7 @@ impl Branch {
     // Preliminary evaluation to find how promising the move is
     pub fn calc_priority(
         orig_board: Board,
+        res_move: Move,
         res_board: Board,
         depth: usize,
         heuristic: Eval,
@@ -27,7 +27,7 @@ impl Branch {
 
             res -= depth as f32 * DEPTH_PENALTY;
 
-            if let Some(capture_val) = Self::is_capture(orig_board, res_board) {
+            if let Some(capture_val) = Self::capture_value(orig_board, res_move) {
                 res += capture_val * (CAPTURE_BONUS - DEPTH_PENALTY * depth as f32);
             }
 
@@ -37,21 +37,11 @@ impl Branch {
         }
     }
 
-    fn is_capture(orig_board: Board, res_board: Board) -> Option<f32> {
-        let side = res_board.side_to_move();
-        let orig_side = orig_board.base.get_side(side);
-        let res_side = res_board.base.get_side(side);
-
-        if orig_side.pawns != res_side.pawns {
-            Some(PAWN_VAL as f32)
-        } else if orig_side.queens != res_side.queens {
-            Some(QUEEN_VAL as f32)
-        } else if orig_side.rooks != res_side.rooks {
-            Some(ROOK_VAL as f32)
-        } else if orig_side.bishops != res_side.bishops {
-            Some(BISHOP_VAL as f32)
-        } else if orig_side.knights != res_side.knights {
-            Some(KNIGHT_VAL as f32)
+    // Material won by a capture after all recaptures (in centipawns), so that losing captures
+    // such as a queen taking a defended pawn are not prioritised
+    fn capture_value(orig_board: Board, res_move: Move) -> Option<f32> {
+        if res_move.is_capture() {
+            Some(orig_board.see(res_move) as f32)
         } else {
             None
         }
@@ -0,0 +1,131 @@
+use chess_backend::{see_value, Board, Colour, FinishedState, GameState, Move, Piece};
+
+use crate::engine::{tree::Branch, utils::eval::Eval};
+
+// A capture is skipped if even winning the captured piece (and this margin on top) can not bring
+// the evaluation back up to the best one found so far
+const DELTA_MARGIN: f32 = 200.;
+
+// Captures shrink the material on the board, but quiet check evasions and promotions do not, so
+// sequences of checks are only ended by this limit
+const MAX_QUIESCENCE_PLY: usize = 16;
+
+impl Branch {
+    /// Heuristic evaluation of a leaf once the captures on the board have been played out, so that
+    /// a position in the middle of an exchange is not scored as if the exchange was over. The
+    /// side to move may stand pat (keep the static evaluation) or make a capture that does not
+    /// lose material, while a side in check has to search all of its evasions. `depth` is the
+    /// ply of the position from the root, for scoring mates found along the way.
+    pub fn eval_quiescence(&self, depth: usize) -> Eval {
+        let mut board = self.board;
+        quiesce(&mut board, Eval::NegInfinity, Eval::Infinity, depth, 0)
+    }
+}
+
+fn quiesce(board: &mut Board, mut alpha: Eval, mut beta: Eval, depth: usize, ply: usize) -> Eval {
+    let moves = board.generate_moves();
+    match board.get_unchecked_game_state(moves.len()) {
+        GameState::Ongoing => {}
+        GameState::Finished(FinishedState::Win(colour, _)) => return Eval::Mate(depth, colour),
+        GameState::Finished(FinishedState::Draw(_)) => return Eval::Numeric(0.),
+    }
+
+    let maximize = board.side_to_move() == Colour::White;
+    let in_check = board.is_check();
+    let (mut best, moves) = if in_check && ply < MAX_QUIESCENCE_PLY {
+        // There is no standing pat in check, every evasion is searched
+        let best = if maximize {
+            Eval::NegInfinity
+        } else {
+            Eval::Infinity
+        };
+        (best, moves)
+    } else {
+        let stand_pat = Branch::from(*board).eval_heuristic();
+        if ply >= MAX_QUIESCENCE_PLY {
+            return stand_pat;
+        }
+        if maximize {
+            if stand_pat >= beta {
+                return stand_pat;
+            }
+            alpha = alpha.max(stand_pat);
+        } else {
+            if stand_pat <= alpha {
+                return stand_pat;
+            }
+            beta = beta.min(stand_pat);
+        }
+        (
+            stand_pat,
+            captures(board, moves, stand_pat, alpha, beta, maximize),
+        )
+    };
+
+    for m in moves {
+        let undo = board.make_move(m);
+        let eval = quiesce(board, alpha, beta, depth + 1, ply + 1);
+        board.unmake_move(m, undo);
+
+        if maximize {
+            best = best.max(eval);
+            alpha = alpha.max(eval);
+        } else {
+            best = best.min(eval);
+            beta = beta.min(eval);
+        }
+        if alpha >= beta {
+            break;
+        }
+    }
+    best
+}
+
+// Captures (and promotions) worth searching, ordered by their static exchange evaluation with the
+// most valuable victims first among captures that win the same material
+fn captures(
+    board: &Board,
+    moves: Vec<Move>,
+    stand_pat: Eval,
+    alpha: Eval,
+    beta: Eval,
+    maximize: bool,
+) -> Vec<Move> {
+    let Eval::Numeric(stand_pat) = stand_pat else {
+        return Vec::new();
+    };
+    let mut captures: Vec<(Move, i32)> = moves
+        .into_iter()
+        .filter(|m| m.is_capture() || m.promotion().is_some())
+        .map(|m| (m, board.see(m)))
+        // Losing captures are left to the main search
+        .filter(|&(_, see)| see >= 0)
+        // Delta pruning
+        .filter(|&(m, _)| {
+            let gain = material_gain(board, m) as f32 + DELTA_MARGIN;
+            if maximize {
+                Eval::Numeric(stand_pat + gain) > alpha
+            } else {
+                Eval::Numeric(stand_pat - gain) < beta
+            }
+        })
+        .collect();
+    captures.sort_by_key(|&(m, see)| {
+        let victim = board.piece_on(m.dest()).map_or(0, see_value);
+        (-see, -victim)
+    });
+    captures.into_iter().map(|(m, _)| m).collect()
+}
+
+// Most material a move can win, i.e. the captured piece and what a pawn is promoted to
+fn material_gain(board: &Board, m: Move) -> i32 {
+    let captured = if m.is_en_passent() {
+        see_value(Piece::Pawn(board.side_to_move().other()))
+    } else {
+        board.piece_on(m.dest()).map_or(0, see_value)
+    };
+    let promoted = m.promotion().map_or(0, |p| {
+        see_value(p) - see_value(Piece::Pawn(board.side_to_move()))
+    });
+    captured + promoted
+}
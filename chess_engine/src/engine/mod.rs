@@ -1,8 +1,9 @@
 use std::{
     error::Error,
-    io,
+    io::{self, BufRead, Write},
     slice::Iter,
     sync::{
+        atomic::{AtomicBool, Ordering},
         mpsc::{channel, Receiver, Sender},
         Arc, Mutex, RwLock,
     },
@@ -16,19 +17,28 @@ use opening_book::search_manual;
 use sqlite::{self, Connection};
 const DB_PATH: &str = "openings.db";
 
-use chess_backend::{Board, Colour, GameState, SanMove};
+use chess_backend::{Board, Colour, Game, GameState, SanMove};
+use pvs::{Pvs, SearchStats};
 use threadpool::ThreadPool;
+use transposition::TranspositionTable;
 use tree::Branch;
+use uci::UciHandler;
 use utils::{
     error::EngineError,
     eval::Eval,
     phase::{self, GamePhase},
 };
+use xboard::XBoardHandler;
 
 pub mod heuristics;
 mod opening_book;
+pub mod pvs;
+pub mod search;
+pub mod transposition;
 pub mod tree;
+pub mod uci;
 pub mod utils;
+pub mod xboard;
 
 fn get_db_connection() -> Connection {
     Connection::open(DB_PATH).expect("Failed to connect to opening database")
@@ -42,14 +52,28 @@ pub enum Player {
 
 const PRELIMINARY_TIME_SHARE: f32 = 0.5;
 
+/// How the engine looks for a move once the opening book has run out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SearchMode {
+    /// Grows a tree of evaluated positions on all workers, expanding the most promising ones first
+    #[default]
+    BestFirst,
+    /// Iterative deepening principal variation search on a single thread (see `pvs::Pvs`), which
+    /// gives the same result for the same depth
+    Pvs,
+}
+
 pub struct EngineController {
     white: Player,
     black: Player,
     time_limit: Duration,
-    board: Board,
+    game: Game,
     n_workers: usize,
     db_conn: Connection,
     phase: Option<GamePhase>,
+    // Kept between moves, so that each search starts with what the previous ones found
+    table: Arc<TranspositionTable>,
+    search_mode: SearchMode,
 }
 impl EngineController {
     fn init() {
@@ -67,28 +91,35 @@ impl EngineController {
             white,
             black,
             time_limit,
-            board,
+            game: Game::from(board),
             n_workers,
             db_conn: get_db_connection(),
             phase,
+            table: Arc::new(TranspositionTable::default()),
+            search_mode: SearchMode::default(),
         }
     }
     pub fn pick_move(&mut self, time_limit: Duration) {
-        let mut engine = Engine::new(self.board, self.n_workers, self.phase);
-        (self.board, self.phase) = engine.begin_search(
+        let mut engine = Engine::new(self.game.board(), self.n_workers, self.phase);
+        engine.set_table(Arc::clone(&self.table));
+        engine.set_search_mode(self.search_mode);
+        engine.set_history(self.game.history().to_vec());
+        let (board, phase, _eval) = engine.begin_search(
             time_limit.mul_f32(PRELIMINARY_TIME_SHARE),
             time_limit.mul_f32(1. - PRELIMINARY_TIME_SHARE),
             self.phase,
-            &self.db_conn,
+            Some(&self.db_conn),
         );
+        self.phase = phase;
+        self.game.push(board);
     }
 
     pub fn show_board(&self) {
-        println!("{}", self.board);
+        println!("{}", self.game.board());
     }
 
     pub fn get_game_state(&self) -> GameState {
-        self.board.get_game_state()
+        self.game.get_game_state()
     }
 
     pub fn is_over(&self) -> bool {
@@ -105,29 +136,37 @@ impl EngineController {
                 self.pick_move(self.time_limit);
                 Ok(())
             }
-            Player::Manual => {
+            Player::Manual => loop {
                 let m = io::stdin().lines().next().unwrap().unwrap();
-                self.manual_move(
-                    SanMove::from_string(m.trim(), self.board).expect("Failed to parse san move"),
-                )
-            }
+                match SanMove::from_string(m.trim(), self.game.board()) {
+                    Ok(san_m) => return self.manual_move(san_m),
+                    // Input that can not be played is reported, and the move asked for again
+                    Err(e) => println!("{e}"),
+                }
+            },
         }
     }
 
     pub fn manual_move(&mut self, m: impl Into<SanMove>) -> Result<(), EngineError> {
         let san_m: SanMove = m.into();
-        if let Ok(_) = self.board.make_san_move(san_m) {
-            if let Some(GamePhase::Opening(id)) = self.phase {
-                if let Some(phase) = search_manual(&self.db_conn, id, san_m.into()) {
-                    self.phase = Some(phase);
-                } else {
-                    self.phase = Some(GamePhase::MiddleGame)
-                }
+        self.game.play_san(san_m)?;
+        if let Some(GamePhase::Opening(id)) = self.phase {
+            if let Some(phase) = search_manual(&self.db_conn, id, san_m.into()) {
+                self.phase = Some(phase);
+            } else {
+                self.phase = Some(GamePhase::MiddleGame)
             }
-            Ok(())
-        } else {
-            Err(EngineError::InputError)
         }
+        Ok(())
+    }
+
+    /// Replaces the transposition table with an empty one of the given size in megabytes
+    pub fn set_table_size(&mut self, megabytes: usize) {
+        self.table = Arc::new(TranspositionTable::new(megabytes));
+    }
+
+    pub fn set_search_mode(&mut self, mode: SearchMode) {
+        self.search_mode = mode;
     }
 
     pub fn set_white(&mut self, player: Player) {
@@ -140,14 +179,14 @@ impl EngineController {
     pub fn play(&mut self) -> Result<(), EngineError> {
         Self::init();
 
-        println!("{}", self.board);
-        while let GameState::Ongoing = self.board.get_game_state() {
-            let colour = self.board.side_to_move();
+        println!("{}", self.game.board());
+        while let GameState::Ongoing = self.game.get_game_state() {
+            let colour = self.game.board().side_to_move();
             match colour {
                 Colour::White => self.request_move(self.white)?,
                 Colour::Black => self.request_move(self.black)?,
             }
-            println!("{}", self.board);
+            println!("{}", self.game.board());
         }
 
         Ok(())
@@ -159,14 +198,59 @@ impl Default for EngineController {
             white: Player::Engine,
             black: Player::Engine,
             time_limit: Duration::from_secs(2),
-            board: Board::default(),
+            game: Game::default(),
             n_workers: num_cpus::get(),
             db_conn: get_db_connection(),
             phase: Some(GamePhase::Opening(1)),
+            table: Arc::new(TranspositionTable::default()),
+            search_mode: SearchMode::default(),
         }
     }
 }
 
+/// Runs the engine for a GUI on stdin and stdout until it is told to quit or the input ends. The
+/// protocol is XBoard if the first command is `xboard`, and UCI otherwise.
+pub fn run_protocol() -> io::Result<()> {
+    let mut lines = io::stdin().lock().lines();
+    let Some(first) = lines.next().transpose()? else {
+        return Ok(());
+    };
+    if first.trim() == "xboard" {
+        let mut handler = XBoardHandler::new(io::stdout());
+        serve(|line| handler.handle(line), &first, lines)
+    } else {
+        let mut handler = UciHandler::new(io::stdout());
+        serve(|line| handler.handle(line), &first, lines)
+    }
+}
+
+fn serve(
+    mut handle: impl FnMut(&str) -> bool,
+    first: &str,
+    lines: impl Iterator<Item = io::Result<String>>,
+) -> io::Result<()> {
+    if !handle(first) {
+        return Ok(());
+    }
+    for line in lines {
+        if !handle(&line?) {
+            return Ok(());
+        }
+    }
+    // Waits for a running search to finish, as if quit had been sent
+    handle("quit");
+    Ok(())
+}
+
+// Sends a line to the GUI, shared by the protocol front ends whose searches reply from their
+// own thread
+fn send<W: Write>(out: &Mutex<W>, line: &str) {
+    let mut out = out.lock().expect("Failed to lock output");
+    writeln!(out, "{line}")
+        .and_then(|_| out.flush())
+        .expect("Failed to write to output");
+}
+
 #[derive(Debug, Clone)]
 enum WorkerType {
     // Contains the base node that this worker covers
@@ -187,6 +271,16 @@ struct Engine {
     n_workers: usize,
     sender_model: Sender<WorkerRes>,
     receiver: Receiver<WorkerRes>,
+    // Set from outside the search to demand an answer before the time limit is reached
+    stop: Arc<AtomicBool>,
+    table: Option<Arc<TranspositionTable>>,
+    mode: SearchMode,
+    // Only followed by the PVS search
+    max_depth: Option<usize>,
+    max_nodes: Option<u64>,
+    stats: Option<SearchStats>,
+    // Positions of the game before the searched one, for detecting repetitions in the PVS search
+    history: Vec<u64>,
 }
 impl Engine {
     pub fn new(board: Board, n_workers: usize, phase: Option<GamePhase>) -> Self {
@@ -197,21 +291,66 @@ impl Engine {
             sender_model,
             n_workers,
             receiver,
+            stop: Arc::new(AtomicBool::new(false)),
+            table: None,
+            mode: SearchMode::default(),
+            max_depth: None,
+            max_nodes: None,
+            stats: None,
+            history: Vec::new(),
         }
     }
+    /// Shares the flag that ends the search early once set. The best move found so far is then
+    /// returned as if the time limit had been reached.
+    pub fn set_stop(&mut self, stop: Arc<AtomicBool>) {
+        self.stop = stop;
+    }
+    /// Shares a transposition table with the workers. Positions in it are not evaluated again, and
+    /// the explored tree is stored in it once the search is done, so that it can be used for the
+    /// next move.
+    pub fn set_table(&mut self, table: Arc<TranspositionTable>) {
+        self.table = Some(table);
+    }
+    pub fn set_search_mode(&mut self, mode: SearchMode) {
+        self.mode = mode;
+    }
+    /// Limits the depth of a PVS search, which otherwise deepens until the time runs out
+    pub fn set_depth_limit(&mut self, depth: Option<usize>) {
+        self.max_depth = depth;
+    }
+    /// Limits the nodes of a PVS search. Like the time limit, it is only checked once the first
+    /// iteration is done.
+    pub fn set_node_limit(&mut self, nodes: Option<u64>) {
+        self.max_nodes = nodes;
+    }
+    /// Zobrist keys of the positions played in the game before the searched one (oldest first),
+    /// which the PVS search scores as draws when a line repeats them
+    pub fn set_history(&mut self, history: Vec<u64>) {
+        self.history = history;
+    }
+    /// Depth and nodes of the last PVS search, `None` for the best-first search and book moves
+    pub fn stats(&self) -> Option<SearchStats> {
+        self.stats
+    }
+    fn stopped(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+    }
+
+    /// Picks a move for the position, from the opening book if a database is given and the game
+    /// is still in the opening. The evaluation of the chosen position is `None` for book moves.
     pub fn begin_search(
         &mut self,
         prel_search_limit: Duration,
         deep_search_limit: Duration,
         phase: Option<GamePhase>,
-        db_conn: &Connection,
-    ) -> (Board, Option<GamePhase>) {
+        db_conn: Option<&Connection>,
+    ) -> (Board, Option<GamePhase>, Option<Eval>) {
         if let Some(p) = phase {
-            match p {
-                GamePhase::Opening(id) => {
+            match (p, db_conn) {
+                (GamePhase::Opening(id), Some(db_conn)) => {
                     if let Ok((board, phase)) = opening_book::play_bookmove(db_conn, id) {
                         if let Some(res_board) = board {
-                            (res_board, phase)
+                            (res_board, phase, None)
                         } else {
                             self.search(prel_search_limit, deep_search_limit)
                         }
@@ -233,7 +372,42 @@ impl Engine {
         &mut self,
         prel_search_limit: Duration,
         deep_search_limit: Duration,
-    ) -> (Board, Option<GamePhase>) {
+    ) -> (Board, Option<GamePhase>, Option<Eval>) {
+        match self.mode {
+            SearchMode::BestFirst => self.tree_search(prel_search_limit, deep_search_limit),
+            // Without a preliminary stage, the PVS search gets all of the time
+            SearchMode::Pvs => self.pvs_search(prel_search_limit + deep_search_limit),
+        }
+    }
+
+    fn pvs_search(&mut self, time_limit: Duration) -> (Board, Option<GamePhase>, Option<Eval>) {
+        let (mut board, phase) = if let Ok(branch) = self.branch.read() {
+            (branch.board, branch.phase)
+        } else {
+            panic!("Failed to read branch");
+        };
+        let mut pvs = Pvs::new(
+            self.table.as_deref(),
+            &self.stop,
+            Some(time_limit),
+            self.max_nodes,
+        );
+        pvs.set_history(&self.history);
+        let res = pvs
+            .search(board, self.max_depth)
+            .expect("Failed to analyze position");
+        info!("Best eval is {:?} at depth {}", res.eval, res.stats.depth);
+        self.stats = Some(res.stats);
+
+        board.make_move(res.best_move);
+        (board, phase, Some(res.eval))
+    }
+
+    fn tree_search(
+        &mut self,
+        prel_search_limit: Duration,
+        deep_search_limit: Duration,
+    ) -> (Board, Option<GamePhase>, Option<Eval>) {
         self.preliminary_search(prel_search_limit);
         self.main_search(deep_search_limit);
 
@@ -248,11 +422,14 @@ impl Engine {
             info!("Best eval is {:?}", best.unwrap().eval);
 
             let res = if let Some(chosen) = best {
-                (chosen.board, chosen.phase)
+                (chosen.board, chosen.phase, chosen.eval)
             } else {
                 debug!("Incomplete");
                 panic!("Failed to analyze position");
             };
+            if let Some(table) = &self.table {
+                branch.store_tree(table, 0);
+            }
             //branch.show_branch(0);
             res
         } else {
@@ -325,7 +502,7 @@ impl Engine {
 
                 self.add_job(requested, next_worker);
 
-                if start_time.elapsed().unwrap() > time_limit {
+                if start_time.elapsed().unwrap() > time_limit || self.stopped() {
                     break 'search_loop;
                 }
             }
@@ -364,7 +541,7 @@ impl Engine {
 
                 self.add_job(requested, next_worker);
 
-                if start_time.elapsed().unwrap() > time_limit {
+                if start_time.elapsed().unwrap() > time_limit || self.stopped() {
                     // When an answer has been demanded, let the current threads finish
                     break 'search_loop;
                 }
@@ -421,11 +598,12 @@ impl Engine {
     fn add_job(&self, location: Vec<usize>, worker_type: WorkerType) {
         let tx = self.sender_model.clone();
         let tree_wrapper = Arc::clone(&self.branch);
+        let table = self.table.clone();
         let mut node = self.find_node(&location.as_slice());
         self.workers.execute(move || {
             let start_time = SystemTime::now();
             debug!("Evaluating {location:?}");
-            node.run_node(location.len());
+            node.run_node(location.len(), table.as_deref());
 
             // determine next node to be evaluated
             let next_worker = if let Ok(tree) = tree_wrapper.read() {
@@ -474,10 +652,11 @@ impl Engine {
     fn add_base_job(&self) {
         let tx = self.sender_model.clone();
 
+        let table = self.table.clone();
         let mut node = self.find_node(&vec![].as_slice()).clone();
         self.workers.execute(move || {
             debug!("Initializing base node");
-            node.run_base_node();
+            node.run_base_node(table.as_deref());
             tx.send((
                 Some((node, vec![], true)),
                 (None, WorkerType::WideSearch((vec![], 0))),
@@ -503,6 +682,13 @@ impl Default for Engine {
             sender_model,
             n_workers: num_cpus::get(),
             receiver,
+            stop: Arc::new(AtomicBool::new(false)),
+            table: None,
+            mode: SearchMode::default(),
+            max_depth: None,
+            max_nodes: None,
+            stats: None,
+            history: Vec::new(),
         }
     }
 }
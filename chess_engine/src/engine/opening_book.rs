@@ -30,7 +30,13 @@ pub fn play_bookmove(
             .unwrap();
         stm_req.bind((":id", chosen.id)).unwrap();
         if let Ok(State::Row) = stm_req.next() {
-            let res_board = Board::from(stm_req.read::<String, _>("fen").unwrap());
+            let res_board = match Board::from_fen(&stm_req.read::<String, _>("fen").unwrap()) {
+                Ok(board) => board,
+                Err(e) => {
+                    error!("Book move has invalid FEN: {e}");
+                    return Err(EngineError::DatabaseError);
+                }
+            };
             let phase = if chosen.terminal {
                 Some(GamePhase::MiddleGame)
             } else {
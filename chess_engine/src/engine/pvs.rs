@@ -0,0 +1,368 @@
+// Iterative deepening principal variation search, the alternative to the best-first tree search
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::{Duration, Instant},
+};
+
+use chess_backend::{Board, Colour, FinishedState, GameState, Move};
+
+use super::{
+    transposition::{Bound, TranspositionTable},
+    tree::Branch,
+    utils::eval::Eval,
+};
+
+// Inside the search, scores are centipawns from the point of view of the side to move, with a
+// mate given as MATE minus the ply of the mated position
+const MATE: i32 = 1_000_000;
+const INFINITY: i32 = MATE + 1;
+// Deepest line the search can go into, which also bounds the plies of a mate score
+pub const MAX_PLY: usize = 128;
+// Nodes between checks of the clock and the stop flag
+const CHECK_INTERVAL: u64 = 1024;
+
+/// Progress of a search, as of the last completed iteration
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SearchStats {
+    pub depth: usize,
+    pub nodes: u64,
+}
+
+/// Outcome of `Pvs::search`
+#[derive(Debug, Clone, PartialEq)]
+pub struct PvsResult {
+    pub best_move: Move,
+    /// Evaluation of the root position from white's point of view
+    pub eval: Eval,
+    /// The expected line, starting with the best move
+    pub pv: Vec<Move>,
+    pub stats: SearchStats,
+}
+
+/// A single-threaded alpha-beta search with principal variation windows, searching the position
+/// one ply deeper on every iteration until the time, depth or node limit is reached. Leaves are
+/// evaluated with `Branch::eval_quiescence`.
+pub struct Pvs<'a> {
+    table: Option<&'a TranspositionTable>,
+    stop: &'a AtomicBool,
+    deadline: Option<Instant>,
+    max_nodes: Option<u64>,
+    nodes: u64,
+    // Set once a limit is reached, after which the unfinished iteration is thrown away
+    aborted: bool,
+    // The first iteration is always finished, so that there is a move to play
+    can_abort: bool,
+    // Zobrist keys of the positions played in the game and those on the current line, for
+    // detecting repetitions
+    line: Vec<u64>,
+}
+impl<'a> Pvs<'a> {
+    pub fn new(
+        table: Option<&'a TranspositionTable>,
+        stop: &'a AtomicBool,
+        time_limit: Option<Duration>,
+        max_nodes: Option<u64>,
+    ) -> Self {
+        Self {
+            table,
+            stop,
+            deadline: time_limit.and_then(|limit| Instant::now().checked_add(limit)),
+            max_nodes,
+            nodes: 0,
+            aborted: false,
+            can_abort: false,
+            line: Vec::new(),
+        }
+    }
+
+    /// Zobrist keys of the positions played in the game before the searched one (oldest first),
+    /// so that moves repeating one of them are scored as draws as well
+    pub fn set_history(&mut self, history: &[u64]) {
+        self.line = history.to_vec();
+    }
+
+    /// Searches the position to `max_depth` plies (or until a limit is reached), `None` if there
+    /// are no legal moves
+    pub fn search(&mut self, board: Board, max_depth: Option<usize>) -> Option<PvsResult> {
+        let mut board = board;
+        if board.generate_moves().is_empty() {
+            return None;
+        }
+
+        let colour = board.side_to_move();
+        let mut res = None;
+        for depth in 1..=max_depth.unwrap_or(MAX_PLY).clamp(1, MAX_PLY) {
+            let (score, best_move) = self.root(&mut board, depth);
+            if self.aborted {
+                break;
+            }
+            res = Some(PvsResult {
+                best_move,
+                eval: to_eval(score, colour),
+                pv: self.principal_variation(board, best_move, depth),
+                stats: SearchStats {
+                    depth,
+                    nodes: self.nodes,
+                },
+            });
+            self.can_abort = true;
+            // A forced mate will not change with more depth
+            if score.abs() >= MATE - depth as i32 || self.interrupted() {
+                break;
+            }
+        }
+        res
+    }
+
+    fn root(&mut self, board: &mut Board, depth: usize) -> (i32, Move) {
+        let moves = self.ordered_moves(board);
+        let mut alpha = -INFINITY;
+        let mut best_move = moves[0];
+        for (i, &m) in moves.iter().enumerate() {
+            let score = self.search_move(board, m, depth, alpha, INFINITY, 0, i == 0);
+            if self.aborted {
+                break;
+            }
+            if score > alpha {
+                alpha = score;
+                best_move = m;
+            }
+        }
+        if !self.aborted {
+            self.store(board, depth, Bound::Exact, alpha, Some(best_move), 0);
+        }
+        (alpha, best_move)
+    }
+
+    // Searches the position after `m`, with a null window for all but the first move. Those only
+    // need to be searched again with the full window if they turn out to be better.
+    #[allow(clippy::too_many_arguments)]
+    fn search_move(
+        &mut self,
+        board: &mut Board,
+        m: Move,
+        depth: usize,
+        alpha: i32,
+        beta: i32,
+        ply: usize,
+        first: bool,
+    ) -> i32 {
+        self.line.push(board.hash());
+        let undo = board.make_move(m);
+        let mut score = if first {
+            -self.pvs(board, depth - 1, -beta, -alpha, ply + 1)
+        } else {
+            -self.pvs(board, depth - 1, -alpha - 1, -alpha, ply + 1)
+        };
+        if !first && score > alpha && score < beta {
+            score = -self.pvs(board, depth - 1, -beta, -alpha, ply + 1);
+        }
+        board.unmake_move(m, undo);
+        self.line.pop();
+        score
+    }
+
+    fn pvs(&mut self, board: &mut Board, depth: usize, alpha: i32, beta: i32, ply: usize) -> i32 {
+        self.nodes += 1;
+        self.check_limits();
+        if self.aborted {
+            return 0;
+        }
+
+        if self.is_repetition(board) {
+            return 0;
+        }
+        let moves = board.generate_moves();
+        match board.get_unchecked_game_state(moves.len()) {
+            GameState::Ongoing => {}
+            // Only the side to move can have been mated
+            GameState::Finished(FinishedState::Win(..)) => return -(MATE - ply as i32),
+            GameState::Finished(FinishedState::Draw(_)) => return 0,
+        }
+        if depth == 0 || ply >= MAX_PLY {
+            return self.evaluate(board, ply);
+        }
+
+        let entry = self.table.and_then(|t| t.probe(board.hash(), ply));
+        if let Some(entry) = entry.filter(|e| e.depth >= depth) {
+            let score = to_score(entry.eval, board.side_to_move());
+            match entry.bound {
+                Bound::Exact => return score,
+                Bound::Lower if score >= beta => return score,
+                Bound::Upper if score <= alpha => return score,
+                _ => {}
+            }
+        }
+
+        let moves = self.order(board, moves, entry.and_then(|e| e.best_move));
+        let original_alpha = alpha;
+        let mut alpha = alpha;
+        let mut best_score = -INFINITY;
+        let mut best_move = None;
+        for (i, &m) in moves.iter().enumerate() {
+            let score = self.search_move(board, m, depth, alpha, beta, ply, i == 0);
+            if self.aborted {
+                return 0;
+            }
+            if score > best_score {
+                best_score = score;
+                best_move = Some(m);
+            }
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        let bound = if best_score >= beta {
+            Bound::Lower
+        } else if best_score <= original_alpha {
+            Bound::Upper
+        } else {
+            Bound::Exact
+        };
+        self.store(board, depth, bound, best_score, best_move, ply);
+        best_score
+    }
+
+    fn evaluate(&mut self, board: &Board, ply: usize) -> i32 {
+        let colour = board.side_to_move();
+        // Positions evaluated by either search are stored with depth 0
+        if let Some(entry) = self
+            .table
+            .and_then(|t| t.probe(board.hash(), ply))
+            .filter(|e| e.bound == Bound::Exact)
+        {
+            return to_score(entry.eval, colour);
+        }
+        let eval = Branch::from(*board).eval_quiescence(ply);
+        if let Some(table) = self.table {
+            table.store(board.hash(), 0, Bound::Exact, eval, None, ply);
+        }
+        to_score(eval, colour)
+    }
+
+    fn store(
+        &self,
+        board: &Board,
+        depth: usize,
+        bound: Bound,
+        score: i32,
+        best_move: Option<Move>,
+        ply: usize,
+    ) {
+        if let Some(table) = self.table {
+            let eval = to_eval(score, board.side_to_move());
+            table.store(board.hash(), depth, bound, eval, best_move, ply);
+        }
+    }
+
+    fn ordered_moves(&self, board: &Board) -> Vec<Move> {
+        let best_move = self
+            .table
+            .and_then(|t| t.probe(board.hash(), 0))
+            .and_then(|e| e.best_move);
+        self.order(board, board.generate_moves(), best_move)
+    }
+
+    // The best move from the table first, then captures by their static exchange evaluation
+    // and finally the quiet moves
+    fn order(&self, board: &Board, mut moves: Vec<Move>, best_move: Option<Move>) -> Vec<Move> {
+        moves.sort_by_cached_key(|&m| {
+            if Some(m) == best_move {
+                i32::MIN
+            } else if m.is_capture() || m.promotion().is_some() {
+                -MATE - board.see(m)
+            } else {
+                0
+            }
+        });
+        moves
+    }
+
+    // Repetitions of a position in the game or on the current line are scored as draws, going
+    // back no further than the last capture or pawn move
+    fn is_repetition(&self, board: &Board) -> bool {
+        let hash = board.hash();
+        self.line
+            .iter()
+            .rev()
+            .take(board.halfmove() as usize)
+            .skip(1)
+            .step_by(2)
+            .any(|&h| h == hash)
+    }
+
+    fn check_limits(&mut self) {
+        if !self.can_abort {
+            return;
+        }
+        if self.max_nodes.is_some_and(|n| self.nodes >= n) {
+            self.aborted = true;
+        }
+        // The clock and the stop flag are only looked at every so often
+        if self.nodes.is_multiple_of(CHECK_INTERVAL) && self.interrupted() {
+            self.aborted = true;
+        }
+    }
+
+    fn interrupted(&self) -> bool {
+        self.deadline.is_some_and(|d| Instant::now() >= d) || self.stop.load(Ordering::Relaxed)
+    }
+
+    // Follows the best moves stored in the table, which have to be legal in the position
+    fn principal_variation(&self, board: Board, best_move: Move, depth: usize) -> Vec<Move> {
+        let mut board = board;
+        let mut pv = vec![best_move];
+        board.make_move(best_move);
+        let Some(table) = self.table else {
+            return pv;
+        };
+        while pv.len() < depth {
+            let Some(m) = table
+                .probe(board.hash(), pv.len())
+                .and_then(|e| e.best_move)
+                .filter(|m| board.generate_moves().contains(m))
+            else {
+                break;
+            };
+            pv.push(m);
+            board.make_move(m);
+        }
+        pv
+    }
+}
+
+/// Score for the side to move of an evaluation from white's point of view
+fn to_score(eval: Eval, colour: Colour) -> i32 {
+    let score = match eval {
+        Eval::Numeric(n) => n.round() as i32,
+        Eval::Mate(ply, Colour::White) => MATE - ply as i32,
+        Eval::Mate(ply, Colour::Black) => -(MATE - ply as i32),
+        Eval::Infinity => INFINITY,
+        Eval::NegInfinity => -INFINITY,
+    };
+    match colour {
+        Colour::White => score,
+        Colour::Black => -score,
+    }
+}
+
+/// Evaluation from white's point of view of a score for the side to move
+fn to_eval(score: i32, colour: Colour) -> Eval {
+    let score = match colour {
+        Colour::White => score,
+        Colour::Black => -score,
+    };
+    if score >= INFINITY {
+        Eval::Infinity
+    } else if score <= -INFINITY {
+        Eval::NegInfinity
+    } else if score >= MATE - MAX_PLY as i32 {
+        Eval::Mate((MATE - score) as usize, Colour::White)
+    } else if score <= -(MATE - MAX_PLY as i32) {
+        Eval::Mate((MATE + score) as usize, Colour::Black)
+    } else {
+        Eval::Numeric(score as f32)
+    }
+}
@@ -0,0 +1,206 @@
+// Searches started by the protocol front ends (UCI and XBoard), which only need the chosen move
+use std::{
+    path::Path,
+    sync::{atomic::AtomicBool, Arc},
+    time::{Duration, SystemTime},
+};
+
+use chess_backend::{Board, ChessMove, Colour};
+use log::info;
+use sqlite::Connection;
+
+use super::{
+    opening_book::search_manual,
+    pvs::SearchStats,
+    transposition::TranspositionTable,
+    utils::{eval::Eval, phase::GamePhase},
+    Engine, SearchMode, PRELIMINARY_TIME_SHARE,
+};
+
+// Used when the GUI does not give a clock, e.g. `go depth 8`
+pub const DEFAULT_MOVE_TIME: Duration = Duration::from_secs(2);
+// Moves the remaining time is spread over when the GUI does not say how many are left
+const DEFAULT_MOVES_TO_GO: u32 = 30;
+// Kept back from every move for joining the workers and replying
+const MOVE_OVERHEAD: Duration = Duration::from_millis(50);
+// Searching "until stopped" is a search with a time limit that is never reached
+const INFINITE_TIME: Duration = Duration::from_secs(60 * 60 * 24 * 365);
+
+/// Limits of a search as given by the GUI, e.g. by a UCI `go` command
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchLimits {
+    pub wtime: Option<Duration>,
+    pub btime: Option<Duration>,
+    pub winc: Option<Duration>,
+    pub binc: Option<Duration>,
+    pub movestogo: Option<u32>,
+    pub movetime: Option<Duration>,
+    /// Only followed by the PVS search, the best-first search is purely time based
+    pub depth: Option<usize>,
+    /// Only followed by the PVS search, the best-first search is purely time based
+    pub nodes: Option<u64>,
+    pub infinite: bool,
+}
+impl SearchLimits {
+    /// Time to spend on the move for the side to move, `None` for searching until stopped
+    pub fn time_limit(&self, colour: Colour) -> Option<Duration> {
+        if self.infinite {
+            return None;
+        }
+        if let Some(movetime) = self.movetime {
+            return Some(movetime.saturating_sub(MOVE_OVERHEAD));
+        }
+        let (remaining, inc) = match colour {
+            Colour::White => (self.wtime, self.winc),
+            Colour::Black => (self.btime, self.binc),
+        };
+        match remaining {
+            Some(remaining) => {
+                let moves = self.movestogo.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+                let share = remaining / moves + inc.unwrap_or_default();
+                // Never risk more than half of the clock on a single move
+                Some(share.min(remaining / 2).saturating_sub(MOVE_OVERHEAD))
+            }
+            None => Some(DEFAULT_MOVE_TIME),
+        }
+    }
+
+    /// Time to spend on the move in the given mode. Unlike the best-first search, a PVS search
+    /// limited only by depth or nodes is not given a time limit.
+    pub fn time_limit_in(&self, colour: Colour, mode: SearchMode) -> Option<Duration> {
+        let has_clock = self.movetime.is_some() || self.wtime.is_some() || self.btime.is_some();
+        let has_limit = self.depth.is_some() || self.nodes.is_some();
+        if mode == SearchMode::Pvs && has_limit && !has_clock {
+            return None;
+        }
+        self.time_limit(colour)
+    }
+}
+
+/// Everything a search thread needs, taken from the front end when it is asked for a move
+pub struct Search {
+    pub board: Board,
+    /// Zobrist keys of the positions played before `board`, for detecting repetitions
+    pub history: Vec<u64>,
+    /// `None` for searching until stopped
+    pub time_limit: Option<Duration>,
+    pub threads: usize,
+    /// Path of the opening database and the SAN of the moves played from the start position,
+    /// `None` if the book is not to be followed
+    pub book: Option<(String, Vec<String>)>,
+    /// Ends the search early once set
+    pub stop: Arc<AtomicBool>,
+    /// Kept by the front end between searches
+    pub table: Arc<TranspositionTable>,
+    pub mode: SearchMode,
+    /// Limits of a PVS search, ignored by the best-first search
+    pub depth: Option<usize>,
+    pub nodes: Option<u64>,
+}
+
+/// Evaluation as reported to a GUI, from the point of view of one side
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Score {
+    Centipawns(i64),
+    /// Moves (not plies) until mate, negative when the side is getting mated
+    Mate(i64),
+}
+impl Score {
+    /// Score of an evaluation (which is from white's point of view) for the given side, `None`
+    /// for the bounds of the search
+    pub fn new(eval: Eval, colour: Colour) -> Option<Self> {
+        let sign = match colour {
+            Colour::White => 1,
+            Colour::Black => -1,
+        };
+        match eval {
+            Eval::Numeric(n) => Some(Self::Centipawns(sign * n.round() as i64)),
+            Eval::Mate(depth, winner) => {
+                // The depth counts plies from the searched position
+                let moves = (depth as i64 + 1) / 2;
+                Some(Self::Mate(if winner == colour { moves } else { -moves }))
+            }
+            Eval::Infinity | Eval::NegInfinity => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchResult {
+    pub best: ChessMove,
+    /// Evaluation of the position after the move (from white's point of view), `None` for
+    /// book moves
+    pub eval: Option<Eval>,
+    pub elapsed: Duration,
+    /// Depth and nodes reached by a PVS search
+    pub stats: Option<SearchStats>,
+}
+
+impl Search {
+    /// Searches until a limit is reached or the search is stopped, `None` if there are no legal
+    /// moves
+    pub fn run(self) -> Option<SearchResult> {
+        let moves = self.board.generate_legal_moves();
+        if moves.is_empty() {
+            return None;
+        }
+
+        let db_conn = self.book.as_ref().and_then(|(path, _)| open_book(path));
+        let phase = match (&db_conn, &self.book) {
+            (Some(db_conn), Some((_, line))) => Some(book_phase(db_conn, line)),
+            _ => None,
+        };
+
+        let start_time = SystemTime::now();
+        let time_limit = self.time_limit.unwrap_or(INFINITE_TIME);
+        let mut engine = Engine::new(self.board, self.threads, phase);
+        engine.set_stop(self.stop);
+        engine.set_table(self.table);
+        engine.set_search_mode(self.mode);
+        engine.set_depth_limit(self.depth);
+        engine.set_node_limit(self.nodes);
+        engine.set_history(self.history);
+        let (board, _phase, eval) = engine.begin_search(
+            time_limit.mul_f32(PRELIMINARY_TIME_SHARE),
+            time_limit.mul_f32(1. - PRELIMINARY_TIME_SHARE),
+            phase,
+            db_conn.as_ref(),
+        );
+
+        // Book positions are read from FEN, so the move is found by the position it leads to
+        let best = moves
+            .into_iter()
+            .find(|m| m.board.hash() == board.hash())
+            .expect("Chosen position should follow from a legal move");
+        Some(SearchResult {
+            best,
+            eval,
+            elapsed: start_time.elapsed().unwrap_or_default(),
+            stats: engine.stats(),
+        })
+    }
+}
+
+fn open_book(path: &str) -> Option<Connection> {
+    // Opening a missing file would create an empty database without any book moves
+    if !Path::new(path).exists() {
+        info!("No opening book at {path}");
+        return None;
+    }
+    Connection::open(path).ok()
+}
+
+// Follows the moves from the start position through the book, in the same way the controller
+// does while playing
+fn book_phase(db_conn: &Connection, line: &[String]) -> GamePhase {
+    let mut phase = GamePhase::Opening(1);
+    for san in line {
+        match phase {
+            GamePhase::Opening(id) => {
+                phase = search_manual(db_conn, id, san.clone()).unwrap_or(GamePhase::MiddleGame);
+            }
+            _ => break,
+        }
+    }
+    phase
+}
@@ -0,0 +1,129 @@
+// Transposition table, shared by the search workers and kept between moves
+use std::{mem::size_of, sync::Mutex};
+
+use chess_backend::Move;
+
+use super::utils::eval::Eval;
+
+/// Size of the table used by the controller and the protocol front ends unless told otherwise
+pub const DEFAULT_TABLE_MB: usize = 16;
+
+/// How the stored evaluation relates to the true value of the position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound {
+    Exact,
+    /// The position is worth at least the stored evaluation (the search failed high)
+    Lower,
+    /// The position is worth at most the stored evaluation (the search failed low)
+    Upper,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Entry {
+    /// Zobrist key of the position
+    pub key: u64,
+    /// Plies searched below the position, 0 for the evaluation of a leaf
+    pub depth: usize,
+    pub bound: Bound,
+    /// Evaluation from white's point of view. Mates count plies from the position itself, and are
+    /// converted back to plies from the root by `TranspositionTable::probe`.
+    pub eval: Eval,
+    pub best_move: Option<Move>,
+}
+
+/// A fixed-size table of evaluated positions, indexed by their Zobrist key. Every slot has its
+/// own lock, so that workers only wait for each other when they access the same slot.
+#[derive(Debug)]
+pub struct TranspositionTable {
+    slots: Vec<Mutex<Option<Entry>>>,
+}
+impl TranspositionTable {
+    /// Creates a table that takes up (at most) the given number of megabytes, with at least one slot
+    pub fn new(megabytes: usize) -> Self {
+        let n_slots = (megabytes * 1024 * 1024 / size_of::<Mutex<Option<Entry>>>()).max(1);
+        Self {
+            slots: (0..n_slots).map(|_| Mutex::new(None)).collect(),
+        }
+    }
+
+    /// Share of the table in use in permille, estimated from the first thousand slots (as given
+    /// by UCI's `hashfull`)
+    pub fn hashfull(&self) -> usize {
+        let sample = &self.slots[..self.slots.len().min(1000)];
+        let used = sample
+            .iter()
+            .filter(|slot| slot.lock().expect("Failed to lock table slot").is_some())
+            .count();
+        used * 1000 / sample.len()
+    }
+
+    fn slot(&self, key: u64) -> &Mutex<Option<Entry>> {
+        &self.slots[(key % self.slots.len() as u64) as usize]
+    }
+
+    /// Looks up a position found `ply` plies from the root of the current search
+    pub fn probe(&self, key: u64, ply: usize) -> Option<Entry> {
+        let entry = (*self.slot(key).lock().expect("Failed to lock table slot"))?;
+        if entry.key != key {
+            return None;
+        }
+        Some(Entry {
+            eval: from_table(entry.eval, ply),
+            ..entry
+        })
+    }
+
+    /// Stores the evaluation of a position found `ply` plies from the root of the current search.
+    /// Another position in the same slot is always replaced, while an entry for the same position
+    /// is only replaced by one searched at least as deep.
+    pub fn store(
+        &self,
+        key: u64,
+        depth: usize,
+        bound: Bound,
+        eval: Eval,
+        best_move: Option<Move>,
+        ply: usize,
+    ) {
+        let mut slot = self.slot(key).lock().expect("Failed to lock table slot");
+        if let Some(entry) = *slot {
+            if entry.key == key && entry.depth > depth {
+                return;
+            }
+        }
+        *slot = Some(Entry {
+            key,
+            depth,
+            bound,
+            eval: to_table(eval, ply),
+            best_move,
+        });
+    }
+
+    /// Removes every entry, e.g. when a new game is started
+    pub fn clear(&self) {
+        for slot in &self.slots {
+            *slot.lock().expect("Failed to lock table slot") = None;
+        }
+    }
+}
+impl Default for TranspositionTable {
+    fn default() -> Self {
+        Self::new(DEFAULT_TABLE_MB)
+    }
+}
+
+// Mates are stored relative to the position, since the same position can be reached at different
+// plies from the root (and from different roots on later moves)
+fn to_table(eval: Eval, ply: usize) -> Eval {
+    match eval {
+        Eval::Mate(depth, colour) => Eval::Mate(depth.saturating_sub(ply), colour),
+        _ => eval,
+    }
+}
+fn from_table(eval: Eval, ply: usize) -> Eval {
+    match eval {
+        Eval::Mate(depth, colour) => Eval::Mate(depth + ply, colour),
+        _ => eval,
+    }
+}
@@ -1,9 +1,10 @@
 use std::marker::{Send, Sync};
 use std::sync::{Arc, Mutex};
 
-use chess_backend::{to_str, Board, ChessMove, Colour, MoveType, Piece};
+use chess_backend::{Board, ChessMove, Colour, Move, MoveType, Piece};
 use log::{debug, error, info};
 
+use crate::engine::transposition::{Bound, TranspositionTable};
 use crate::engine::utils::eval::Eval;
 use crate::engine::utils::phase::GamePhase;
 
@@ -11,7 +12,7 @@ use crate::engine::utils::phase::GamePhase;
 pub struct Branch {
     pub board: Board,
     pub game_over: bool,
-    pub _res_move: Option<ChessMove>, // debug purposes only
+    pub _res_move: Option<Move>, // move that led to this position
     pub eval: Option<Eval>,
     pub phase: Option<GamePhase>,
     pub children: Vec<Branch>,
@@ -31,35 +32,39 @@ impl Branch {
             .collect();
     }
 
-    pub fn run_base_node(&mut self) {
-        self.eval_node(None, 0);
-        self.run_node(0);
+    pub fn run_base_node(&mut self, table: Option<&TranspositionTable>) {
+        self.eval_node(None, 0, table);
+        self.run_node(0, table);
     }
 
-    pub fn run_node(&mut self, depth: usize) {
+    pub fn run_node(&mut self, depth: usize, table: Option<&TranspositionTable>) {
         debug!("Running node");
         if let Some(Eval::Mate(_, _)) = self.eval {
             info!("Evaluating mate");
         }
         self.is_terminal = false;
         for child in &mut self.children {
-            child.eval_node(Some(self.board), depth + 1);
+            child.eval_node(Some(self.board), depth + 1, table);
         }
     }
 
-    fn eval_node(&mut self, parent_board: Option<Board>, depth: usize) {
+    fn eval_node(
+        &mut self,
+        parent_board: Option<Board>,
+        depth: usize,
+        table: Option<&TranspositionTable>,
+    ) {
         self.populate(depth);
 
         self.is_terminal = true;
 
-        let heuristic = self.eval_position(self.children.len(), depth);
+        let heuristic = self.eval_position(self.children.len(), depth, table);
         self.eval = Some(heuristic);
-        self.priority = if let Some(orig_board) = parent_board {
-            Some(Self::calc_priority(
-                orig_board, self.board, depth, heuristic,
-            ))
-        } else {
-            None
+        self.priority = match (parent_board, self._res_move) {
+            (Some(orig_board), Some(res_move)) => Some(Self::calc_priority(
+                orig_board, res_move, self.board, depth, heuristic,
+            )),
+            _ => None,
         }
     }
 
@@ -275,6 +280,34 @@ impl Branch {
         }
     }
 
+    /// Stores the evaluations of the explored tree in the table, after `simple_minimax` has backed
+    /// them up from the terminal nodes. Returns the depth the branch has been searched to, which
+    /// is that of its shallowest child line.
+    pub fn store_tree(&self, table: &TranspositionTable, ply: usize) -> usize {
+        // Terminal nodes are stored when they are evaluated
+        if self.is_terminal || self.children.is_empty() {
+            return 0;
+        }
+        let Some(eval) = self.eval else {
+            return 0;
+        };
+        let evaluated = self.children.iter().filter(|c| c.eval.is_some());
+        let depth = 1 + evaluated
+            .clone()
+            .map(|child| child.store_tree(table, ply + 1))
+            .min()
+            .unwrap_or(0);
+        let best_move = if self.board.side_to_move() == Colour::White {
+            evaluated.max_by(|c1, c2| c1.eval.partial_cmp(&c2.eval).unwrap())
+        } else {
+            evaluated.min_by(|c1, c2| c1.eval.partial_cmp(&c2.eval).unwrap())
+        }
+        .and_then(|child| child._res_move);
+        // The tree is fully minimaxed, so the backed up evaluations are exact
+        table.store(self.board.hash(), depth, Bound::Exact, eval, best_move, ply);
+        depth
+    }
+
     pub fn find_branch(&self, location: &[usize]) -> &Self {
         if location.len() == 0 {
             self
@@ -302,9 +335,7 @@ impl Branch {
                 print!("({depth})-");
             }
             if let Some(m) = self._res_move {
-                if let Some(dest) = m.base.destination_square {
-                    print!("{:?} -> {:?}, ", m.base.piece, to_str(dest));
-                }
+                print!("{m}, ");
             }
             println!(
                 "{} {:?} {:?} {}",
@@ -325,7 +356,7 @@ impl Branch {
         Self {
             board: m.board,
             game_over: false,
-            _res_move: Some(m),
+            _res_move: Some(self.board.encode_move(&m)),
             eval: None,
             phase: self.phase,
             priority: None,
@@ -0,0 +1,325 @@
+// Universal Chess Interface UCI, for running the engine from a GUI or match runner
+use std::{
+    io::Write,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use chess_backend::{Board, Colour, Game};
+
+use super::{
+    search::{Score, Search, SearchLimits, DEFAULT_MOVE_TIME},
+    send,
+    transposition::{TranspositionTable, DEFAULT_TABLE_MB},
+    utils::{error::EngineError, eval::Eval},
+    SearchMode, DB_PATH,
+};
+
+impl FromStr for SearchLimits {
+    type Err = EngineError;
+    /// Parses the arguments of a `go` command, e.g. `wtime 60000 btime 60000 movestogo 40`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        fn value<T: FromStr>(name: &str, value: Option<&str>) -> Result<T, EngineError> {
+            value.and_then(|v| v.parse().ok()).ok_or_else(|| {
+                EngineError::InvalidCommand(format!("go {name} {}", value.unwrap_or("")))
+            })
+        }
+        let millis = |name, v| value(name, v).map(Duration::from_millis);
+
+        let mut res = Self::default();
+        let mut tokens = s.split_whitespace();
+        while let Some(token) = tokens.next() {
+            match token {
+                "wtime" => res.wtime = Some(millis(token, tokens.next())?),
+                "btime" => res.btime = Some(millis(token, tokens.next())?),
+                "winc" => res.winc = Some(millis(token, tokens.next())?),
+                "binc" => res.binc = Some(millis(token, tokens.next())?),
+                "movestogo" => res.movestogo = Some(value(token, tokens.next())?),
+                "movetime" => res.movetime = Some(millis(token, tokens.next())?),
+                "depth" => res.depth = Some(value(token, tokens.next())?),
+                "nodes" => res.nodes = Some(value(token, tokens.next())?),
+                "infinite" => res.infinite = true,
+                // Pondering is not supported, so a ponder search is just a normal search
+                "ponder" => {}
+                _ => return Err(EngineError::InvalidCommand(format!("go {s}"))),
+            }
+        }
+        Ok(res)
+    }
+}
+
+/// Settings changed through `setoption`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UciOptions {
+    /// Number of search workers
+    pub threads: usize,
+    /// Opening database, the book is not used if the file does not exist
+    pub book_path: String,
+    /// Size of the transposition table in megabytes
+    pub hash: usize,
+    pub search_mode: SearchMode,
+}
+impl Default for UciOptions {
+    fn default() -> Self {
+        Self {
+            threads: num_cpus::get(),
+            book_path: DB_PATH.to_string(),
+            hash: DEFAULT_TABLE_MB,
+            search_mode: SearchMode::default(),
+        }
+    }
+}
+
+type Output<W> = Arc<Mutex<W>>;
+
+/// Handles UCI commands one line at a time. Searches run on a separate thread, so that `stop`,
+/// `isready` and `quit` are answered while searching.
+pub struct UciHandler<W: Write + Send + 'static> {
+    out: Output<W>,
+    options: UciOptions,
+    game: Game,
+    // SAN of the moves played from the start position, used to follow the opening book. `None`
+    // for positions set up from a FEN, which are not looked up in the book.
+    book_line: Option<Vec<String>>,
+    table: Arc<TranspositionTable>,
+    search: Option<(JoinHandle<()>, Arc<AtomicBool>)>,
+}
+impl<W: Write + Send + 'static> UciHandler<W> {
+    pub fn new(out: W) -> Self {
+        chess_backend::init();
+        Self {
+            out: Arc::new(Mutex::new(out)),
+            options: UciOptions::default(),
+            game: Game::default(),
+            book_line: Some(vec![]),
+            table: Arc::new(TranspositionTable::default()),
+            search: None,
+        }
+    }
+
+    /// The position set up by the last `position` command
+    pub fn board(&self) -> Board {
+        self.game.board()
+    }
+
+    /// Handles a single command, returning `false` once the engine should quit. Commands that
+    /// can not be handled are reported to the GUI as `info string` and otherwise ignored.
+    pub fn handle(&mut self, line: &str) -> bool {
+        match self.command(line) {
+            Ok(running) => running,
+            Err(e) => {
+                send(&self.out, &format!("info string {e}"));
+                true
+            }
+        }
+    }
+
+    fn command(&mut self, line: &str) -> Result<bool, EngineError> {
+        let (command, args) = line
+            .trim()
+            .split_once(char::is_whitespace)
+            .unwrap_or((line.trim(), ""));
+        match command {
+            "uci" => {
+                send(&self.out, "id name BRAIN");
+                send(&self.out, "id author HellFelix");
+                send(
+                    &self.out,
+                    &format!(
+                        "option name Threads type spin default {} min 1 max 512",
+                        UciOptions::default().threads
+                    ),
+                );
+                send(
+                    &self.out,
+                    &format!("option name BookPath type string default {DB_PATH}"),
+                );
+                send(
+                    &self.out,
+                    &format!(
+                        "option name Hash type spin default {DEFAULT_TABLE_MB} min 1 max 4096"
+                    ),
+                );
+                send(
+                    &self.out,
+                    "option name SearchMode type combo default BestFirst var BestFirst var PVS",
+                );
+                send(&self.out, "uciok");
+            }
+            "isready" => send(&self.out, "readyok"),
+            "ucinewgame" => {
+                self.stop_search();
+                self.game = Game::default();
+                self.book_line = Some(vec![]);
+                self.table.clear();
+            }
+            "setoption" => self.set_option(args)?,
+            "position" => self.set_position(args)?,
+            "go" => {
+                let limits = args.parse()?;
+                self.stop_search();
+                self.start_search(limits);
+            }
+            "stop" => self.stop_search(),
+            "quit" => {
+                self.stop_search();
+                return Ok(false);
+            }
+            // Nothing to do without pondering or debug output
+            "" | "debug" | "ponderhit" | "register" => {}
+            _ => return Err(EngineError::InvalidCommand(line.trim().to_string())),
+        }
+        Ok(true)
+    }
+
+    // setoption name <name> [value <value>], where the name may contain spaces
+    fn set_option(&mut self, args: &str) -> Result<(), EngineError> {
+        let invalid = || EngineError::InvalidCommand(format!("setoption {args}"));
+        let rest = args.trim().strip_prefix("name").ok_or_else(invalid)?;
+        let (name, value) = match rest.split_once(" value ") {
+            Some((name, value)) => (name.trim(), value.trim()),
+            None => (rest.trim(), ""),
+        };
+        match name.to_lowercase().as_str() {
+            "threads" => {
+                let threads: usize = value.parse().map_err(|_| invalid())?;
+                self.options.threads = threads.max(1);
+            }
+            "bookpath" => self.options.book_path = value.to_string(),
+            "hash" => {
+                let hash: usize = value.parse().map_err(|_| invalid())?;
+                self.options.hash = hash.max(1);
+                self.stop_search();
+                self.table = Arc::new(TranspositionTable::new(self.options.hash));
+            }
+            "searchmode" => {
+                self.options.search_mode = match value.to_lowercase().as_str() {
+                    "bestfirst" => SearchMode::BestFirst,
+                    "pvs" => SearchMode::Pvs,
+                    _ => return Err(invalid()),
+                }
+            }
+            _ => return Err(invalid()),
+        }
+        Ok(())
+    }
+
+    // position (startpos | fen <fen>) [moves <move>...]
+    fn set_position(&mut self, args: &str) -> Result<(), EngineError> {
+        let tokens: Vec<&str> = args.split_whitespace().collect();
+        let (setup, moves) = match tokens.iter().position(|&t| t == "moves") {
+            Some(i) => (&tokens[..i], &tokens[i + 1..]),
+            None => (&tokens[..], &[][..]),
+        };
+        let (board, mut book_line) = match setup.split_first() {
+            Some((&"startpos", [])) => (Board::default(), Some(vec![])),
+            Some((&"fen", fen)) => (Board::from_fen(&fen.join(" "))?, None),
+            _ => return Err(EngineError::InvalidCommand(format!("position {args}"))),
+        };
+
+        // Only replaced once all moves have been played, so that a bad command changes nothing
+        let mut game = Game::from(board);
+        for m in moves {
+            let board = game.board();
+            let m = board.parse_uci_move(m)?;
+            if let Some(line) = &mut book_line {
+                line.push(board.get_san(&m.board).into());
+            }
+            game.play(&m);
+        }
+        self.game = game;
+        self.book_line = book_line;
+        Ok(())
+    }
+
+    fn start_search(&mut self, limits: SearchLimits) {
+        let colour = self.board().side_to_move();
+        let mode = self.options.search_mode;
+        let stop = Arc::new(AtomicBool::new(false));
+        let search = Search {
+            board: self.board(),
+            history: self.game.history().to_vec(),
+            time_limit: limits.time_limit_in(colour, mode),
+            threads: self.options.threads,
+            // Analysis always searches, the book would answer at once
+            book: self
+                .book_line
+                .clone()
+                .filter(|_| !limits.infinite)
+                .map(|line| (self.options.book_path.clone(), line)),
+            stop: Arc::clone(&stop),
+            table: Arc::clone(&self.table),
+            mode,
+            depth: limits.depth,
+            nodes: limits.nodes,
+        };
+        let out = Arc::clone(&self.out);
+        let table = Arc::clone(&self.table);
+        let stopped = Arc::clone(&stop);
+        let handle = thread::spawn(move || {
+            let limited = limits.depth.is_some() || limits.nodes.is_some();
+            if limited && mode == SearchMode::BestFirst {
+                send(
+                    &out,
+                    &format!(
+                        "info string depth and nodes limits are not supported, searching for {}ms",
+                        search.time_limit.unwrap_or(DEFAULT_MOVE_TIME).as_millis()
+                    ),
+                );
+            }
+            let res = search.run();
+            // A search can end by itself (e.g. on finding a forced mate), but in infinite mode the
+            // move may only be given once the GUI sends `stop`
+            if limits.infinite {
+                while !stopped.load(Ordering::Relaxed) {
+                    thread::sleep(Duration::from_millis(5));
+                }
+            }
+            match res {
+                Some(res) => {
+                    let stats = res
+                        .stats
+                        .map(|stats| format!("depth {} nodes {} ", stats.depth, stats.nodes))
+                        .unwrap_or_default();
+                    let score = res
+                        .eval
+                        .and_then(|eval| score(eval, colour))
+                        .map(|score| format!("score {score} "))
+                        .unwrap_or_default();
+                    let best = res.best.to_uci();
+                    let time = res.elapsed.as_millis();
+                    let hashfull = table.hashfull();
+                    send(
+                        &out,
+                        &format!("info {stats}{score}time {time} hashfull {hashfull} pv {best}"),
+                    );
+                    send(&out, &format!("bestmove {best}"));
+                }
+                None => send(&out, "bestmove 0000"),
+            }
+        });
+        self.search = Some((handle, stop));
+    }
+
+    // Ends the running search (if any) and waits for it to send its best move
+    fn stop_search(&mut self) {
+        if let Some((handle, stop)) = self.search.take() {
+            stop.store(true, Ordering::Relaxed);
+            handle.join().expect("Search thread panicked");
+        }
+    }
+}
+
+/// UCI score of an evaluation (which is from white's point of view) for the side to move, e.g.
+/// `cp 35` or `mate -2`
+pub fn score(eval: Eval, colour: Colour) -> Option<String> {
+    Some(match Score::new(eval, colour)? {
+        Score::Centipawns(cp) => format!("cp {cp}"),
+        Score::Mate(moves) => format!("mate {moves}"),
+    })
+}
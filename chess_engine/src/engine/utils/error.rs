@@ -1,16 +1,28 @@
 use std::{error::Error, fmt::Display};
 
-use chess_backend::ChessError;
+use chess_backend::{ChessError, FenError};
 
 #[derive(Debug)]
 pub enum EngineError {
     DatabaseError,
-    InputError,
+    /// A move that could not be played, with the reason it was rejected
+    InvalidMove(ChessError),
+    /// A position that could not be set up
+    InvalidPosition(FenError),
+    /// A protocol command (UCI) that could not be understood
+    InvalidCommand(String),
 }
 
 impl Display for EngineError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Engine failure")
+        match self {
+            Self::DatabaseError => write!(f, "Engine failure: opening database error"),
+            Self::InvalidMove(e) => write!(f, "Engine failure: {e}"),
+            Self::InvalidPosition(e) => write!(f, "Engine failure: {e}"),
+            Self::InvalidCommand(command) => {
+                write!(f, "Engine failure: invalid command '{command}'")
+            }
+        }
     }
 }
 
@@ -18,8 +30,11 @@ impl Error for EngineError {}
 
 impl From<ChessError> for EngineError {
     fn from(value: ChessError) -> Self {
-        match value {
-            ChessError::InputError => EngineError::InputError,
-        }
+        EngineError::InvalidMove(value)
+    }
+}
+impl From<FenError> for EngineError {
+    fn from(value: FenError) -> Self {
+        EngineError::InvalidPosition(value)
     }
 }
@@ -0,0 +1,451 @@
+// XBoard/WinBoard Chess Engine Communication Protocol CECP (version 2)
+use std::{
+    io::Write,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use chess_backend::{
+    Board, ChessMove, Colour, FinishedState, Game, GameState, ReasonDraw, ReasonWin,
+};
+
+use super::{
+    search::{Score, Search, SearchLimits},
+    send,
+    transposition::TranspositionTable,
+    utils::{error::EngineError, eval::Eval},
+    SearchMode, DB_PATH,
+};
+
+// Score sent for a mate, to which the number of moves until mate is added
+const MATE_SCORE: i64 = 100000;
+
+/// Time control set by `level`, `st` and `sd`, along with the clocks sent by `time` and `otim`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Clock {
+    /// Moves per time control period, 0 if the base time is for the whole game
+    pub moves_per_session: u32,
+    pub base: Option<Duration>,
+    pub increment: Duration,
+    /// Exact time per move (`st`), overrides the other settings
+    pub move_time: Option<Duration>,
+    /// Only followed by the PVS search, the best-first search is purely time based
+    pub depth: Option<usize>,
+    pub own: Option<Duration>,
+    pub opponent: Option<Duration>,
+}
+impl Clock {
+    /// Limits for a search by the engine playing `colour`, `fullmove` being the number of the
+    /// move to be played
+    pub fn limits(&self, colour: Colour, fullmove: u32) -> SearchLimits {
+        let own = self.own.or(self.base);
+        let opponent = self.opponent.or(self.base);
+        let (wtime, btime) = match colour {
+            Colour::White => (own, opponent),
+            Colour::Black => (opponent, own),
+        };
+        let movestogo = match self.moves_per_session {
+            0 => None,
+            n => Some(n - (fullmove.max(1) - 1) % n),
+        };
+        SearchLimits {
+            wtime,
+            btime,
+            winc: Some(self.increment),
+            binc: Some(self.increment),
+            movestogo,
+            movetime: self.move_time,
+            depth: self.depth,
+            ..Default::default()
+        }
+    }
+}
+
+// Positions are kept as the moves played from the start, so that they can be taken back
+#[derive(Debug, Clone)]
+struct Position {
+    start: Board,
+    // Only games from the start position are looked up in the opening book
+    from_startpos: bool,
+    moves: Vec<ChessMove>,
+    game: Game,
+}
+impl Position {
+    fn new(start: Board, from_startpos: bool) -> Self {
+        Self {
+            start,
+            from_startpos,
+            moves: Vec::new(),
+            game: Game::from(start),
+        }
+    }
+
+    fn play(&mut self, m: ChessMove) {
+        self.game.play(&m);
+        self.moves.push(m);
+    }
+
+    // Takes back the last plies, returning false if there are not enough moves to do so
+    fn take_back(&mut self, plies: usize) -> bool {
+        if plies > self.moves.len() {
+            return false;
+        }
+        self.moves.truncate(self.moves.len() - plies);
+        self.game = Game::from(self.start);
+        for m in &self.moves {
+            self.game.play(m);
+        }
+        true
+    }
+
+    // SAN of the moves played from the start position
+    fn book_line(&self) -> Option<Vec<String>> {
+        if !self.from_startpos {
+            return None;
+        }
+        let mut board = self.start;
+        let line = self
+            .moves
+            .iter()
+            .map(|m| {
+                let san = board.get_san(&m.board).into();
+                board = m.board;
+                san
+            })
+            .collect();
+        Some(line)
+    }
+}
+impl Default for Position {
+    fn default() -> Self {
+        Self::new(Board::default(), true)
+    }
+}
+
+/// Result command for a finished game, e.g. `1-0 {White mates}`
+pub fn result(state: GameState) -> Option<String> {
+    let (result, comment) = match state {
+        GameState::Ongoing => return None,
+        GameState::Finished(FinishedState::Win(colour, reason)) => {
+            let result = match colour {
+                Colour::White => "1-0",
+                Colour::Black => "0-1",
+            };
+            let comment = match (colour, reason) {
+                (Colour::White, ReasonWin::Checkmate) => "White mates",
+                (Colour::Black, ReasonWin::Checkmate) => "Black mates",
+                (Colour::White, ReasonWin::Resignation) => "Black resigns",
+                (Colour::Black, ReasonWin::Resignation) => "White resigns",
+            };
+            (result, comment)
+        }
+        GameState::Finished(FinishedState::Draw(reason)) => {
+            let comment = match reason {
+                ReasonDraw::Stalemate => "Stalemate",
+                ReasonDraw::InsufficientMaterial => "Insufficient material",
+                ReasonDraw::HalfmoveLimit | ReasonDraw::SeventyFiveMoveRule => "Fifty move rule",
+                ReasonDraw::ThreefoldRepetition | ReasonDraw::FivefoldRepetition => {
+                    "Draw by repetition"
+                }
+                ReasonDraw::Agreement => "Draw agreed",
+            };
+            ("1/2-1/2", comment)
+        }
+    };
+    Some(format!("{result} {{{comment}}}"))
+}
+
+/// Score of an evaluation (which is from white's point of view) in centipawns for the engine
+/// playing `colour`, with mates given as 100000 plus the number of moves
+pub fn score(eval: Eval, colour: Colour) -> Option<i64> {
+    Some(match Score::new(eval, colour)? {
+        Score::Centipawns(cp) => cp,
+        Score::Mate(moves) if moves < 0 => -MATE_SCORE + moves,
+        Score::Mate(moves) => MATE_SCORE + moves,
+    })
+}
+
+// A running search, which plays its move on the shared position unless it is aborted
+struct Thinking {
+    handle: JoinHandle<()>,
+    stop: Arc<AtomicBool>,
+    abort: Arc<AtomicBool>,
+}
+
+/// Handles XBoard commands one line at a time. The engine thinks on a separate thread, so that
+/// `?`, `force` and the clock updates are handled while it is on move.
+pub struct XBoardHandler<W: Write + Send + 'static> {
+    out: Arc<Mutex<W>>,
+    // Shared with the search thread, which plays the engine's move once it has been found
+    position: Arc<Mutex<Position>>,
+    // Side played by the engine, `None` in force mode
+    engine: Option<Colour>,
+    clock: Clock,
+    threads: usize,
+    book_path: String,
+    table: Arc<TranspositionTable>,
+    search_mode: SearchMode,
+    post: bool,
+    thinking: Option<Thinking>,
+}
+impl<W: Write + Send + 'static> XBoardHandler<W> {
+    pub fn new(out: W) -> Self {
+        chess_backend::init();
+        Self {
+            out: Arc::new(Mutex::new(out)),
+            position: Arc::new(Mutex::new(Position::default())),
+            engine: Some(Colour::Black),
+            clock: Clock::default(),
+            threads: num_cpus::get(),
+            book_path: DB_PATH.to_string(),
+            table: Arc::new(TranspositionTable::default()),
+            search_mode: SearchMode::default(),
+            post: false,
+            thinking: None,
+        }
+    }
+
+    /// The current position of the game
+    pub fn board(&self) -> Board {
+        self.position().game.board()
+    }
+    fn position(&self) -> MutexGuard<'_, Position> {
+        self.position.lock().expect("Failed to lock position")
+    }
+
+    /// Handles a single command, returning `false` once the engine should quit. Commands that
+    /// can not be handled are answered with an `Error` line.
+    pub fn handle(&mut self, line: &str) -> bool {
+        match self.command(line) {
+            Ok(running) => running,
+            Err(e) => {
+                send(&self.out, &format!("Error ({e}): {}", line.trim()));
+                true
+            }
+        }
+    }
+
+    fn command(&mut self, line: &str) -> Result<bool, EngineError> {
+        let (command, args) = line
+            .trim()
+            .split_once(char::is_whitespace)
+            .unwrap_or((line.trim(), ""));
+        let args = args.trim();
+        let invalid = || EngineError::InvalidCommand(line.trim().to_string());
+        match command {
+            "protover" => send(
+                &self.out,
+                &format!(
+                    "feature myname=\"BRAIN\" ping=1 setboard=1 usermove=1 san=0 colors=0 \
+                     sigint=0 sigterm=0 reuse=1 analyze=0 memory=1 \
+                     option=\"BookPath -file {DB_PATH}\" \
+                     option=\"SearchMode -combo *BestFirst /// PVS\" done=1"
+                ),
+            ),
+            "new" => {
+                self.abort_thinking();
+                *self.position() = Position::default();
+                self.engine = Some(Colour::Black);
+                self.clock.depth = None;
+                self.table.clear();
+            }
+            "force" => {
+                self.abort_thinking();
+                self.engine = None;
+            }
+            "go" => {
+                self.abort_thinking();
+                self.engine = Some(self.board().side_to_move());
+                self.think();
+            }
+            // Move now
+            "?" => self.stop_thinking(),
+            "usermove" => self.user_move(args)?,
+            "level" => self.set_level(args).ok_or_else(invalid)?,
+            "st" => {
+                let seconds: f64 = args.parse().map_err(|_| invalid())?;
+                self.clock.move_time = Some(Duration::from_secs_f64(seconds.max(0.)));
+            }
+            "sd" => self.clock.depth = Some(args.parse().map_err(|_| invalid())?),
+            "time" => self.clock.own = Some(centiseconds(args).ok_or_else(invalid)?),
+            "otim" => self.clock.opponent = Some(centiseconds(args).ok_or_else(invalid)?),
+            "undo" | "remove" => {
+                self.abort_thinking();
+                let plies = if command == "undo" { 1 } else { 2 };
+                if !self.position().take_back(plies) {
+                    return Err(EngineError::InvalidCommand(
+                        "no moves to take back".to_string(),
+                    ));
+                }
+            }
+            "setboard" => {
+                self.abort_thinking();
+                *self.position() = Position::new(Board::from_fen(args)?, false);
+            }
+            "result" => {
+                self.abort_thinking();
+                self.engine = None;
+            }
+            "post" => self.post = true,
+            "nopost" => self.post = false,
+            "ping" => send(&self.out, &format!("pong {args}")),
+            // Hash size in megabytes, which is all the memory the engine allocates
+            "memory" => {
+                let megabytes: usize = args.parse().map_err(|_| invalid())?;
+                self.abort_thinking();
+                self.table = Arc::new(TranspositionTable::new(megabytes.max(1)));
+            }
+            "cores" => self.threads = args.parse::<usize>().map_err(|_| invalid())?.max(1),
+            "option" => match args.split_once('=') {
+                Some(("BookPath", path)) => self.book_path = path.to_string(),
+                Some(("SearchMode", "BestFirst")) => self.search_mode = SearchMode::BestFirst,
+                Some(("SearchMode", "PVS")) => self.search_mode = SearchMode::Pvs,
+                _ => return Err(invalid()),
+            },
+            "quit" => {
+                self.abort_thinking();
+                return Ok(false);
+            }
+            // Nothing to do for the rest of the handshake, the opponent and draw offers
+            "" | "xboard" | "accepted" | "rejected" | "random" | "hard" | "easy" | "computer"
+            | "name" | "rating" | "ics" | "draw" | "white" | "black" => {}
+            // Without usermove=1 being accepted, moves are sent as they are
+            _ if self.parse_move(line.trim()).is_ok() => self.user_move(line.trim())?,
+            _ => return Err(EngineError::InvalidCommand("unknown command".to_string())),
+        }
+        Ok(true)
+    }
+
+    // Moves are sent in coordinate notation, but SAN is accepted as well
+    fn parse_move(&self, m: &str) -> Result<ChessMove, EngineError> {
+        let board = self.board();
+        board
+            .parse_uci_move(m)
+            .or_else(|_| board.parse_san(m))
+            .map_err(EngineError::from)
+    }
+
+    fn user_move(&mut self, m: &str) -> Result<(), EngineError> {
+        self.abort_thinking();
+        let Ok(chess_move) = self.parse_move(m) else {
+            send(&self.out, &format!("Illegal move: {m}"));
+            return Ok(());
+        };
+        self.position().play(chess_move);
+        self.think();
+        Ok(())
+    }
+
+    // level MPS BASE INC, with the base time given in minutes or as minutes:seconds
+    fn set_level(&mut self, args: &str) -> Option<()> {
+        let [mps, base, inc] = args.split_whitespace().collect::<Vec<_>>()[..] else {
+            return None;
+        };
+        let base = match base.split_once(':') {
+            Some((minutes, seconds)) => {
+                minutes.parse::<u64>().ok()? * 60 + seconds.parse::<u64>().ok()?
+            }
+            None => base.parse::<u64>().ok()? * 60,
+        };
+        self.clock.moves_per_session = mps.parse().ok()?;
+        self.clock.base = Some(Duration::from_secs(base));
+        self.clock.increment = Duration::from_secs_f64(inc.parse::<f64>().ok()?.max(0.));
+        self.clock.move_time = None;
+        Some(())
+    }
+
+    // Starts a search if the engine is on move
+    fn think(&mut self) {
+        let position = self.position().clone();
+        let board = position.game.board();
+        let colour = board.side_to_move();
+        if self.engine != Some(colour) {
+            return;
+        }
+        if let Some(result) = result(position.game.get_game_state()) {
+            send(&self.out, &result);
+            return;
+        }
+
+        let limits = self.clock.limits(colour, board.fullmove() as u32);
+        let mode = self.search_mode;
+        if limits.depth.is_some() && mode == SearchMode::BestFirst {
+            send(
+                &self.out,
+                "# depth limits are not supported, searching by time",
+            );
+        }
+        let stop = Arc::new(AtomicBool::new(false));
+        let abort = Arc::new(AtomicBool::new(false));
+        let search = Search {
+            board,
+            history: position.game.history().to_vec(),
+            time_limit: limits.time_limit_in(colour, mode),
+            threads: self.threads,
+            book: position
+                .book_line()
+                .map(|line| (self.book_path.clone(), line)),
+            stop: Arc::clone(&stop),
+            table: Arc::clone(&self.table),
+            mode,
+            depth: limits.depth,
+            nodes: None,
+        };
+
+        let out = Arc::clone(&self.out);
+        let shared = Arc::clone(&self.position);
+        let post = self.post;
+        let aborted = Arc::clone(&abort);
+        let handle = thread::spawn(move || {
+            let res = search.run();
+            let mut position = shared.lock().expect("Failed to lock position");
+            let Some(res) = res.filter(|_| !aborted.load(Ordering::Relaxed)) else {
+                return;
+            };
+            if post {
+                // The tree search has no iteration depth, so the ply is then given as 1
+                let (ply, nodes) = res.stats.map_or((1, 0), |s| (s.depth, s.nodes));
+                let score = res.eval.and_then(|e| score(e, colour)).unwrap_or(0);
+                let centis = res.elapsed.as_millis() / 10;
+                let san = board.get_san(&res.best.board);
+                send(&out, &format!("{ply} {score} {centis} {nodes} {san}"));
+            }
+            send(&out, &format!("move {}", res.best.to_uci()));
+            position.play(res.best);
+            if let Some(result) = result(position.game.get_game_state()) {
+                send(&out, &result);
+            }
+        });
+        self.thinking = Some(Thinking {
+            handle,
+            stop,
+            abort,
+        });
+    }
+
+    // Makes the engine move at once, with the best move found so far
+    fn stop_thinking(&mut self) {
+        if let Some(thinking) = self.thinking.take() {
+            thinking.stop.store(true, Ordering::Relaxed);
+            thinking.handle.join().expect("Search thread panicked");
+        }
+    }
+
+    // Ends the search without playing its move (unless the move has already been sent)
+    fn abort_thinking(&mut self) {
+        if let Some(thinking) = &self.thinking {
+            thinking.abort.store(true, Ordering::Relaxed);
+        }
+        self.stop_thinking();
+    }
+}
+
+fn centiseconds(s: &str) -> Option<Duration> {
+    // Clocks can go negative once the time has run out
+    let centis = s.parse::<i64>().ok()?.max(0) as u64;
+    Some(Duration::from_millis(centis * 10))
+}
@@ -9,6 +9,13 @@ use engine::EngineController;
 fn main() {
     env_logger::init();
 
+    // The engine speaks UCI or XBoard unless a game in the console is asked for
+    if std::env::args().nth(1).as_deref() != Some("console") {
+        info!("Waiting for protocol");
+        engine::run_protocol().unwrap();
+        return;
+    }
+
     info!("Creating controller");
     let mut controller = EngineController::default();
     controller.set_black(engine::Player::Manual);
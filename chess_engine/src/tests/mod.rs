@@ -127,3 +127,21 @@ mod engine;
 
 #[cfg(test)]
 mod san;
+
+#[cfg(test)]
+mod uci;
+
+#[cfg(test)]
+mod xboard;
+
+#[cfg(test)]
+mod output;
+
+#[cfg(test)]
+mod transposition;
+
+#[cfg(test)]
+mod pvs;
+
+#[cfg(test)]
+mod quiescence;
@@ -0,0 +1,147 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use chess_backend::{init, Board, Colour};
+
+use crate::engine::{
+    pvs::{Pvs, PvsResult},
+    search::{Search, SearchLimits},
+    transposition::TranspositionTable,
+    utils::eval::Eval,
+    SearchMode,
+};
+
+fn search(fen: &str, depth: usize) -> PvsResult {
+    let table = TranspositionTable::new(1);
+    let stop = AtomicBool::new(false);
+    Pvs::new(Some(&table), &stop, None, None)
+        .search(Board::from_fen(fen).unwrap(), Some(depth))
+        .unwrap()
+}
+
+fn uci(board: Board, res: &PvsResult) -> String {
+    board.decode_move(res.best_move).to_uci().to_string()
+}
+
+#[test]
+fn finds_mates() {
+    init();
+    let fen = "6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1";
+    let res = search(fen, 4);
+    assert_eq!(uci(Board::from_fen(fen).unwrap(), &res), "a1a8");
+    assert_eq!(res.eval, Eval::Mate(1, Colour::White));
+    // There is no need to search deeper than the mate
+    assert_eq!(res.stats.depth, 1);
+
+    let fen = "r5k1/8/8/8/8/8/5PPP/6K1 b - - 0 1";
+    let res = search(fen, 2);
+    assert_eq!(uci(Board::from_fen(fen).unwrap(), &res), "a8a1");
+    assert_eq!(res.eval, Eval::Mate(1, Colour::Black));
+
+    // Mate in two, found at the third ply
+    let fen = "kbK5/pp6/1P6/8/8/8/8/R7 w - - 0 1";
+    let res = search(fen, 4);
+    assert_eq!(uci(Board::from_fen(fen).unwrap(), &res), "a1a6");
+    assert_eq!(res.eval, Eval::Mate(3, Colour::White));
+    assert_eq!(res.stats.depth, 3);
+    assert_eq!(res.pv.len(), 3);
+}
+
+#[test]
+fn deterministic() {
+    init();
+    let fen = "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3";
+    let first = search(fen, 3);
+    let second = search(fen, 3);
+    assert_eq!(first, second);
+    assert_eq!(first.stats.depth, 3);
+    assert_eq!(first.pv[0], first.best_move);
+
+    // A hanging queen is taken
+    let fen = "4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1";
+    let res = search(fen, 2);
+    assert_eq!(uci(Board::from_fen(fen).unwrap(), &res), "d2d5");
+}
+
+#[test]
+fn game_history() {
+    init();
+    // White is a queen down, so going back to a position from earlier in the game is best
+    let board = Board::from_fen("5k2/8/8/q7/8/8/8/7K w - - 4 30").unwrap();
+    let earlier = Board::from_fen("5k2/8/8/q7/8/8/8/6K1 b - - 3 29").unwrap();
+    let stop = AtomicBool::new(false);
+
+    let res = Pvs::new(None, &stop, None, None)
+        .search(board, Some(1))
+        .unwrap();
+    assert!(res.eval < Eval::Numeric(0.));
+
+    let mut pvs = Pvs::new(None, &stop, None, None);
+    pvs.set_history(&[earlier.hash()]);
+    let res = pvs.search(board, Some(1)).unwrap();
+    assert_eq!(uci(board, &res), "h1g1");
+    assert_eq!(res.eval, Eval::Numeric(0.));
+}
+
+#[test]
+fn limits() {
+    init();
+    let board = Board::default();
+    let stop = AtomicBool::new(false);
+
+    // The first iteration is always finished
+    let res = Pvs::new(None, &stop, None, Some(1))
+        .search(board, None)
+        .unwrap();
+    assert_eq!(res.stats.depth, 1);
+    stop.store(true, Ordering::Relaxed);
+    let res = Pvs::new(None, &stop, None, None)
+        .search(board, None)
+        .unwrap();
+    assert_eq!(res.stats.depth, 1);
+
+    // Without legal moves there is nothing to search
+    let mated = Board::from_fen("4k3/4Q3/4K3/8/8/8/8/8 b - - 0 1").unwrap();
+    assert!(Pvs::new(None, &stop, None, None)
+        .search(mated, None)
+        .is_none());
+
+    // Depth and node limits replace the default time limit
+    let limits: SearchLimits = "depth 6".parse().unwrap();
+    assert_eq!(limits.time_limit_in(Colour::White, SearchMode::Pvs), None);
+    assert!(limits
+        .time_limit_in(Colour::White, SearchMode::BestFirst)
+        .is_some());
+    let limits: SearchLimits = "depth 6 movetime 1000".parse().unwrap();
+    assert_eq!(
+        limits.time_limit_in(Colour::White, SearchMode::Pvs),
+        Some(Duration::from_millis(950))
+    );
+}
+
+#[test]
+fn selected_mode() {
+    init();
+    let search = Search {
+        board: Board::default(),
+        history: Vec::new(),
+        time_limit: None,
+        threads: 1,
+        book: None,
+        stop: Arc::new(AtomicBool::new(false)),
+        table: Arc::new(TranspositionTable::new(1)),
+        mode: SearchMode::Pvs,
+        depth: Some(2),
+        nodes: None,
+    };
+    let res = search.run().unwrap();
+    assert_eq!(res.stats.unwrap().depth, 2);
+    assert!(Board::default()
+        .parse_uci_move(&res.best.to_uci().to_string())
+        .is_ok());
+}
@@ -0,0 +1,49 @@
+use std::sync::atomic::AtomicBool;
+
+use chess_backend::{init, Board};
+
+use crate::engine::{pvs::Pvs, tree::Branch, utils::eval::Eval};
+
+fn evals(fen: &str) -> (Eval, Eval) {
+    let branch = Branch::from(Board::from_fen(fen).unwrap());
+    (branch.eval_heuristic(), branch.eval_quiescence(0))
+}
+
+#[test]
+fn captures_are_played_out() {
+    init();
+    // Quiet positions keep their static evaluation
+    let (heuristic, quiescence) =
+        evals("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1");
+    assert_eq!(heuristic, quiescence);
+
+    // A hanging queen is taken
+    let (heuristic, quiescence) = evals("4k3/8/8/3q4/4P3/8/8/4K3 w - - 0 1");
+    assert!(heuristic < Eval::Numeric(0.));
+    assert!(quiescence > Eval::Numeric(0.));
+
+    // As is a knight in the middle of an exchange
+    let (heuristic, quiescence) = evals("4k3/8/3p4/4N3/8/8/8/4K3 b - - 0 1");
+    assert!(heuristic > Eval::Numeric(0.));
+    assert!(quiescence < Eval::Numeric(0.));
+
+    // A defended pawn is not worth the queen, so the side to move stands pat
+    let (heuristic, quiescence) = evals("4k3/2p5/3p4/8/8/8/3Q4/4K3 w - - 0 1");
+    assert_eq!(heuristic, quiescence);
+}
+
+#[test]
+fn no_horizon_at_leaves() {
+    init();
+    // At depth 1, taking the pawn looks like winning it unless the recapture is seen
+    let fen = "3rk3/8/8/3p4/8/8/3Q4/4K3 w - - 0 1";
+    let board = Board::from_fen(fen).unwrap();
+    let stop = AtomicBool::new(false);
+    let res = Pvs::new(None, &stop, None, None)
+        .search(board, Some(1))
+        .unwrap();
+    assert_ne!(
+        board.decode_move(res.best_move).to_uci().to_string(),
+        "d2d5"
+    );
+}
@@ -0,0 +1,116 @@
+use std::{
+    sync::{atomic::AtomicBool, Arc},
+    thread,
+    time::Duration,
+};
+
+use chess_backend::{init, Board, Colour};
+
+use crate::engine::{
+    search::Search,
+    transposition::{Bound, TranspositionTable},
+    utils::eval::Eval,
+    SearchMode,
+};
+
+#[test]
+fn store_and_probe() {
+    let table = TranspositionTable::new(1);
+    assert_eq!(table.probe(42, 0), None);
+
+    table.store(42, 3, Bound::Lower, Eval::Numeric(25.), None, 0);
+    let entry = table.probe(42, 0).unwrap();
+    assert_eq!(entry.depth, 3);
+    assert_eq!(entry.bound, Bound::Lower);
+    assert_eq!(entry.eval, Eval::Numeric(25.));
+
+    // Shallower results do not replace deeper ones for the same position
+    table.store(42, 1, Bound::Exact, Eval::Numeric(-10.), None, 0);
+    assert_eq!(table.probe(42, 0).unwrap().depth, 3);
+    table.store(42, 3, Bound::Exact, Eval::Numeric(-10.), None, 0);
+    assert_eq!(table.probe(42, 0).unwrap().eval, Eval::Numeric(-10.));
+
+    table.clear();
+    assert_eq!(table.probe(42, 0), None);
+    assert_eq!(table.hashfull(), 0);
+}
+
+#[test]
+fn collisions() {
+    // A single slot, so that every position collides
+    let table = TranspositionTable::new(0);
+    table.store(1, 5, Bound::Exact, Eval::Numeric(1.), None, 0);
+    table.store(2, 0, Bound::Upper, Eval::Numeric(2.), None, 0);
+    assert_eq!(table.probe(1, 0), None);
+    assert_eq!(table.probe(2, 0).unwrap().eval, Eval::Numeric(2.));
+    assert_eq!(table.hashfull(), 1000);
+}
+
+#[test]
+fn mate_distance() {
+    let table = TranspositionTable::new(1);
+    // Mate five plies from the root, found three plies from the root
+    table.store(7, 2, Bound::Exact, Eval::Mate(5, Colour::Black), None, 3);
+    // Reached through a shorter line later on
+    assert_eq!(
+        table.probe(7, 1).unwrap().eval,
+        Eval::Mate(3, Colour::Black)
+    );
+    assert_eq!(
+        table.probe(7, 3).unwrap().eval,
+        Eval::Mate(5, Colour::Black)
+    );
+}
+
+#[test]
+fn shared_between_threads() {
+    let table = Arc::new(TranspositionTable::new(1));
+    let workers: Vec<_> = (0..4)
+        .map(|t| {
+            let table = Arc::clone(&table);
+            thread::spawn(move || {
+                for key in (t * 1000)..(t * 1000 + 1000) {
+                    table.store(key, 1, Bound::Exact, Eval::Numeric(key as f32), None, 0);
+                    if let Some(entry) = table.probe(key, 0) {
+                        assert_eq!(entry.eval, Eval::Numeric(key as f32));
+                    }
+                }
+            })
+        })
+        .collect();
+    for worker in workers {
+        worker.join().unwrap();
+    }
+    assert!(table.probe(3999, 0).is_some());
+}
+
+#[test]
+fn kept_between_searches() {
+    init();
+    let table = Arc::new(TranspositionTable::new(1));
+    let board = Board::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+    let search = Search {
+        board,
+        history: Vec::new(),
+        time_limit: Some(Duration::from_millis(200)),
+        threads: 2,
+        book: None,
+        stop: Arc::new(AtomicBool::new(false)),
+        table: Arc::clone(&table),
+        mode: SearchMode::BestFirst,
+        depth: None,
+        nodes: None,
+    };
+    let res = search.run().unwrap();
+    assert_eq!(res.best.to_uci().to_string(), "a1a8");
+
+    // The explored tree is stored, with the best move and the mate from the root
+    let entry = table.probe(board.hash(), 0).unwrap();
+    assert!(entry.depth >= 1);
+    assert_eq!(entry.bound, Bound::Exact);
+    assert_eq!(entry.eval, Eval::Mate(1, Colour::White));
+    assert_eq!(entry.best_move, Some(board.encode_move(&res.best)));
+    // As are the other moves, which have at least been evaluated
+    let other = board.parse_uci_move("g1f1").unwrap();
+    assert!(table.probe(other.board.hash(), 1).is_some());
+}
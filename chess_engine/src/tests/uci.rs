@@ -0,0 +1,167 @@
+use std::{thread, time::Duration};
+
+use chess_backend::{init, Board, Colour};
+
+use super::output::SharedOutput;
+use crate::engine::{
+    search::SearchLimits,
+    uci::{score, UciHandler},
+    utils::eval::Eval,
+};
+
+fn handler() -> (UciHandler<SharedOutput>, SharedOutput) {
+    let out = SharedOutput::default();
+    let mut handler = UciHandler::new(out.clone());
+    // Keep the tests independent of an opening database in the working directory
+    handler.handle("setoption name BookPath value missing.db");
+    handler.handle("setoption name Threads value 2");
+    handler.handle("setoption name Hash value 1");
+    (handler, out)
+}
+
+#[test]
+fn handshake() {
+    init();
+    let (mut handler, out) = handler();
+    handler.handle("uci");
+    handler.handle("isready");
+
+    let lines = out.lines();
+    assert!(lines[0].starts_with("id name"));
+    assert!(lines.iter().any(|l| l.starts_with("option name Threads")));
+    assert!(lines.iter().any(|l| l.starts_with("option name BookPath")));
+    assert!(lines.iter().any(|l| l.starts_with("option name Hash")));
+    assert_eq!(lines[lines.len() - 2], "uciok");
+    assert_eq!(lines[lines.len() - 1], "readyok");
+
+    // Options are checked when they are set
+    handler.handle("setoption name Threads value many");
+    handler.handle("setoption name Contempt value 10");
+    let lines = out.lines();
+    assert!(lines[lines.len() - 2..]
+        .iter()
+        .all(|l| l.starts_with("info string")));
+    assert!(!handler.handle("quit"));
+}
+
+#[test]
+fn positions() {
+    init();
+    let (mut handler, out) = handler();
+    handler.handle("position startpos moves e2e4 e7e5 g1f3");
+    assert_eq!(
+        handler.board().into_fen(),
+        "rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2"
+    );
+
+    let fen = "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1";
+    handler.handle(&format!("position fen {fen} moves e1g1"));
+    assert_eq!(
+        handler.board().into_fen(),
+        "r3k2r/8/8/8/8/8/8/R4RK1 b kq - 1 1"
+    );
+
+    // A rejected command leaves the position as it was
+    handler.handle("position startpos moves e2e5");
+    handler.handle("position fen 8/8/8 w - - 0 1");
+    assert_eq!(
+        handler.board().into_fen(),
+        "r3k2r/8/8/8/8/8/8/R4RK1 b kq - 1 1"
+    );
+    let lines = out.lines();
+    assert_eq!(lines.len(), 2);
+    assert!(lines.iter().all(|l| l.starts_with("info string")));
+}
+
+#[test]
+fn search_limits() {
+    let limits: SearchLimits = "wtime 60000 btime 30000 winc 1000 binc 0 movestogo 20"
+        .parse()
+        .unwrap();
+    assert_eq!(
+        limits.time_limit(Colour::White),
+        Some(Duration::from_millis(3950))
+    );
+    assert_eq!(
+        limits.time_limit(Colour::Black),
+        Some(Duration::from_millis(1450))
+    );
+
+    let limits: SearchLimits = "movetime 1000".parse().unwrap();
+    assert_eq!(
+        limits.time_limit(Colour::Black),
+        Some(Duration::from_millis(950))
+    );
+    let limits: SearchLimits = "infinite".parse().unwrap();
+    assert_eq!(limits.time_limit(Colour::White), None);
+    let limits: SearchLimits = "depth 6".parse().unwrap();
+    assert_eq!(limits.depth, Some(6));
+    assert!(limits.time_limit(Colour::White).is_some());
+
+    assert!("wtime".parse::<SearchLimits>().is_err());
+    assert!("movetime soon".parse::<SearchLimits>().is_err());
+    assert!("mate 3".parse::<SearchLimits>().is_err());
+}
+
+#[test]
+fn scores() {
+    assert_eq!(score(Eval::Numeric(35.4), Colour::White).unwrap(), "cp 35");
+    assert_eq!(score(Eval::Numeric(35.4), Colour::Black).unwrap(), "cp -35");
+    assert_eq!(
+        score(Eval::Mate(3, Colour::White), Colour::White).unwrap(),
+        "mate 2"
+    );
+    assert_eq!(
+        score(Eval::Mate(2, Colour::White), Colour::Black).unwrap(),
+        "mate -1"
+    );
+    assert_eq!(score(Eval::Infinity, Colour::White), None);
+}
+
+#[test]
+fn search() {
+    init();
+    let (mut handler, out) = handler();
+    handler.handle("position startpos moves e2e4");
+    handler.handle("go movetime 300");
+    // Waits for the search to reply
+    assert!(!handler.handle("quit"));
+
+    let lines = out.lines();
+    let best = lines.last().unwrap().strip_prefix("bestmove ").unwrap();
+    let board = handler.board();
+    assert!(board.parse_uci_move(best).is_ok());
+    assert!(lines[lines.len() - 2].starts_with("info score cp"));
+
+    // Stopping an infinite search still gives a move
+    handler.handle("position fen 4k3/8/8/8/8/8/8/R3K3 w - - 0 1");
+    handler.handle("go infinite");
+    handler.handle("stop");
+    let best = out.lines().pop().unwrap();
+    assert!(best.starts_with("bestmove "));
+    assert!(Board::from_fen("4k3/8/8/8/8/8/8/R3K3 w - - 0 1")
+        .unwrap()
+        .parse_uci_move(best.strip_prefix("bestmove ").unwrap())
+        .is_ok());
+
+    // Without legal moves there is no move to give
+    handler.handle("position fen 4k3/4Q3/4K3/8/8/8/8/8 b - - 0 1");
+    handler.handle("go depth 3");
+    handler.handle("stop");
+    assert_eq!(out.lines().pop().unwrap(), "bestmove 0000");
+}
+
+#[test]
+fn infinite_search_waits_for_stop() {
+    init();
+    let (mut handler, out) = handler();
+    handler.handle("setoption name SearchMode value PVS");
+    // The PVS search ends by itself on finding the mate, but may not give the move before `stop`
+    handler.handle("position fen 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+    handler.handle("go infinite");
+    thread::sleep(Duration::from_millis(300));
+    assert!(!out.lines().iter().any(|line| line.starts_with("bestmove")));
+
+    handler.handle("stop");
+    assert_eq!(out.lines().pop().unwrap(), "bestmove a1a8");
+}
@@ -0,0 +1,158 @@
+use std::time::Duration;
+
+use chess_backend::{init, Board, Colour, FinishedState, GameState, ReasonWin};
+
+use super::output::SharedOutput;
+use crate::engine::{
+    utils::eval::Eval,
+    xboard::{result, score, Clock, XBoardHandler},
+};
+
+fn handler() -> (XBoardHandler<SharedOutput>, SharedOutput) {
+    let out = SharedOutput::default();
+    let mut handler = XBoardHandler::new(out.clone());
+    // Keep the tests independent of an opening database in the working directory
+    handler.handle("option BookPath=missing.db");
+    handler.handle("cores 2");
+    handler.handle("memory 1");
+    (handler, out)
+}
+
+#[test]
+fn handshake() {
+    init();
+    let (mut handler, out) = handler();
+    handler.handle("xboard");
+    handler.handle("protover 2");
+    handler.handle("accepted usermove");
+    handler.handle("ping 7");
+    handler.handle("foo");
+
+    let lines = out.lines();
+    assert!(lines[0].starts_with("feature "));
+    assert!(lines[0].contains("usermove=1") && lines[0].ends_with("done=1"));
+    assert_eq!(lines[1], "pong 7");
+    assert!(lines[2].starts_with("Error") && lines[2].ends_with("foo"));
+    assert!(!handler.handle("quit"));
+}
+
+#[test]
+fn force_mode() {
+    init();
+    let (mut handler, out) = handler();
+    handler.handle("new");
+    handler.handle("force");
+    handler.handle("usermove e2e4");
+    handler.handle("usermove e7e5");
+    // SAN and moves without the usermove prefix are accepted as well
+    handler.handle("Nf3");
+    assert_eq!(
+        handler.board().into_fen(),
+        "rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2"
+    );
+
+    handler.handle("usermove e7e5");
+    assert_eq!(out.lines(), vec!["Illegal move: e7e5"]);
+
+    handler.handle("undo");
+    assert_eq!(
+        handler.board().into_fen(),
+        "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2"
+    );
+    handler.handle("remove");
+    assert_eq!(handler.board(), Board::default());
+    handler.handle("undo");
+    assert!(out.lines().pop().unwrap().starts_with("Error"));
+
+    let fen = "4k3/8/8/8/8/8/8/R3K3 w Q - 0 1";
+    handler.handle(&format!("setboard {fen}"));
+    handler.handle("usermove e1c1");
+    assert_eq!(handler.board().into_fen(), "4k3/8/8/8/8/8/8/2KR4 b - - 1 1");
+}
+
+#[test]
+fn engine_moves() {
+    init();
+    let (mut handler, out) = handler();
+    handler.handle("new");
+    handler.handle("post");
+    handler.handle("st 1");
+    handler.handle("usermove e2e4");
+    // Move now
+    handler.handle("?");
+
+    let lines = out.lines();
+    assert_eq!(lines.len(), 2);
+    let reply = lines[1].strip_prefix("move ").unwrap();
+    let after_e4 =
+        Board::from_fen("rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1").unwrap();
+    assert_eq!(
+        handler.board(),
+        after_e4.parse_uci_move(reply).unwrap().board
+    );
+    // ply score time nodes pv
+    assert_eq!(lines[0].split(' ').count(), 5);
+
+    // The engine takes over white and mates
+    handler.handle("setboard 6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1");
+    handler.handle("nopost");
+    handler.handle("go");
+    handler.handle("?");
+    let lines = out.lines();
+    assert_eq!(lines[lines.len() - 2], "move a1a8");
+    assert_eq!(lines[lines.len() - 1], "1-0 {White mates}");
+
+    // Quitting while thinking gives no move
+    handler.handle("new");
+    handler.handle("go");
+    assert!(!handler.handle("quit"));
+    assert_eq!(out.lines().len(), lines.len());
+}
+
+#[test]
+fn clock() {
+    let mut clock = Clock {
+        moves_per_session: 40,
+        base: Some(Duration::from_secs(300)),
+        ..Default::default()
+    };
+    let limits = clock.limits(Colour::Black, 1);
+    assert_eq!(limits.movestogo, Some(40));
+    assert_eq!(limits.btime, Some(Duration::from_secs(300)));
+    assert_eq!(clock.limits(Colour::White, 41).movestogo, Some(40));
+    assert_eq!(clock.limits(Colour::White, 45).movestogo, Some(36));
+
+    clock.own = Some(Duration::from_secs(100));
+    clock.opponent = Some(Duration::from_secs(200));
+    let limits = clock.limits(Colour::White, 10);
+    assert_eq!(limits.wtime, Some(Duration::from_secs(100)));
+    assert_eq!(limits.btime, Some(Duration::from_secs(200)));
+
+    clock.move_time = Some(Duration::from_secs(5));
+    assert_eq!(
+        clock.limits(Colour::White, 10).time_limit(Colour::White),
+        Some(Duration::from_millis(4950))
+    );
+}
+
+#[test]
+fn results_and_scores() {
+    assert_eq!(result(GameState::Ongoing), None);
+    assert_eq!(
+        result(GameState::Finished(FinishedState::Win(
+            Colour::Black,
+            ReasonWin::Checkmate
+        )))
+        .unwrap(),
+        "0-1 {Black mates}"
+    );
+    assert_eq!(score(Eval::Numeric(-50.), Colour::Black), Some(50));
+    assert_eq!(
+        score(Eval::Mate(1, Colour::White), Colour::White),
+        Some(100001)
+    );
+    assert_eq!(
+        score(Eval::Mate(4, Colour::White), Colour::Black),
+        Some(-100002)
+    );
+}
@@ -1,10 +1,12 @@
 use core::panic;
-use std::{fs, path::Path};
+use std::{fs::File, path::Path};
 
-use pgn_parser::{parse_pgn, Move, ParsedGame};
 use sqlite::{self, Connection, Value};
 
-use chess_backend::{Board, ChessError, SanMove, START_POSITION};
+use chess_backend::{
+    pgn::{PgnGame, PgnReader, PgnResult},
+    Board,
+};
 
 const INIT_COMMAND: &str = "
     CREATE TABLE moves (
@@ -42,18 +44,18 @@ fn main() {
 }
 
 fn add_from_file(conn: &Connection, file_name: &str) {
-    let pgn_data = fs::read_to_string(file_name).unwrap();
-    let games = pgn_data.split("[Event");
+    let pgn_file = File::open(file_name).unwrap();
 
     let mut game_count = 0;
-    for game_pgn in games {
-        if let Some(start_index) = game_pgn.find("\n") {
-            if let Ok(game) = parse_pgn(&game_pgn[start_index..]) {
-                add_game(&conn, game);
+    for game in PgnReader::new(pgn_file) {
+        match game {
+            Ok(game) => {
+                add_game(conn, game);
 
                 println!("Added game {game_count}");
                 game_count += 1;
             }
+            Err(e) => println!("Skipped game: {e}"),
         }
     }
 
@@ -62,102 +64,50 @@ fn add_from_file(conn: &Connection, file_name: &str) {
         .unwrap();
 }
 
-fn add_game(conn: &Connection, game: ParsedGame) {
-    let mut metadata = game.metadata.iter();
-    let white_elo = metadata
-        .find(|v| v.0 == "WhiteElo")
-        .unwrap()
-        .1
-        .parse::<usize>()
-        .unwrap();
-
-    let black_elo = metadata
-        .find(|v| v.0 == "BlackElo")
-        .unwrap()
-        .1
-        .parse::<usize>()
-        .unwrap();
-
-    let res: Vec<&str> = game.game_result.split("-").collect();
-    let white_score = result_conv(res[0]);
-    let black_score = result_conv(res[1]);
-
-    let mut parent_move = find_move(&conn, "Origin", 0).unwrap();
-    let mut move_number = 1;
-    let mut board = Board::from(START_POSITION);
-    for m in game.moves {
-        let white_move = fix_move_format(m.0);
-        parent_move = if let Ok(id) = add_move(
-            conn,
-            &white_move,
-            parent_move,
-            eval_modifier(white_score, white_elo),
-            &mut board,
-        ) {
-            id
+fn add_game(conn: &Connection, game: PgnGame) {
+    // The opening tree always starts from the standard starting position
+    if game.start != Board::default() {
+        return;
+    }
+    let (Some(white_elo), Some(black_elo)) = (elo(&game, "WhiteElo"), elo(&game, "BlackElo"))
+    else {
+        return;
+    };
+
+    let (white_score, black_score) = match game.result {
+        PgnResult::WhiteWins => (1., 0.2),
+        PgnResult::BlackWins => (0.2, 1.),
+        PgnResult::Draw => (0.5, 0.5),
+        PgnResult::Unknown => (0., 0.),
+    };
+
+    let mut parent_move = find_move(conn, "Origin", 0).unwrap();
+    // Go no deeper than 11 moves
+    for (ply, m) in game.moves().iter().take(22).enumerate() {
+        let eval = if ply % 2 == 0 {
+            eval_modifier(white_score, white_elo)
         } else {
-            break;
+            eval_modifier(black_score, black_elo)
         };
-
-        if let Some(m1) = m.1 {
-            let black_move = fix_move_format(m1);
-
-            parent_move = if let Ok(id) = add_move(
-                conn,
-                &black_move,
-                parent_move,
-                eval_modifier(black_score, black_elo),
-                &mut board,
-            ) {
-                id
-            } else {
-                break;
-            }
-        } else {
-            break;
-        }
-
-        move_number += 1;
-        // Go no deeper than 12 moves
-        if move_number == 12 {
-            break;
-        }
-    }
-}
-
-fn result_conv(res: &str) -> f32 {
-    match res {
-        "1" => 1.,
-        "1/2" => 0.5,
-        "0" => 0.2,
-        _ => 0.,
+        parent_move = add_move(conn, &m.san.to_string(), parent_move, eval, &m.board);
     }
 }
 
-fn fix_move_format(m: Move) -> String {
-    let s = m.to_string();
-    let end_index = s.find("{").unwrap();
-    s[..end_index].trim().to_string()
+fn elo(game: &PgnGame, tag: &str) -> Option<usize> {
+    game.tag(tag)?.parse::<usize>().ok()
 }
 
 fn eval_modifier(score: f32, elo: usize) -> f32 {
     score * (elo as f32) / 1000.
 }
 
-fn add_move(
-    conn: &Connection,
-    san: &str,
-    parent_move: i64,
-    eval: f32,
-    board: &mut Board,
-) -> Result<i64, ChessError> {
+fn add_move(conn: &Connection, san: &str, parent_move: i64, eval: f32, board: &Board) -> i64 {
     if let Some(id) = find_move(conn, san, parent_move) {
         conn.execute(format!(
             "UPDATE moves SET eval = eval + {eval}, frequency = frequency + 1 WHERE id = {id}"
         ))
         .unwrap();
-        board.make_san_move(SanMove::from_string(san, *board)?)?;
-        Ok(id)
+        id
     } else {
         conn.execute(format!(
             "INSERT INTO moves (parent_move, san, eval, frequency) VALUES ({parent_move}, '{san}', {eval}, 1)"
@@ -167,7 +117,6 @@ fn add_move(
         ));
 
         let id = find_move(conn, san, parent_move).unwrap();
-        board.make_san_move(SanMove::from_string(san, *board)?)?;
         let fen = board.into_fen();
         conn.execute(format!(
             "INSERT INTO boards (id, fen) VALUES ({id}, '{fen}')"
@@ -188,7 +137,7 @@ fn add_move(
         .expect(&format!(
             "Failed to update children count for parent move with id {parent_move}"
         ));
-        Ok(id)
+        id
     }
 }
 